@@ -1,27 +1,472 @@
 // Configuration commands
 
+use crate::llm::tools::ToolCategory;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::AppHandle;
+
+/// LLM provider (API protocol) selector
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProviderKind {
+    /// OpenAI-compatible /chat/completions
+    #[default]
+    #[serde(rename = "openai")]
+    OpenAi,
+    /// Anthropic Messages API
+    #[serde(rename = "anthropic")]
+    Anthropic,
+    /// Local Ollama / llama.cpp server (OpenAI-compatible, no key required)
+    #[serde(rename = "local")]
+    Local,
+}
+
+/// How tools are offered to the model
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolCallMode {
+    /// Native function calling (`tools` / `tool_calls`)
+    #[default]
+    Native,
+    /// JSON or XML tool invocations parsed from the response text
+    Text,
+}
 
 /// API configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApiConfig {
+    #[serde(default)]
+    pub provider: ProviderKind,
     pub endpoint: String,
     pub api_key: String,
     pub model: String,
     pub supports_vision: bool,
     pub max_tokens: u32,
     pub temperature: f32,
+    /// Stream responses token by token (SSE)
+    #[serde(default = "default_stream")]
+    pub stream: bool,
+    #[serde(default)]
+    pub tool_mode: ToolCallMode,
+    /// Per-request timeout (for streams: maximum silence between chunks)
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    /// Retry policy for rate limits, server errors and timeouts
+    #[serde(default)]
+    pub retry: RetryConfig,
+}
+
+/// Exponential backoff settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// Retries after the first attempt (0 disables retrying)
+    pub max_retries: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    pub multiplier: f64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff_ms: 1000,
+            max_backoff_ms: 30_000,
+            multiplier: 2.0,
+        }
+    }
+}
+
+impl ApiConfig {
+    /// Whether requests need an API key (local servers usually run without one)
+    pub fn requires_api_key(&self) -> bool {
+        self.provider != ProviderKind::Local
+    }
+}
+
+fn default_stream() -> bool {
+    true
+}
+
+fn default_timeout_secs() -> u64 {
+    120
+}
+
+fn default_connect_timeout_secs() -> u64 {
+    10
 }
 
 impl Default for ApiConfig {
     fn default() -> Self {
         Self {
+            provider: ProviderKind::OpenAi,
             endpoint: "https://api.openai.com/v1".to_string(),
             api_key: String::new(),
             model: "gpt-4o".to_string(),
             supports_vision: true,
             max_tokens: 4096,
             temperature: 0.7,
+            stream: default_stream(),
+            tool_mode: ToolCallMode::Native,
+            timeout_secs: default_timeout_secs(),
+            connect_timeout_secs: default_connect_timeout_secs(),
+            retry: RetryConfig::default(),
+        }
+    }
+}
+
+/// Conversation history limits for the agent loop
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContextConfig {
+    /// Screenshots kept inline; older ones become a text placeholder
+    pub max_screenshots: usize,
+    /// Estimated prompt size the history should stay under
+    pub token_budget: usize,
+    /// Fraction of the budget at which old steps are summarized
+    pub compact_threshold: f32,
+    /// Most recent messages never folded into the summary
+    pub keep_recent_messages: usize,
+}
+
+impl Default for ContextConfig {
+    fn default() -> Self {
+        Self {
+            max_screenshots: 2,
+            token_budget: 60_000,
+            compact_threshold: 0.8,
+            keep_recent_messages: 8,
+        }
+    }
+}
+
+/// Screen check around input actions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VerificationConfig {
+    /// Compare the screen before and after mouse, keyboard and scroll actions
+    pub enabled: bool,
+    /// Wait for the UI to settle before the second capture
+    pub settle_ms: u64,
+    /// Per-channel difference at which a pixel counts as changed
+    pub pixel_threshold: u8,
+    /// Changed regions listed in the summary
+    pub max_regions: usize,
+}
+
+impl Default for VerificationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            settle_ms: 300,
+            pixel_threshold: 24,
+            max_regions: 3,
+        }
+    }
+}
+
+/// Detection of runs that repeat themselves without progress
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StallConfig {
+    pub enabled: bool,
+    /// Identical tool calls in a row that count as a loop
+    pub repeat_limit: usize,
+    /// A-B cycles in a row that count as oscillation
+    pub oscillation_cycles: usize,
+    /// Input actions without a visible screen change
+    pub unchanged_limit: usize,
+    /// Corrections (notes, then a re-plan in plan mode) before the run is aborted
+    pub max_interventions: u32,
+}
+
+impl Default for StallConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            repeat_limit: 3,
+            oscillation_cycles: 2,
+            unchanged_limit: 4,
+            max_interventions: 2,
+        }
+    }
+}
+
+/// Transcripts of past runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TranscriptConfig {
+    /// Transcripts older than this are deleted at startup, along with the
+    /// screenshots no other transcript uses (0 = keep everything)
+    pub retention_days: u64,
+}
+
+impl Default for TranscriptConfig {
+    fn default() -> Self {
+        Self { retention_days: 30 }
+    }
+}
+
+/// Tools a run may use
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolProfile {
+    /// Only tools of these categories (empty = every category)
+    pub categories: Vec<ToolCategory>,
+    /// Tools left out by name
+    pub disabled: Vec<String>,
+    /// Offer tools that send mouse and keyboard input
+    pub allow_input: bool,
+}
+
+impl Default for ToolProfile {
+    fn default() -> Self {
+        Self {
+            categories: Vec::new(),
+            disabled: Vec::new(),
+            allow_input: true,
+        }
+    }
+}
+
+/// Named tool profiles (a run picks one, or uses the default)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolsConfig {
+    pub default_profile: String,
+    pub profiles: BTreeMap<String, ToolProfile>,
+}
+
+impl Default for ToolsConfig {
+    fn default() -> Self {
+        let observe = ToolProfile {
+            allow_input: false,
+            ..ToolProfile::default()
+        };
+        Self {
+            default_profile: "full".to_string(),
+            profiles: BTreeMap::from([
+                ("full".to_string(), ToolProfile::default()),
+                ("observe".to_string(), observe),
+            ]),
+        }
+    }
+}
+
+impl ToolsConfig {
+    /// Profile by name (None = the default profile)
+    pub fn profile(&self, name: Option<&str>) -> anyhow::Result<&ToolProfile> {
+        let name = name.unwrap_or(&self.default_profile);
+        self.profiles
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown tool profile: {}", name))
+    }
+}
+
+/// Limits that stop an agent run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunBudget {
+    /// LLM calls of the main agent
+    pub max_iterations: usize,
+    /// Wall-clock limit for the whole run (None = unlimited)
+    pub max_duration_secs: Option<u64>,
+    /// Calls allowed per tool name (tools not listed are unlimited)
+    pub max_tool_calls: BTreeMap<String, u32>,
+    /// How deeply sub-agents may be nested (0 disables sub-agents)
+    pub max_sub_agent_depth: usize,
+    /// LLM calls of each sub-agent run
+    pub sub_agent_max_iterations: usize,
+}
+
+impl Default for RunBudget {
+    fn default() -> Self {
+        Self {
+            max_iterations: 20,
+            max_duration_secs: None,
+            max_tool_calls: BTreeMap::new(),
+            max_sub_agent_depth: 1,
+            sub_agent_max_iterations: 10,
+        }
+    }
+}
+
+/// Per-run changes to the configured budget (unset fields keep the config value)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunBudgetOverride {
+    pub max_iterations: Option<usize>,
+    pub max_duration_secs: Option<u64>,
+    /// Merged into the configured per-tool limits
+    pub max_tool_calls: Option<BTreeMap<String, u32>>,
+    pub max_sub_agent_depth: Option<usize>,
+    pub sub_agent_max_iterations: Option<usize>,
+}
+
+impl RunBudgetOverride {
+    /// Budget for one run: `base` with the set fields replaced
+    pub fn apply(&self, base: &RunBudget) -> RunBudget {
+        let mut budget = base.clone();
+        if let Some(max) = self.max_iterations {
+            budget.max_iterations = max;
+        }
+        if let Some(secs) = self.max_duration_secs {
+            budget.max_duration_secs = Some(secs);
+        }
+        if let Some(limits) = &self.max_tool_calls {
+            budget.max_tool_calls.extend(limits.clone());
+        }
+        if let Some(depth) = self.max_sub_agent_depth {
+            budget.max_sub_agent_depth = depth;
+        }
+        if let Some(max) = self.sub_agent_max_iterations {
+            budget.sub_agent_max_iterations = max;
+        }
+        budget
+    }
+}
+
+/// What the approval policy does with a tool call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyAction {
+    Allow,
+    /// Ask the user and wait for an answer
+    Ask,
+    Deny,
+}
+
+/// Approval rule - matches when every condition that is set matches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyRule {
+    pub action: PolicyAction,
+    /// Tool name (e.g. "keyboard_press")
+    #[serde(default)]
+    pub tool: Option<String>,
+    /// Key combination of a keyboard_press call (e.g. "win+r", order-insensitive)
+    #[serde(default)]
+    pub keys: Option<String>,
+    /// Regex matched against text typed by keyboard_type
+    #[serde(default)]
+    pub text_pattern: Option<String>,
+    /// Regex matched against the title of the target window
+    #[serde(default)]
+    pub window_pattern: Option<String>,
+    /// Shown to the user and recorded with the decision
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl PolicyRule {
+    fn keys(action: PolicyAction, keys: &str, description: &str) -> Self {
+        Self {
+            action,
+            tool: None,
+            keys: Some(keys.to_string()),
+            text_pattern: None,
+            window_pattern: None,
+            description: Some(description.to_string()),
+        }
+    }
+}
+
+/// Human-in-the-loop approval of tool calls
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApprovalConfig {
+    pub enabled: bool,
+    /// Action when no rule matches
+    pub default_action: PolicyAction,
+    /// How long to wait for the user before rejecting
+    pub timeout_secs: u64,
+    /// Checked in order; the first matching rule decides
+    pub rules: Vec<PolicyRule>,
+}
+
+impl Default for ApprovalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            default_action: PolicyAction::Allow,
+            timeout_secs: 60,
+            rules: vec![
+                PolicyRule::keys(PolicyAction::Deny, "ctrl+alt+delete", "Security screen"),
+                PolicyRule::keys(PolicyAction::Ask, "win+r", "Opens the Run dialog"),
+                PolicyRule::keys(PolicyAction::Ask, "alt+f4", "Closes the active window"),
+                PolicyRule::keys(PolicyAction::Ask, "win+l", "Locks the computer"),
+                PolicyRule {
+                    action: PolicyAction::Ask,
+                    tool: Some("keyboard_type".to_string()),
+                    keys: None,
+                    text_pattern: Some(
+                        r"(?i)\b(rm\s+-rf|del\s+/[sfq]|format\s+[a-z]:|shutdown|reg\s+delete)"
+                            .to_string(),
+                    ),
+                    window_pattern: None,
+                    description: Some("Destructive shell command".to_string()),
+                },
+            ],
+        }
+    }
+}
+
+/// Price of a model in USD per million tokens
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelPrice {
+    /// Model name or prefix (the longest matching prefix wins)
+    pub model: String,
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+}
+
+impl ModelPrice {
+    fn new(model: &str, input_per_mtok: f64, output_per_mtok: f64) -> Self {
+        Self {
+            model: model.to_string(),
+            input_per_mtok,
+            output_per_mtok,
+        }
+    }
+}
+
+/// Usage accounting and budget settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageConfig {
+    pub prices: Vec<ModelPrice>,
+    /// Stop a task once it has spent this much (USD)
+    pub task_budget_usd: Option<f64>,
+    /// Stop tasks once today's total spend reaches this much (USD)
+    pub daily_budget_usd: Option<f64>,
+}
+
+impl UsageConfig {
+    /// Price entry for a model (longest prefix match)
+    pub fn price_for(&self, model: &str) -> Option<&ModelPrice> {
+        self.prices
+            .iter()
+            .filter(|p| model.starts_with(&p.model))
+            .max_by_key(|p| p.model.len())
+    }
+}
+
+impl Default for UsageConfig {
+    fn default() -> Self {
+        Self {
+            prices: vec![
+                ModelPrice::new("gpt-4o", 2.5, 10.0),
+                ModelPrice::new("gpt-4o-mini", 0.15, 0.6),
+                ModelPrice::new("gpt-4.1", 2.0, 8.0),
+                ModelPrice::new("gpt-4.1-mini", 0.4, 1.6),
+                ModelPrice::new("gpt-4.1-nano", 0.1, 0.4),
+                ModelPrice::new("claude-opus-4", 15.0, 75.0),
+                ModelPrice::new("claude-sonnet-4", 3.0, 15.0),
+                ModelPrice::new("claude-3-5-haiku", 0.8, 4.0),
+            ],
+            task_budget_usd: None,
+            daily_budget_usd: None,
         }
     }
 }
@@ -33,6 +478,29 @@ pub struct AppConfig {
     pub language: String,
     pub theme: String,
     pub global_shortcut: String,
+    /// Aborts all input and cancels every agent run
+    #[serde(default = "default_emergency_stop_shortcut")]
+    pub emergency_stop_shortcut: String,
+    #[serde(default)]
+    pub usage: UsageConfig,
+    #[serde(default)]
+    pub context: ContextConfig,
+    #[serde(default)]
+    pub budget: RunBudget,
+    #[serde(default)]
+    pub approval: ApprovalConfig,
+    #[serde(default)]
+    pub verification: VerificationConfig,
+    #[serde(default)]
+    pub stall: StallConfig,
+    #[serde(default)]
+    pub tools: ToolsConfig,
+    #[serde(default)]
+    pub transcripts: TranscriptConfig,
+}
+
+fn default_emergency_stop_shortcut() -> String {
+    "Shift+Alt+X".to_string()
 }
 
 impl Default for AppConfig {
@@ -42,6 +510,15 @@ impl Default for AppConfig {
             language: "ko".to_string(),
             theme: "dark".to_string(),
             global_shortcut: "Shift+Alt+A".to_string(),
+            emergency_stop_shortcut: default_emergency_stop_shortcut(),
+            usage: UsageConfig::default(),
+            context: ContextConfig::default(),
+            budget: RunBudget::default(),
+            approval: ApprovalConfig::default(),
+            verification: VerificationConfig::default(),
+            stall: StallConfig::default(),
+            tools: ToolsConfig::default(),
+            transcripts: TranscriptConfig::default(),
         }
     }
 }
@@ -52,16 +529,22 @@ pub async fn get_config() -> Result<AppConfig, String> {
     crate::config::storage::load_config().map_err(|e| e.to_string())
 }
 
-/// Save configuration
+/// Save configuration and re-register the global shortcuts
 #[tauri::command]
-pub async fn save_config(config: AppConfig) -> Result<(), String> {
-    crate::config::storage::save_config(&config).map_err(|e| e.to_string())
+pub async fn save_config(app: AppHandle, config: AppConfig) -> Result<(), String> {
+    crate::config::storage::save_config(&config).map_err(|e| e.to_string())?;
+    crate::config::shortcuts::register_shortcuts(&app, &config).map_err(|e| {
+        format!(
+            "Configuration saved, but global shortcuts could not be registered: {}",
+            e
+        )
+    })
 }
 
 /// Test API connection
 #[tauri::command]
 pub async fn test_api_connection(config: ApiConfig) -> Result<String, String> {
-    crate::llm::client::test_connection(&config)
+    crate::llm::provider::test_connection(&config)
         .await
         .map_err(|e| e.to_string())
 }
@@ -4,7 +4,9 @@ use crate::guides::storage::{
     get_guide_index, list_guides, preview_guide, read_guide, save_guide, GuideEntry,
     GuideIndexEntry,
 };
+use crate::input::cancel::CancelToken;
 use crate::llm::agents::GuideSearchAgentTool;
+use crate::llm::provider::create_metered_provider;
 use crate::llm::tools::Tool;
 use crate::llm::types::ToolContext;
 use serde::{Deserialize, Serialize};
@@ -53,7 +55,16 @@ pub async fn guide_search(query: String) -> Result<String, String> {
     let config = crate::config::storage::load_config().map_err(|e| e.to_string())?;
 
     let ctx = ToolContext {
-        api_config: config.api,
+        provider: create_metered_provider(&config).0,
+        tool_mode: config.api.tool_mode,
+        cancel: CancelToken::new(),
+        budget: config.budget.clone(),
+        depth: 0,
+        deadline: None,
+        dry_run: false,
+        plan: None,
+        input: None,
+        held: Default::default(),
     };
 
     let tool = GuideSearchAgentTool;
@@ -76,12 +87,11 @@ pub async fn guide_search(query: String) -> Result<String, String> {
 #[tauri::command]
 pub async fn guide_create(request: CreateGuideRequest) -> Result<CreateGuideResponse, String> {
     use crate::config::storage::load_config;
-    use crate::llm::client::chat_completion;
-    use crate::llm::types::{Message, MessageContent};
+    use crate::llm::types::Message;
 
     let config = load_config().map_err(|e| e.to_string())?;
 
-    if config.api.api_key.is_empty() {
+    if config.api.requires_api_key() && config.api.api_key.is_empty() {
         return Err("API key not configured".to_string());
     }
 
@@ -130,19 +140,13 @@ Rules:
             + &chrono::Local::now().format("%Y-%m-%d").to_string();
 
     let messages = vec![
-        Message {
-            role: "system".to_string(),
-            content: MessageContent::Text(system_prompt),
-            tool_call_id: None,
-        },
-        Message {
-            role: "user".to_string(),
-            content: MessageContent::Text(request.user_input),
-            tool_call_id: None,
-        },
+        Message::system(system_prompt),
+        Message::user(request.user_input),
     ];
 
-    let response = chat_completion(&config.api, messages, None)
+    let response = create_metered_provider(&config)
+        .0
+        .chat(messages, None)
         .await
         .map_err(|e| e.to_string())?;
 
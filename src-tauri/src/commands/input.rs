@@ -1,9 +1,12 @@
 // Input automation commands (mouse & keyboard)
 
+use crate::input::backend;
+use crate::input::cancel::CancelToken;
+use crate::input::keys::parse_keys;
 use serde::{Deserialize, Serialize};
 
 /// Mouse button type
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum MouseButton {
     Left,
@@ -14,18 +17,13 @@ pub enum MouseButton {
 /// Move mouse to coordinates with smooth animation
 #[tauri::command]
 pub async fn mouse_move(x: i32, y: i32, smooth: Option<bool>) -> Result<(), String> {
-    #[cfg(windows)]
-    {
-        let use_smooth = smooth.unwrap_or(true);
-        if use_smooth {
-            crate::input::mouse::smooth_move(x, y, 300).map_err(|e| e.to_string())
-        } else {
-            crate::input::mouse::instant_move(x, y).map_err(|e| e.to_string())
-        }
-    }
-    #[cfg(not(windows))]
-    {
-        Err("Mouse control is only supported on Windows".to_string())
+    let input = backend::native().map_err(|e| e.to_string())?;
+    let use_smooth = smooth.unwrap_or(true);
+    if use_smooth {
+        crate::input::mouse::smooth_move(input.as_ref(), x, y, 300, &CancelToken::new())
+            .map_err(|e| e.to_string())
+    } else {
+        crate::input::mouse::instant_move(input.as_ref(), x, y).map_err(|e| e.to_string())
     }
 }
 
@@ -37,41 +35,34 @@ pub async fn mouse_click(
     button: Option<MouseButton>,
     double: Option<bool>,
 ) -> Result<(), String> {
-    #[cfg(windows)]
-    {
-        let btn = button.unwrap_or(MouseButton::Left);
-        let is_double = double.unwrap_or(false);
-        crate::input::mouse::click(x, y, btn, is_double).map_err(|e| e.to_string())
-    }
-    #[cfg(not(windows))]
-    {
-        Err("Mouse control is only supported on Windows".to_string())
-    }
+    let input = backend::native().map_err(|e| e.to_string())?;
+    let btn = button.unwrap_or(MouseButton::Left);
+    let is_double = double.unwrap_or(false);
+    crate::input::mouse::click(input.as_ref(), x, y, btn, is_double, &CancelToken::new())
+        .map_err(|e| e.to_string())
 }
 
 /// Type text
 #[tauri::command]
 pub async fn keyboard_type(text: &str, delay_ms: Option<u64>) -> Result<(), String> {
-    #[cfg(windows)]
-    {
-        let delay = delay_ms.unwrap_or(30);
-        crate::input::keyboard::type_text(text, delay).map_err(|e| e.to_string())
-    }
-    #[cfg(not(windows))]
-    {
-        Err("Keyboard control is only supported on Windows".to_string())
-    }
+    let input = backend::native().map_err(|e| e.to_string())?;
+    let delay = delay_ms.unwrap_or(30);
+    crate::input::keyboard::type_text(input.as_ref(), text, delay, &CancelToken::new())
+        .map_err(|e| e.to_string())
 }
 
-/// Press key or key combination
+/// Press keys: a key string ("ctrl+k ctrl+s") or an array of key names pressed together
 #[tauri::command]
-pub async fn keyboard_press(keys: Vec<String>) -> Result<(), String> {
-    #[cfg(windows)]
-    {
-        crate::input::keyboard::press_keys(&keys).map_err(|e| e.to_string())
-    }
-    #[cfg(not(windows))]
-    {
-        Err("Keyboard control is only supported on Windows".to_string())
-    }
+pub async fn keyboard_press(keys: serde_json::Value) -> Result<(), String> {
+    let chords = parse_keys(&keys).map_err(|e| e.to_string())?;
+    let input = backend::native().map_err(|e| e.to_string())?;
+    crate::input::keyboard::press_chords(input.as_ref(), &chords, &CancelToken::new())
+        .map_err(|e| e.to_string())
+}
+
+/// Emergency stop - abort all input in progress and cancel every agent run
+#[tauri::command]
+pub async fn emergency_stop() -> Result<(), String> {
+    crate::input::cancel::emergency_stop();
+    Ok(())
 }
@@ -1,18 +1,24 @@
 // LLM interaction commands
 
-use crate::llm::runner::AgentResult;
+use crate::commands::config::RunBudgetOverride;
+use crate::llm::runner::approval;
+use crate::llm::runner::control::{self, RunControl};
+use crate::llm::runner::events::{new_run_id, subscribe, AgentEventEnvelope, EventSink};
+use crate::llm::runner::{AgentResult, RunOptions};
 use crate::llm::types::ActionResponse;
+use std::sync::Arc;
+use tauri::ipc::Channel;
 use tauri::{AppHandle, Manager};
 
-/// Send message to LLM and run full agent loop
-/// Returns complete execution result with all steps
-#[tauri::command]
-pub async fn send_message(
+/// Run the agent loop for one message, hiding the main window while it works
+async fn execute_run(
     app: AppHandle,
-    message: &str,
-    include_screen: Option<bool>,
+    events: EventSink,
+    control: Arc<RunControl>,
+    message: String,
+    options: RunOptions,
 ) -> Result<AgentResult, String> {
-    let with_screen = include_screen.unwrap_or(true);
+    let with_screen = options.include_screen;
 
     // Minimize main window before screen capture so it doesn't appear in screenshot
     if with_screen {
@@ -23,9 +29,10 @@ pub async fn send_message(
         }
     }
 
-    let result = crate::llm::runner::run_agent_loop(message, with_screen)
+    let result = crate::llm::runner::run_agent_loop(&message, &options, &events, &control)
         .await
         .map_err(|e| e.to_string());
+    control::unregister(events.run_id());
 
     // Restore main window after processing
     if with_screen {
@@ -38,6 +45,128 @@ pub async fn send_message(
     result
 }
 
+/// Send message to LLM and run full agent loop
+/// Returns complete execution result with all steps
+/// The run's transcript is written to `transcripts/<run_id>.jsonl` (see `export_run`)
+/// Progress is emitted as `agent-event` (and tokens as `llm-stream`) while the loop runs;
+/// pass `run_id` to choose the ID those events carry
+/// `budget` overrides the configured run budget for this call only
+/// `session_id` continues a saved conversation; without it a new session is started
+/// `dry_run` makes mouse, keyboard and scroll tools describe their effect (shown on the
+/// overlay) instead of performing it; the planned steps are returned but not saved
+/// `plan` has a planner sub-agent break the request into steps first; the agent reports
+/// progress on them (`plan_updated` event) and the final plan is returned with the result
+/// `tool_profile` picks the tools offered to the agent from the configured profiles
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn send_message(
+    app: AppHandle,
+    message: &str,
+    include_screen: Option<bool>,
+    run_id: Option<String>,
+    budget: Option<RunBudgetOverride>,
+    session_id: Option<String>,
+    dry_run: Option<bool>,
+    plan: Option<bool>,
+    tool_profile: Option<String>,
+) -> Result<AgentResult, String> {
+    let run_id = run_id.unwrap_or_else(new_run_id);
+    let events = EventSink::new(run_id.clone(), Some(app.clone())).with_transcript();
+    let control = control::register(&run_id);
+    let options = RunOptions {
+        include_screen: include_screen.unwrap_or(true),
+        budget,
+        session_id,
+        dry_run: dry_run.unwrap_or(false),
+        plan: plan.unwrap_or(false),
+        tool_profile,
+    };
+    execute_run(app, events, control, message.to_string(), options).await
+}
+
+/// Start the agent loop in the background and return its run ID immediately
+/// Follow the run with `subscribe_agent_run` or the `agent-event` event
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn start_agent_run(
+    app: AppHandle,
+    message: String,
+    include_screen: Option<bool>,
+    budget: Option<RunBudgetOverride>,
+    session_id: Option<String>,
+    dry_run: Option<bool>,
+    plan: Option<bool>,
+    tool_profile: Option<String>,
+) -> Result<String, String> {
+    let run_id = new_run_id();
+    let options = RunOptions {
+        include_screen: include_screen.unwrap_or(true),
+        budget,
+        session_id,
+        dry_run: dry_run.unwrap_or(false),
+        plan: plan.unwrap_or(false),
+        tool_profile,
+    };
+
+    // Register the run before returning so an immediate subscribe or cancel finds it
+    let events = EventSink::new(run_id.clone(), Some(app.clone())).with_transcript();
+    let control = control::register(&run_id);
+
+    tauri::async_runtime::spawn(execute_run(app, events, control, message, options));
+
+    Ok(run_id)
+}
+
+/// Receive the events of a run over a channel
+/// Events emitted before subscribing are replayed first
+#[tauri::command]
+pub async fn subscribe_agent_run(
+    run_id: String,
+    on_event: Channel<AgentEventEnvelope>,
+) -> Result<(), String> {
+    if subscribe(&run_id, on_event) {
+        Ok(())
+    } else {
+        Err(format!("Unknown run: {}", run_id))
+    }
+}
+
+/// Stop a run at its next checkpoint (aborts a wait, mouse move or typing in progress)
+#[tauri::command]
+pub async fn cancel_agent_run(run_id: String) -> Result<(), String> {
+    run_control(&run_id)?.cancel();
+    Ok(())
+}
+
+/// Hold a run before its next LLM call or tool call
+#[tauri::command]
+pub async fn pause_agent_run(run_id: String) -> Result<(), String> {
+    run_control(&run_id)?.pause();
+    Ok(())
+}
+
+/// Continue a paused run
+#[tauri::command]
+pub async fn resume_agent_run(run_id: String) -> Result<(), String> {
+    run_control(&run_id)?.resume();
+    Ok(())
+}
+
+/// Answer an approval request of a running agent
+#[tauri::command]
+pub async fn respond_approval(request_id: String, approved: bool) -> Result<(), String> {
+    if approval::respond(&request_id, approved) {
+        Ok(())
+    } else {
+        Err(format!("No pending approval request: {}", request_id))
+    }
+}
+
+/// Look up the control handle of a run in progress
+fn run_control(run_id: &str) -> Result<Arc<RunControl>, String> {
+    control::get(run_id).ok_or_else(|| format!("No active run: {}", run_id))
+}
+
 /// Legacy single-turn message (for backward compatibility)
 #[allow(dead_code)]
 #[tauri::command]
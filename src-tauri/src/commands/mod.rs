@@ -6,6 +6,9 @@ pub mod input;
 pub mod llm;
 pub mod overlay;
 pub mod screen;
+pub mod sessions;
+pub mod transcripts;
+pub mod usage;
 
 /// Simple greet command for testing
 #[tauri::command]
@@ -14,6 +14,22 @@ pub enum OverlayEvent {
     #[serde(rename = "click")]
     Click { x: i32, y: i32, button: String },
 
+    #[serde(rename = "mouse_down")]
+    MouseDown { x: i32, y: i32, button: String },
+
+    #[serde(rename = "mouse_up")]
+    MouseUp { x: i32, y: i32, button: String },
+
+    /// Path of a drag, drawn from `from` to `to`
+    #[serde(rename = "drag")]
+    Drag {
+        from_x: i32,
+        from_y: i32,
+        to_x: i32,
+        to_y: i32,
+        button: String,
+    },
+
     #[serde(rename = "status")]
     Status {
         status: String,
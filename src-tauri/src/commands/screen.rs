@@ -1,5 +1,6 @@
 // Screen capture and UI automation commands
 
+use crate::screen::backend;
 use serde::{Deserialize, Serialize};
 
 /// UI element information
@@ -26,26 +27,14 @@ pub struct BoundingRect {
 /// Capture screen as base64 encoded PNG
 #[tauri::command]
 pub async fn capture_screen() -> Result<String, String> {
-    #[cfg(windows)]
-    {
-        crate::screen::capture::capture_screen_base64().map_err(|e| e.to_string())
-    }
-    #[cfg(not(windows))]
-    {
-        Err("Screen capture is only supported on Windows".to_string())
-    }
+    let screen = backend::native().map_err(|e| e.to_string())?;
+    screen.capture().map_err(|e| e.to_string())
 }
 
 /// Get UI element tree of the active window
 #[tauri::command]
 pub async fn get_ui_tree(max_depth: Option<usize>) -> Result<UIElement, String> {
-    #[cfg(windows)]
-    {
-        let depth = max_depth.unwrap_or(3);
-        crate::screen::ui_automation::get_active_window_tree(depth).map_err(|e| e.to_string())
-    }
-    #[cfg(not(windows))]
-    {
-        Err("UI Automation is only supported on Windows".to_string())
-    }
+    let screen = backend::native().map_err(|e| e.to_string())?;
+    let depth = max_depth.unwrap_or(3);
+    screen.window_tree(depth).map_err(|e| e.to_string())
 }
@@ -0,0 +1,28 @@
+// Session commands - list, load, rename and delete conversations
+
+use crate::sessions::storage;
+use crate::sessions::{Session, SessionSummary};
+
+/// List saved sessions (most recent first)
+#[tauri::command]
+pub async fn list_sessions() -> Result<Vec<SessionSummary>, String> {
+    storage::list_sessions().map_err(|e| e.to_string())
+}
+
+/// Load a session with its message history and runs
+#[tauri::command]
+pub async fn load_session(id: String) -> Result<Session, String> {
+    storage::load_session(&id).map_err(|e| e.to_string())
+}
+
+/// Rename a session
+#[tauri::command]
+pub async fn rename_session(id: String, title: String) -> Result<SessionSummary, String> {
+    storage::rename_session(&id, &title).map_err(|e| e.to_string())
+}
+
+/// Delete a session
+#[tauri::command]
+pub async fn delete_session(id: String) -> Result<(), String> {
+    storage::delete_session(&id).map_err(|e| e.to_string())
+}
@@ -0,0 +1,14 @@
+// Transcript commands - export recorded runs as reports
+
+use crate::transcripts::export::{self, ExportFormat};
+use crate::transcripts::recorder::get_transcripts_dir;
+
+/// Export a run's transcript as a self-contained HTML or Markdown report
+/// Writes to `transcripts/exports/<run_id>.<ext>` and returns the path
+#[tauri::command]
+pub async fn export_run(run_id: String, format: ExportFormat) -> Result<String, String> {
+    get_transcripts_dir()
+        .and_then(|dir| export::export_run(&dir, &run_id, format))
+        .map(|path| path.to_string_lossy().into_owned())
+        .map_err(|e| e.to_string())
+}
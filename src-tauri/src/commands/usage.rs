@@ -0,0 +1,60 @@
+// Usage commands - query token usage and spend
+
+use crate::usage::ledger::{load_ledger, ModelUsage};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Usage for one day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyUsage {
+    pub date: String,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub requests: u64,
+    pub cost_usd: f64,
+    pub models: BTreeMap<String, ModelUsage>,
+}
+
+/// Usage over a range of days (newest first)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageReport {
+    pub days: Vec<DailyUsage>,
+    pub total_cost_usd: f64,
+    pub total_tokens: u64,
+}
+
+/// Get usage for the last `days` days (default 30, including today)
+#[tauri::command]
+pub async fn get_usage(days: Option<u32>) -> Result<UsageReport, String> {
+    let ledger = load_ledger().map_err(|e| e.to_string())?;
+
+    let days = days.unwrap_or(30).max(1) as i64;
+    let since = (chrono::Local::now() - chrono::Duration::days(days - 1))
+        .format("%Y-%m-%d")
+        .to_string();
+
+    // Dates are YYYY-MM-DD, so string order is date order
+    let daily: Vec<DailyUsage> = ledger
+        .days
+        .into_iter()
+        .rev()
+        .filter(|(date, _)| *date >= since)
+        .map(|(date, models)| DailyUsage {
+            date,
+            prompt_tokens: models.values().map(|m| m.prompt_tokens).sum(),
+            completion_tokens: models.values().map(|m| m.completion_tokens).sum(),
+            requests: models.values().map(|m| m.requests).sum(),
+            cost_usd: models.values().map(|m| m.cost_usd).sum(),
+            models,
+        })
+        .collect();
+
+    Ok(UsageReport {
+        total_cost_usd: daily.iter().map(|d| d.cost_usd).sum(),
+        total_tokens: daily
+            .iter()
+            .map(|d| d.prompt_tokens + d.completion_tokens)
+            .sum(),
+        days: daily,
+    })
+}
@@ -1,3 +1,4 @@
 // Configuration management module
 
+pub mod shortcuts;
 pub mod storage;
@@ -0,0 +1,66 @@
+// Global shortcuts - registers the configured hotkeys with the global-shortcut plugin
+
+use crate::commands::config::AppConfig;
+use anyhow::Result;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+/// Tauri event emitted when the emergency stop is triggered
+pub const EMERGENCY_STOP_EVENT: &str = "emergency-stop";
+
+/// (Re-)register the global shortcuts from the config
+/// - `global_shortcut` shows and focuses the main window
+/// - `emergency_stop_shortcut` aborts all input and cancels every agent run
+///
+/// Each shortcut is registered on its own so one bad accelerator doesn't
+/// leave the app without the others; the failures are returned together.
+pub fn register_shortcuts(app: &AppHandle, config: &AppConfig) -> Result<()> {
+    let shortcuts = app.global_shortcut();
+    let mut failures: Vec<String> = Vec::new();
+
+    if let Err(e) = shortcuts.unregister_all() {
+        failures.push(format!(
+            "Failed to unregister the previous shortcuts: {}",
+            e
+        ));
+    }
+
+    // The emergency stop matters most - register it first
+    let emergency = config.emergency_stop_shortcut.trim();
+    if !emergency.is_empty() {
+        let registered = shortcuts.on_shortcut(emergency, |app, _shortcut, event| {
+            if event.state() == ShortcutState::Pressed {
+                crate::input::cancel::emergency_stop();
+                let _ = app.emit(EMERGENCY_STOP_EVENT, ());
+            }
+        });
+        if let Err(e) = registered {
+            failures.push(format!(
+                "Invalid emergency stop shortcut '{}': {}",
+                emergency, e
+            ));
+        }
+    }
+
+    let show = config.global_shortcut.trim();
+    if !show.is_empty() {
+        let registered = shortcuts.on_shortcut(show, |app, _shortcut, event| {
+            if event.state() == ShortcutState::Pressed {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.show();
+                    let _ = window.unminimize();
+                    let _ = window.set_focus();
+                }
+            }
+        });
+        if let Err(e) = registered {
+            failures.push(format!("Invalid global shortcut '{}': {}", show, e));
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(failures.join("; ")))
+    }
+}
@@ -0,0 +1,83 @@
+// Input backends - the primitive mouse and keyboard events of a platform
+//
+// `mouse` and `keyboard` build smooth movement, clicks and typing on top of
+// these primitives. Windows sends them with SendInput, Linux with XTest to the
+// X server in $DISPLAY (so a run can also be driven under Xvfb).
+
+use crate::commands::input::MouseButton;
+use anyhow::Result;
+use std::sync::Arc;
+
+/// Wheel delta of one notch (the Windows WHEEL_DELTA)
+pub const WHEEL_NOTCH: i32 = 120;
+
+/// Primitive mouse and keyboard events
+pub trait InputBackend: Send + Sync {
+    /// Current cursor position
+    fn cursor_position(&self) -> Result<(i32, i32)>;
+
+    /// Put the cursor at screen coordinates
+    fn move_cursor(&self, x: i32, y: i32) -> Result<()>;
+
+    /// Press a mouse button
+    fn button_down(&self, button: MouseButton) -> Result<()>;
+
+    /// Release a mouse button
+    fn button_up(&self, button: MouseButton) -> Result<()>;
+
+    /// Turn the wheel by a wheel delta (`WHEEL_NOTCH` per notch, positive = down / right)
+    fn scroll(&self, dx: i32, dy: i32) -> Result<()>;
+
+    /// Press a key by name (see `keys`, unknown names are an error)
+    fn key_down(&self, key: &str) -> Result<()>;
+
+    /// Release a key by name (see `keys`, unknown names are an error)
+    fn key_up(&self, key: &str) -> Result<()>;
+
+    /// Type one character, whatever the keyboard layout
+    fn type_char(&self, c: char) -> Result<()>;
+
+    /// Release every modifier key and mouse button (emergency stop)
+    fn release_all(&self) -> Result<()>;
+
+    /// Press and release a mouse button
+    fn click(&self, button: MouseButton) -> Result<()> {
+        self.button_down(button)?;
+        self.button_up(button)
+    }
+}
+
+/// Input backend of the platform the app runs on
+#[cfg(windows)]
+pub fn native() -> Result<Arc<dyn InputBackend>> {
+    Ok(Arc::new(crate::input::win32::WindowsInput))
+}
+
+/// Input backend of the platform the app runs on
+/// (the X11 connection is opened on first use and shared afterwards)
+#[cfg(target_os = "linux")]
+pub fn native() -> Result<Arc<dyn InputBackend>> {
+    use crate::input::x11::X11Input;
+    use std::sync::Mutex;
+
+    lazy_static::lazy_static! {
+        static ref X11: Mutex<Option<Arc<X11Input>>> = Mutex::new(None);
+    }
+
+    let mut x11 = X11.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(input) = x11.as_ref() {
+        return Ok(input.clone());
+    }
+    let input = Arc::new(X11Input::connect()?);
+    *x11 = Some(input.clone());
+    Ok(input)
+}
+
+/// Input backend of the platform the app runs on
+#[cfg(not(any(windows, target_os = "linux")))]
+pub fn native() -> Result<Arc<dyn InputBackend>> {
+    Err(anyhow::anyhow!(
+        "Input control is not supported on {}",
+        std::env::consts::OS
+    ))
+}
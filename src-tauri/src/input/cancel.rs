@@ -0,0 +1,99 @@
+// Input cancellation - lets long mouse moves, typing and waits be aborted
+//
+// A token is cancelled either directly (the run it belongs to was stopped)
+// or by the emergency stop, which invalidates every token created before it.
+
+use anyhow::Result;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Longest stretch slept without checking for cancellation
+const CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Bumped by every emergency stop
+static EMERGENCY_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Cancellation flag checked between input steps
+#[derive(Debug, Clone)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    generation: u64,
+}
+
+impl CancelToken {
+    /// New token (not affected by earlier emergency stops)
+    pub fn new() -> Self {
+        Self {
+            cancelled: Arc::new(AtomicBool::new(false)),
+            generation: EMERGENCY_GENERATION.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Cancel this token and all its clones
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+            || EMERGENCY_GENERATION.load(Ordering::SeqCst) != self.generation
+    }
+
+    /// Error if cancelled
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(anyhow::anyhow!("Cancelled"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Block the thread for `duration`, returning early with an error if cancelled
+    pub fn sleep_blocking(&self, duration: Duration) -> Result<()> {
+        let deadline = Instant::now() + duration;
+        loop {
+            self.check()?;
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(());
+            }
+            std::thread::sleep((deadline - now).min(CHECK_INTERVAL));
+        }
+    }
+
+    /// Sleep for `duration`, returning early with an error if cancelled
+    pub async fn sleep(&self, duration: Duration) -> Result<()> {
+        let deadline = tokio::time::Instant::now() + duration;
+        loop {
+            self.check()?;
+            let now = tokio::time::Instant::now();
+            if now >= deadline {
+                return Ok(());
+            }
+            tokio::time::sleep((deadline - now).min(CHECK_INTERVAL)).await;
+        }
+    }
+
+    /// Resolves once the token is cancelled
+    pub async fn cancelled(&self) {
+        while !self.is_cancelled() {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    }
+}
+
+impl Default for CancelToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Abort all input in progress: cancels every existing token and releases held keys
+pub fn emergency_stop() {
+    EMERGENCY_GENERATION.fetch_add(1, Ordering::SeqCst);
+
+    if let Ok(input) = crate::input::backend::native() {
+        let _ = input.release_all();
+    }
+}
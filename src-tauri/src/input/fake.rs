@@ -0,0 +1,300 @@
+// Fake desktop - in-memory input and screen backend for tests
+//
+// `FakeBackend` records every primitive input action instead of sending it
+// and serves scripted screenshots and UI trees. Tools reach it through
+// `ToolContext.input`, the agent loop through `FakeDesktop`, so both can be
+// tested on any platform without touching the real mouse, keyboard or screen.
+
+use crate::commands::config::AppConfig;
+use crate::commands::input::MouseButton;
+use crate::commands::screen::{BoundingRect, UIElement};
+use crate::input::backend::InputBackend;
+use crate::input::cancel::CancelToken;
+use crate::input::keys;
+use crate::llm::provider::create_provider;
+use crate::llm::runner::environment::Desktop;
+use crate::llm::runner::ToolExecutor;
+use crate::llm::types::{ToolContext, ToolResult};
+use crate::screen::backend::{png_data_url, ScreenBackend};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// Primitive input action, as sent to a backend
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputAction {
+    Move { x: i32, y: i32 },
+    ButtonDown(MouseButton),
+    ButtonUp(MouseButton),
+    Scroll { dx: i32, dy: i32 },
+    KeyDown(String),
+    KeyUp(String),
+    Type(char),
+    ReleaseAll,
+}
+
+#[derive(Default)]
+struct FakeState {
+    cursor: (i32, i32),
+    actions: Vec<InputAction>,
+    /// Screens still to be served (the last one stays on screen)
+    screens: VecDeque<(String, UIElement)>,
+    current: Option<(String, UIElement)>,
+    /// Every input primitive fails with this message
+    failure: Option<String>,
+}
+
+/// Recording input backend and scripted screen
+#[derive(Default)]
+pub struct FakeBackend {
+    state: Mutex<FakeState>,
+}
+
+impl FakeBackend {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, FakeState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Queue a screen for the next capture
+    pub fn push_screen(&self, screenshot: impl Into<String>, tree: UIElement) {
+        self.state().screens.push_back((screenshot.into(), tree));
+    }
+
+    /// Make every input primitive fail from now on
+    pub fn fail_with(&self, message: &str) {
+        self.state().failure = Some(message.to_string());
+    }
+
+    pub fn set_cursor(&self, x: i32, y: i32) {
+        self.state().cursor = (x, y);
+    }
+
+    /// Everything sent so far, in order
+    pub fn actions(&self) -> Vec<InputAction> {
+        self.state().actions.clone()
+    }
+
+    /// Positions the cursor was moved through
+    pub fn cursor_path(&self) -> Vec<(i32, i32)> {
+        self.actions()
+            .into_iter()
+            .filter_map(|action| match action {
+                InputAction::Move { x, y } => Some((x, y)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Button, wheel and key events (everything but moves and typed characters)
+    pub fn events(&self) -> Vec<InputAction> {
+        self.actions()
+            .into_iter()
+            .filter(|action| !matches!(action, InputAction::Move { .. } | InputAction::Type(_)))
+            .collect()
+    }
+
+    /// Characters typed so far
+    pub fn typed(&self) -> String {
+        self.actions()
+            .into_iter()
+            .filter_map(|action| match action {
+                InputAction::Type(c) => Some(c),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn record(&self, action: InputAction) -> Result<()> {
+        let mut state = self.state();
+        if let Some(failure) = &state.failure {
+            return Err(anyhow::anyhow!("{}", failure));
+        }
+        if let InputAction::Move { x, y } = action {
+            state.cursor = (x, y);
+        }
+        state.actions.push(action);
+        Ok(())
+    }
+
+    /// Next scripted screen (the last one again once the script runs out)
+    fn screen(&self) -> Result<(String, UIElement)> {
+        let mut state = self.state();
+        if let Some(next) = state.screens.pop_front() {
+            state.current = Some(next);
+        }
+        state
+            .current
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("No screen scripted"))
+    }
+}
+
+impl InputBackend for FakeBackend {
+    fn cursor_position(&self) -> Result<(i32, i32)> {
+        Ok(self.state().cursor)
+    }
+
+    fn move_cursor(&self, x: i32, y: i32) -> Result<()> {
+        self.record(InputAction::Move { x, y })
+    }
+
+    fn button_down(&self, button: MouseButton) -> Result<()> {
+        self.record(InputAction::ButtonDown(button))
+    }
+
+    fn button_up(&self, button: MouseButton) -> Result<()> {
+        self.record(InputAction::ButtonUp(button))
+    }
+
+    fn scroll(&self, dx: i32, dy: i32) -> Result<()> {
+        self.record(InputAction::Scroll { dx, dy })
+    }
+
+    fn key_down(&self, key: &str) -> Result<()> {
+        self.record(InputAction::KeyDown(keys::key(key)?.to_string()))
+    }
+
+    fn key_up(&self, key: &str) -> Result<()> {
+        self.record(InputAction::KeyUp(keys::key(key)?.to_string()))
+    }
+
+    fn type_char(&self, c: char) -> Result<()> {
+        self.record(InputAction::Type(c))
+    }
+
+    fn release_all(&self) -> Result<()> {
+        self.record(InputAction::ReleaseAll)
+    }
+}
+
+impl ScreenBackend for FakeBackend {
+    fn capture(&self) -> Result<String> {
+        Ok(self.screen()?.0)
+    }
+
+    fn window_tree(&self, _max_depth: usize) -> Result<UIElement> {
+        Ok(self.screen()?.1)
+    }
+
+    fn foreground_window_title(&self) -> Option<String> {
+        let state = self.state();
+        let (_, tree) = state.current.as_ref().or(state.screens.front())?;
+        Some(tree.name.clone())
+    }
+
+    fn window_title_at(&self, _x: i32, _y: i32) -> Option<String> {
+        self.foreground_window_title()
+    }
+}
+
+/// Desktop of an agent run backed by a fake (tools run for real against it)
+pub struct FakeDesktop {
+    pub backend: Arc<FakeBackend>,
+}
+
+#[async_trait]
+impl Desktop for FakeDesktop {
+    fn os(&self) -> &str {
+        "windows"
+    }
+
+    fn capture(&self) -> Result<Option<(String, String)>> {
+        let (screenshot, tree) = self.backend.screen()?;
+        Ok(Some((screenshot, serde_json::to_string_pretty(&tree)?)))
+    }
+
+    fn input(&self) -> Option<Arc<dyn InputBackend>> {
+        Some(self.backend.clone())
+    }
+
+    fn screen(&self) -> Option<Arc<dyn ScreenBackend>> {
+        Some(self.backend.clone())
+    }
+
+    async fn execute(
+        &self,
+        executor: &ToolExecutor,
+        tool_name: &str,
+        params: Value,
+        ctx: &ToolContext,
+    ) -> Result<ToolResult> {
+        executor.execute(tool_name, params, ctx).await
+    }
+}
+
+/// Tool context acting on `backend` (default config, the provider is never called)
+pub fn tool_context(backend: &Arc<FakeBackend>) -> ToolContext {
+    let config = AppConfig::default();
+    ToolContext {
+        provider: create_provider(&config.api),
+        tool_mode: config.api.tool_mode,
+        cancel: CancelToken::new(),
+        budget: config.budget,
+        depth: 0,
+        deadline: None,
+        dry_run: false,
+        plan: None,
+        input: Some(backend.clone()),
+        held: Default::default(),
+    }
+}
+
+/// Tool context on `backend` whose provider talks to the mock server at `url`
+pub fn agent_context(backend: &Arc<FakeBackend>, url: &str) -> ToolContext {
+    let mut api = AppConfig::default().api;
+    api.endpoint = url.to_string();
+    api.api_key = "test-key".to_string();
+    api.stream = false;
+    ToolContext {
+        provider: create_provider(&api),
+        ..tool_context(backend)
+    }
+}
+
+/// Single-colour screenshot as a PNG data URL
+pub fn solid_screenshot(width: u32, height: u32, rgba: [u8; 4]) -> String {
+    let pixels = rgba.repeat((width * height) as usize);
+    png_data_url(width, height, &pixels).unwrap()
+}
+
+/// UI element with the given children (zero-sized)
+pub fn element(
+    control_type: &str,
+    name: &str,
+    focused: bool,
+    children: Vec<UIElement>,
+) -> UIElement {
+    UIElement {
+        name: name.to_string(),
+        class_name: String::new(),
+        control_type: control_type.to_string(),
+        bounding_rect: BoundingRect {
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 0,
+        },
+        is_enabled: true,
+        is_focused: focused,
+        children,
+    }
+}
+
+/// 800x600 window element with the given children
+pub fn window(name: &str, children: Vec<UIElement>) -> UIElement {
+    UIElement {
+        bounding_rect: BoundingRect {
+            x: 0,
+            y: 0,
+            width: 800,
+            height: 600,
+        },
+        ..element("Window", name, false, children)
+    }
+}
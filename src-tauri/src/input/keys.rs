@@ -0,0 +1,417 @@
+// Key names - the key vocabulary shared by every input backend
+//
+// Names are case-insensitive and have aliases ("control", "esc", "pgdn"),
+// but backends only ever see the canonical name. Key strings are parsed into
+// chords: "ctrl+shift+t" is one chord, "ctrl+k ctrl+s" two pressed one after
+// another. A chord can be repeated ("down*5") or held ("space@2s").
+
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+/// How long a chord is held unless it says otherwise
+pub const DEFAULT_HOLD_MS: u64 = 50;
+
+/// Longest hold of a chord
+const MAX_HOLD_MS: u64 = 10_000;
+
+/// Most repeats of a chord
+const MAX_REPEAT: u32 = 100;
+
+/// Keys pressed together, released in reverse order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chord {
+    /// Canonical key names
+    pub keys: Vec<&'static str>,
+    pub hold_ms: u64,
+    pub repeat: u32,
+}
+
+impl Chord {
+    /// Chord of separate key names (e.g. ["ctrl", "c"])
+    pub fn from_names(names: &[String]) -> Result<Self> {
+        if names.is_empty() {
+            return Err(anyhow::anyhow!("No keys given"));
+        }
+        let mut keys = Vec::new();
+        for name in names {
+            push_key(&mut keys, name)?;
+        }
+        Ok(Self {
+            keys,
+            hold_ms: DEFAULT_HOLD_MS,
+            repeat: 1,
+        })
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.keys.join("+"))?;
+        if self.repeat != 1 {
+            write!(f, "*{}", self.repeat)?;
+        }
+        if self.hold_ms != DEFAULT_HOLD_MS {
+            write!(f, "@{}ms", self.hold_ms)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a key string: chords separated by spaces
+/// (e.g. "Ctrl+Shift+T", "ctrl+k ctrl+s", "down*5 enter", "space@1500ms")
+pub fn parse_sequence(text: &str) -> Result<Vec<Chord>> {
+    let chords = text
+        .split_whitespace()
+        .map(parse_chord)
+        .collect::<Result<Vec<_>>>()?;
+    if chords.is_empty() {
+        return Err(anyhow::anyhow!("No keys given"));
+    }
+    Ok(chords)
+}
+
+/// Parse keys as a tool call gives them: a key string, or an array of key
+/// names pressed together (the older form, still found in saved sessions)
+pub fn parse_keys(value: &Value) -> Result<Vec<Chord>> {
+    match value {
+        Value::String(text) => parse_sequence(text),
+        Value::Array(names) => {
+            let names = names
+                .iter()
+                .map(|name| {
+                    name.as_str()
+                        .map(str::to_string)
+                        .ok_or_else(|| anyhow::anyhow!("Key names must be strings"))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(vec![Chord::from_names(&names)?])
+        }
+        _ => Err(anyhow::anyhow!("Missing keys")),
+    }
+}
+
+/// Parse one chord with its optional `*repeat` and `@hold` suffixes
+fn parse_chord(token: &str) -> Result<Chord> {
+    let (combo, mut suffixes) = match token.find(['*', '@']) {
+        Some(index) => token.split_at(index),
+        None => (token, ""),
+    };
+
+    // A trailing "++" (or a lone "+") is the plus key itself
+    let (combo, plus) = match combo.strip_suffix("++") {
+        Some(rest) => (rest, true),
+        None if combo == "+" => ("", true),
+        None => (combo, false),
+    };
+
+    let mut keys = Vec::new();
+    if !(plus && combo.is_empty()) {
+        for name in combo.split('+') {
+            if name.is_empty() {
+                return Err(anyhow::anyhow!("Missing key in {:?}", token));
+            }
+            push_key(&mut keys, name)?;
+        }
+    }
+    if plus {
+        push_key(&mut keys, "+")?;
+    }
+
+    let mut chord = Chord {
+        keys,
+        hold_ms: DEFAULT_HOLD_MS,
+        repeat: 1,
+    };
+    while let Some(kind) = suffixes.chars().next() {
+        let rest = &suffixes[1..];
+        let end = rest.find(['*', '@']).unwrap_or(rest.len());
+        let value = &rest[..end];
+        match kind {
+            '*' => chord.repeat = parse_repeat(value, token)?,
+            _ => chord.hold_ms = parse_hold(value, token)?,
+        }
+        suffixes = &rest[end..];
+    }
+    Ok(chord)
+}
+
+fn parse_repeat(value: &str, token: &str) -> Result<u32> {
+    value
+        .parse::<u32>()
+        .ok()
+        .filter(|count| (1..=MAX_REPEAT).contains(count))
+        .ok_or_else(|| anyhow::anyhow!("Invalid repeat count in {:?} (1 to {})", token, MAX_REPEAT))
+}
+
+/// Hold duration: "300ms", "1.5s" or plain milliseconds
+fn parse_hold(value: &str, token: &str) -> Result<u64> {
+    let ms = if let Some(ms) = value.strip_suffix("ms") {
+        ms.parse::<u64>().ok()
+    } else if let Some(seconds) = value.strip_suffix('s') {
+        seconds
+            .parse::<f64>()
+            .ok()
+            .filter(|s| s.is_finite() && *s >= 0.0)
+            .map(|s| (s * 1000.0).round() as u64)
+    } else {
+        value.parse::<u64>().ok()
+    };
+    ms.filter(|ms| *ms <= MAX_HOLD_MS).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Invalid hold duration in {:?} (e.g. @500ms or @2s, at most {}ms)",
+            token,
+            MAX_HOLD_MS
+        )
+    })
+}
+
+/// Add the keys a name presses to a chord
+fn push_key(keys: &mut Vec<&'static str>, name: &str) -> Result<()> {
+    for name in presses(name).ok_or_else(|| unknown_key(name))? {
+        if !keys.contains(&name) {
+            keys.push(name);
+        }
+    }
+    Ok(())
+}
+
+/// Canonical keys pressed for a name: the key itself, or shift+= for
+/// "+" / "plus" (shifted "=" on US layouts; "numadd" is the keypad plus)
+pub fn presses(name: &str) -> Option<Vec<&'static str>> {
+    match name.trim().to_lowercase().as_str() {
+        "+" | "plus" => Some(vec!["shift", "="]),
+        _ => canonical(name).map(|name| vec![name]),
+    }
+}
+
+/// Canonical name of a key, or an error naming the unknown key
+pub fn key(name: &str) -> Result<&'static str> {
+    canonical(name).ok_or_else(|| unknown_key(name))
+}
+
+fn unknown_key(name: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "Unknown key: {:?} (use names like ctrl, shift, enter, f5, a, num1, volumeup, hangul)",
+        name
+    )
+}
+
+/// Canonical name of a key name or alias
+pub fn canonical(name: &str) -> Option<&'static str> {
+    lazy_static::lazy_static! {
+        static ref KEY_NAMES: HashMap<&'static str, &'static str> = build_key_names();
+    }
+    KEY_NAMES.get(name.trim().to_lowercase().as_str()).copied()
+}
+
+/// Every canonical key name (each backend maps all of them)
+#[cfg(test)]
+pub fn all() -> Vec<&'static str> {
+    let mut names: Vec<_> = build_key_names().into_values().collect();
+    names.sort_unstable();
+    names.dedup();
+    names
+}
+
+/// Build key name and alias to canonical name mapping
+fn build_key_names() -> HashMap<&'static str, &'static str> {
+    let mut map = HashMap::new();
+    let named: &[(&'static str, &[&'static str])] = &[
+        // Modifiers
+        ("ctrl", &["control", "ctl"]),
+        ("alt", &["option"]),
+        ("shift", &[]),
+        ("win", &["windows", "super", "meta", "cmd"]),
+        // Special keys
+        ("enter", &["return"]),
+        ("tab", &[]),
+        ("escape", &["esc"]),
+        ("backspace", &[]),
+        ("delete", &["del"]),
+        ("insert", &["ins"]),
+        ("home", &[]),
+        ("end", &[]),
+        ("pageup", &["pgup"]),
+        ("pagedown", &["pgdn"]),
+        ("space", &["spacebar"]),
+        ("capslock", &["caps"]),
+        ("numlock", &[]),
+        ("scrolllock", &[]),
+        ("printscreen", &["prtsc", "print"]),
+        ("pause", &["break"]),
+        ("apps", &["contextmenu"]),
+        // Arrow keys
+        ("up", &["arrowup"]),
+        ("down", &["arrowdown"]),
+        ("left", &["arrowleft"]),
+        ("right", &["arrowright"]),
+        // Punctuation (US layout)
+        (";", &["semicolon"]),
+        // "+" / "plus" press shift+= (see `presses`)
+        ("=", &["equals", "equal"]),
+        (",", &["comma"]),
+        ("-", &["minus", "dash"]),
+        (".", &["period", "dot"]),
+        ("/", &["slash"]),
+        ("`", &["backquote", "backtick", "grave"]),
+        ("[", &["bracketleft"]),
+        ("\\", &["backslash"]),
+        ("]", &["bracketright"]),
+        ("'", &["quote", "apostrophe"]),
+        // Numpad operators
+        ("numadd", &["add", "numplus"]),
+        ("numsubtract", &["subtract", "numminus"]),
+        ("nummultiply", &["multiply"]),
+        ("numdivide", &["divide"]),
+        ("numdecimal", &["decimal"]),
+        // Media keys
+        ("volumeup", &[]),
+        ("volumedown", &[]),
+        ("volumemute", &["mute"]),
+        ("playpause", &["mediaplaypause"]),
+        ("mediastop", &[]),
+        ("nexttrack", &["medianext"]),
+        ("prevtrack", &["mediaprev", "previoustrack"]),
+        // Browser keys
+        ("browserback", &[]),
+        ("browserforward", &[]),
+        ("browserrefresh", &[]),
+        ("browserstop", &[]),
+        ("browsersearch", &[]),
+        ("browserfavorites", &[]),
+        ("browserhome", &[]),
+        // IME keys
+        ("hangul", &["hangeul", "hanyeong"]),
+        ("hanja", &[]),
+        ("kana", &["katakana", "hiragana"]),
+        ("kanji", &[]),
+        ("convert", &["henkan"]),
+        ("nonconvert", &["muhenkan"]),
+    ];
+    for (name, aliases) in named {
+        map.insert(*name, *name);
+        for alias in *aliases {
+            map.insert(*alias, *name);
+        }
+    }
+
+    // Function keys
+    for n in 1..=24 {
+        let name: &'static str = Box::leak(format!("f{}", n).into_boxed_str());
+        map.insert(name, name);
+    }
+
+    // Numpad digits
+    for n in 0..=9 {
+        let name: &'static str = Box::leak(format!("num{}", n).into_boxed_str());
+        let alias: &'static str = Box::leak(format!("numpad{}", n).into_boxed_str());
+        map.insert(name, name);
+        map.insert(alias, name);
+    }
+
+    // Letters and numbers
+    for c in ('a'..='z').chain('0'..='9') {
+        let name: &'static str = Box::leak(c.to_string().into_boxed_str());
+        map.insert(name, name);
+    }
+
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn chord(keys: &[&'static str]) -> Chord {
+        Chord {
+            keys: keys.to_vec(),
+            hold_ms: DEFAULT_HOLD_MS,
+            repeat: 1,
+        }
+    }
+
+    #[test]
+    fn parses_combinations_and_sequences() {
+        assert_eq!(
+            parse_sequence("Ctrl+Shift+T").unwrap(),
+            [chord(&["ctrl", "shift", "t"])]
+        );
+        assert_eq!(
+            parse_sequence(" control+k  CTRL+S ").unwrap(),
+            [chord(&["ctrl", "k"]), chord(&["ctrl", "s"])]
+        );
+        assert_eq!(
+            parse_sequence("ctrl+- ctrl+,").unwrap(),
+            [chord(&["ctrl", "-"]), chord(&["ctrl", ","])]
+        );
+        assert_eq!(
+            parse_sequence("ctrl++ ctrl+plus + ++ shift+plus ctrl+= numadd").unwrap(),
+            [
+                chord(&["ctrl", "shift", "="]),
+                chord(&["ctrl", "shift", "="]),
+                chord(&["shift", "="]),
+                chord(&["shift", "="]),
+                chord(&["shift", "="]),
+                chord(&["ctrl", "="]),
+                chord(&["numadd"])
+            ]
+        );
+        assert_eq!(
+            parse_sequence("Hangul f24 numpad7 pgdn").unwrap(),
+            [
+                chord(&["hangul"]),
+                chord(&["f24"]),
+                chord(&["num7"]),
+                chord(&["pagedown"])
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_repeats_and_holds() {
+        let chords = parse_sequence("down*5 space@1.5s shift+a@300ms*2 tab@200").unwrap();
+        let suffixes: Vec<_> = chords.iter().map(|c| (c.repeat, c.hold_ms)).collect();
+        assert_eq!(suffixes, [(5, 50), (1, 1500), (2, 300), (1, 200)]);
+        assert_eq!(
+            chords.iter().map(Chord::to_string).collect::<Vec<_>>(),
+            ["down*5", "space@1500ms", "shift+a*2@300ms", "tab@200ms"]
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_keys_and_bad_suffixes() {
+        let error = |text| parse_sequence(text).unwrap_err().to_string();
+        assert!(error("ctrl+foo").starts_with("Unknown key: \"foo\""));
+        assert_eq!(error("ctrl+"), "Missing key in \"ctrl+\"");
+        assert_eq!(error("ctrl+++"), "Missing key in \"ctrl+++\"");
+        assert!(error("down*0").starts_with("Invalid repeat count"));
+        assert!(error("down*1000").starts_with("Invalid repeat count"));
+        assert!(error("space@forever").starts_with("Invalid hold duration"));
+        assert!(error("space@60s").starts_with("Invalid hold duration"));
+        assert_eq!(error("  "), "No keys given");
+        assert!(Chord::from_names(&["ctrl".into(), "nope".into()]).is_err());
+    }
+
+    #[test]
+    fn parses_key_strings_and_name_arrays() {
+        assert_eq!(
+            parse_keys(&json!("ctrl+k enter")).unwrap(),
+            [chord(&["ctrl", "k"]), chord(&["enter"])]
+        );
+        assert_eq!(
+            parse_keys(&json!(["Control", "Shift", "esc"])).unwrap(),
+            [chord(&["ctrl", "shift", "escape"])]
+        );
+        assert_eq!(
+            parse_keys(&json!(["ctrl", "+"])).unwrap(),
+            [chord(&["ctrl", "shift", "="])]
+        );
+        let error = |value: Value| parse_keys(&value).unwrap_err().to_string();
+        assert_eq!(error(json!([])), "No keys given");
+        assert_eq!(error(json!(["ctrl", 1])), "Key names must be strings");
+        assert_eq!(error(Value::Null), "Missing keys");
+    }
+}
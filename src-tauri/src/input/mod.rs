@@ -1,6 +1,13 @@
 // Input automation module
 
-#[cfg(windows)]
+pub mod backend;
+pub mod cancel;
+#[cfg(test)]
+pub mod fake;
 pub mod keyboard;
-#[cfg(windows)]
+pub mod keys;
 pub mod mouse;
+#[cfg(windows)]
+mod win32;
+#[cfg(target_os = "linux")]
+mod x11;
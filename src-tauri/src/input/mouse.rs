@@ -1,58 +1,58 @@
-// Mouse control implementation (Windows)
+// Mouse control - smooth movement, clicks, drags and scrolling on top of an input backend
 
 use crate::commands::input::MouseButton;
+use crate::input::backend::{InputBackend, WHEEL_NOTCH};
+use crate::input::cancel::CancelToken;
 use anyhow::Result;
-use std::thread;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use windows::Win32::Foundation::*;
-use windows::Win32::UI::Input::KeyboardAndMouse::*;
-use windows::Win32::UI::WindowsAndMessaging::*;
 
 /// Move mouse instantly to coordinates
-pub fn instant_move(x: i32, y: i32) -> Result<()> {
-    unsafe {
-        SetCursorPos(x, y)?;
-    }
-    Ok(())
+pub fn instant_move(input: &dyn InputBackend, x: i32, y: i32) -> Result<()> {
+    input.move_cursor(x, y)
 }
 
 /// Move mouse smoothly using bezier curve
-pub fn smooth_move(target_x: i32, target_y: i32, duration_ms: u64) -> Result<()> {
-    unsafe {
-        let mut current_pos = POINT::default();
-        GetCursorPos(&mut current_pos)?;
-
-        let start_x = current_pos.x as f64;
-        let start_y = current_pos.y as f64;
-        let end_x = target_x as f64;
-        let end_y = target_y as f64;
-
-        // Control points for bezier curve (adds natural curve to movement)
-        let cp1_x = start_x + (end_x - start_x) * 0.3;
-        let cp1_y = start_y + (end_y - start_y) * 0.1;
-        let cp2_x = start_x + (end_x - start_x) * 0.7;
-        let cp2_y = end_y - (end_y - start_y) * 0.1;
-
-        let steps = (duration_ms / 16).max(10) as usize; // ~60fps
-        let step_duration = Duration::from_millis(duration_ms / steps as u64);
-
-        for i in 0..=steps {
-            let t = i as f64 / steps as f64;
-
-            // Cubic bezier formula with ease-in-out
-            let eased_t = ease_in_out(t);
-            let (x, y) = cubic_bezier(
-                start_x, start_y, cp1_x, cp1_y, cp2_x, cp2_y, end_x, end_y, eased_t,
-            );
-
-            SetCursorPos(x as i32, y as i32)?;
-            thread::sleep(step_duration);
-        }
+/// Stops where it is when `cancel` is triggered
+pub fn smooth_move(
+    input: &dyn InputBackend,
+    target_x: i32,
+    target_y: i32,
+    duration_ms: u64,
+    cancel: &CancelToken,
+) -> Result<()> {
+    let (current_x, current_y) = input.cursor_position()?;
+
+    let start_x = current_x as f64;
+    let start_y = current_y as f64;
+    let end_x = target_x as f64;
+    let end_y = target_y as f64;
+
+    // Control points for bezier curve (adds natural curve to movement)
+    let cp1_x = start_x + (end_x - start_x) * 0.3;
+    let cp1_y = start_y + (end_y - start_y) * 0.1;
+    let cp2_x = start_x + (end_x - start_x) * 0.7;
+    let cp2_y = end_y - (end_y - start_y) * 0.1;
+
+    let steps = (duration_ms / 16).max(10) as usize; // ~60fps
+    let step_duration = Duration::from_millis(duration_ms / steps as u64);
+
+    for i in 0..=steps {
+        cancel.check()?;
+        let t = i as f64 / steps as f64;
 
-        // Ensure we end exactly at target
-        SetCursorPos(target_x, target_y)?;
+        // Cubic bezier formula with ease-in-out
+        let eased_t = ease_in_out(t);
+        let (x, y) = cubic_bezier(
+            start_x, start_y, cp1_x, cp1_y, cp2_x, cp2_y, end_x, end_y, eased_t,
+        );
+
+        input.move_cursor(x as i32, y as i32)?;
+        std::thread::sleep(step_duration);
     }
-    Ok(())
+
+    // Ensure we end exactly at target
+    input.move_cursor(target_x, target_y)
 }
 
 /// Ease in-out function for smooth acceleration/deceleration
@@ -65,6 +65,7 @@ fn ease_in_out(t: f64) -> f64 {
 }
 
 /// Cubic bezier interpolation
+#[allow(clippy::too_many_arguments)]
 fn cubic_bezier(
     x0: f64,
     y0: f64,
@@ -89,61 +90,214 @@ fn cubic_bezier(
 }
 
 /// Click at coordinates
-pub fn click(x: i32, y: i32, button: MouseButton, double: bool) -> Result<()> {
+pub fn click(
+    input: &dyn InputBackend,
+    x: i32,
+    y: i32,
+    button: MouseButton,
+    double: bool,
+    cancel: &CancelToken,
+) -> Result<()> {
     // Move to position first
-    smooth_move(x, y, 200)?;
+    smooth_move(input, x, y, 200, cancel)?;
 
     // Small delay after move
-    thread::sleep(Duration::from_millis(50));
-
-    let (down_flag, up_flag) = match button {
-        MouseButton::Left => (MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP),
-        MouseButton::Right => (MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP),
-        MouseButton::Middle => (MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP),
-    };
-
-    unsafe {
-        let clicks = if double { 2 } else { 1 };
-
-        for _ in 0..clicks {
-            let mut inputs = [
-                INPUT {
-                    r#type: INPUT_MOUSE,
-                    Anonymous: INPUT_0 {
-                        mi: MOUSEINPUT {
-                            dwFlags: down_flag,
-                            ..Default::default()
-                        },
-                    },
-                },
-                INPUT {
-                    r#type: INPUT_MOUSE,
-                    Anonymous: INPUT_0 {
-                        mi: MOUSEINPUT {
-                            dwFlags: up_flag,
-                            ..Default::default()
-                        },
-                    },
-                },
-            ];
-
-            SendInput(&mut inputs, std::mem::size_of::<INPUT>() as i32);
-
-            if double {
-                thread::sleep(Duration::from_millis(50));
-            }
+    cancel.sleep_blocking(Duration::from_millis(50))?;
+
+    let clicks = if double { 2 } else { 1 };
+    for _ in 0..clicks {
+        input.click(button)?;
+
+        if double {
+            std::thread::sleep(Duration::from_millis(50));
         }
     }
 
     Ok(())
 }
 
-/// Get current cursor position
-#[allow(dead_code)]
-pub fn get_position() -> Result<(i32, i32)> {
-    unsafe {
-        let mut point = POINT::default();
-        GetCursorPos(&mut point)?;
-        Ok((point.x, point.y))
+/// Move to coordinates and press a button (held until `release`)
+pub fn press(
+    input: &dyn InputBackend,
+    x: i32,
+    y: i32,
+    button: MouseButton,
+    cancel: &CancelToken,
+) -> Result<()> {
+    smooth_move(input, x, y, 200, cancel)?;
+    cancel.sleep_blocking(Duration::from_millis(50))?;
+    input.button_down(button)
+}
+
+/// Move to coordinates and release a button
+pub fn release(
+    input: &dyn InputBackend,
+    x: i32,
+    y: i32,
+    button: MouseButton,
+    cancel: &CancelToken,
+) -> Result<()> {
+    smooth_move(input, x, y, 200, cancel)?;
+    cancel.sleep_blocking(Duration::from_millis(50))?;
+    input.button_up(button)
+}
+
+/// Drag from one point to another with `button` held
+pub struct Drag<'a> {
+    pub from: (i32, i32),
+    pub to: (i32, i32),
+    pub button: MouseButton,
+    /// Held before moving, so the target notices a press-and-hold
+    pub hold_ms: u64,
+    /// Duration of the move from `from` to `to`
+    pub duration_ms: u64,
+    /// Keys held for the whole drag (e.g. ctrl to copy instead of move)
+    pub modifiers: &'a [String],
+}
+
+/// Drag along a bezier path
+/// The button and modifiers are released even when the drag fails or is cancelled
+pub fn drag(input: &dyn InputBackend, drag: &Drag, cancel: &CancelToken) -> Result<()> {
+    smooth_move(input, drag.from.0, drag.from.1, 200, cancel)?;
+    cancel.sleep_blocking(Duration::from_millis(50))?;
+
+    let mut held = Vec::new();
+    let mut pressed = false;
+    let mut result = (|| -> Result<()> {
+        for key in drag.modifiers {
+            input.key_down(key)?;
+            held.push(key);
+        }
+        input.button_down(drag.button)?;
+        pressed = true;
+        cancel.sleep_blocking(Duration::from_millis(drag.hold_ms))?;
+        smooth_move(input, drag.to.0, drag.to.1, drag.duration_ms, cancel)?;
+        // Give the drop target a moment to react to the hover
+        std::thread::sleep(Duration::from_millis(50));
+        Ok(())
+    })();
+
+    if pressed {
+        result = result.and(input.button_up(drag.button));
+    }
+    for key in held.into_iter().rev() {
+        result = result.and(input.key_up(key));
+    }
+    result
+}
+
+/// Buttons a run pressed with mouse_down and hasn't released yet
+/// (shared by the run's tools, so the run can let go of them when it ends)
+#[derive(Debug, Clone, Default)]
+pub struct HeldButtons(Arc<Mutex<Vec<MouseButton>>>);
+
+impl HeldButtons {
+    pub fn press(&self, button: MouseButton) {
+        let mut held = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if !held.contains(&button) {
+            held.push(button);
+        }
+    }
+
+    pub fn release(&self, button: MouseButton) {
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|held| *held != button);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).is_empty()
+    }
+
+    /// Release every held button, the last pressed first
+    pub fn release_all(&self, input: &dyn InputBackend) -> Result<()> {
+        let held = std::mem::take(&mut *self.0.lock().unwrap_or_else(|e| e.into_inner()));
+        let mut result = Ok(());
+        for button in held.into_iter().rev() {
+            if let Err(e) = input.button_up(button) {
+                result = Err(e);
+            }
+        }
+        result
+    }
+}
+
+/// Pixels scrolled by one wheel notch (three lines of text at the default settings)
+pub const PIXELS_PER_NOTCH: i32 = 50;
+
+/// Most one scroll may move, in notches
+pub const MAX_SCROLL_NOTCHES: i32 = 100;
+
+/// Wheel delta sent per step of a smooth scroll (a quarter notch)
+const SMOOTH_STEP: i32 = WHEEL_NOTCH / 4;
+
+/// How a scroll amount is measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollUnit {
+    Notches,
+    Pixels,
+}
+
+impl ScrollUnit {
+    /// Largest amount of this unit one scroll may move
+    pub fn max_amount(self) -> i32 {
+        match self {
+            ScrollUnit::Notches => MAX_SCROLL_NOTCHES,
+            ScrollUnit::Pixels => MAX_SCROLL_NOTCHES * PIXELS_PER_NOTCH,
+        }
+    }
+
+    /// Wheel delta of `amount` units
+    /// Fails when the amount is below 1 or above `max_amount`
+    pub fn wheel_delta(self, amount: i64) -> Result<i32> {
+        i32::try_from(amount)
+            .ok()
+            .filter(|amount| (1..=self.max_amount()).contains(amount))
+            .and_then(|amount| amount.checked_mul(WHEEL_NOTCH))
+            .map(|delta| match self {
+                ScrollUnit::Notches => delta,
+                ScrollUnit::Pixels => delta / PIXELS_PER_NOTCH,
+            })
+            .ok_or_else(|| {
+                anyhow::anyhow!("Amount must be between 1 and {}", self.max_amount())
+            })
+    }
+}
+
+/// Scroll by a wheel delta (positive = down / right), over `target` if given
+/// A smooth scroll is sent in quarter-notch steps instead of at once
+pub fn scroll(
+    input: &dyn InputBackend,
+    target: Option<(i32, i32)>,
+    dx: i32,
+    dy: i32,
+    smooth: bool,
+    cancel: &CancelToken,
+) -> Result<()> {
+    if let Some((x, y)) = target {
+        smooth_move(input, x, y, 200, cancel)?;
+        cancel.sleep_blocking(Duration::from_millis(50))?;
+    }
+
+    if !smooth {
+        cancel.check()?;
+        return input.scroll(dx, dy);
     }
+
+    // Split the delta evenly, so the steps add up to exactly (dx, dy)
+    let steps = dx
+        .unsigned_abs()
+        .max(dy.unsigned_abs())
+        .div_ceil(SMOOTH_STEP as u32) as i32;
+    let (mut sent_x, mut sent_y) = (0, 0);
+    for i in 1..=steps {
+        cancel.check()?;
+        let (x, y) = (dx * i / steps, dy * i / steps);
+        input.scroll(x - sent_x, y - sent_y)?;
+        (sent_x, sent_y) = (x, y);
+        std::thread::sleep(Duration::from_millis(15));
+    }
+
+    Ok(())
 }
@@ -0,0 +1,296 @@
+// Input backend (Windows) - SendInput mouse and keyboard events
+
+use crate::commands::input::MouseButton;
+use crate::input::backend::InputBackend;
+use crate::input::keys;
+use anyhow::Result;
+use std::collections::HashMap;
+use windows::Win32::Foundation::*;
+use windows::Win32::UI::Input::KeyboardAndMouse::*;
+use windows::Win32::UI::WindowsAndMessaging::*;
+
+/// SendInput backend
+pub struct WindowsInput;
+
+impl InputBackend for WindowsInput {
+    fn cursor_position(&self) -> Result<(i32, i32)> {
+        unsafe {
+            let mut point = POINT::default();
+            GetCursorPos(&mut point)?;
+            Ok((point.x, point.y))
+        }
+    }
+
+    fn move_cursor(&self, x: i32, y: i32) -> Result<()> {
+        unsafe {
+            SetCursorPos(x, y)?;
+        }
+        Ok(())
+    }
+
+    fn button_down(&self, button: MouseButton) -> Result<()> {
+        let flag = match button {
+            MouseButton::Left => MOUSEEVENTF_LEFTDOWN,
+            MouseButton::Right => MOUSEEVENTF_RIGHTDOWN,
+            MouseButton::Middle => MOUSEEVENTF_MIDDLEDOWN,
+        };
+        send_mouse(&[(flag, 0)]);
+        Ok(())
+    }
+
+    fn button_up(&self, button: MouseButton) -> Result<()> {
+        let flag = match button {
+            MouseButton::Left => MOUSEEVENTF_LEFTUP,
+            MouseButton::Right => MOUSEEVENTF_RIGHTUP,
+            MouseButton::Middle => MOUSEEVENTF_MIDDLEUP,
+        };
+        send_mouse(&[(flag, 0)]);
+        Ok(())
+    }
+
+    fn scroll(&self, dx: i32, dy: i32) -> Result<()> {
+        // Positive wheel delta scrolls up, positive horizontal delta scrolls right.
+        // Deltas below a notch are passed on for applications that scroll smoothly.
+        if dy != 0 {
+            send_mouse(&[(MOUSEEVENTF_WHEEL, -dy)]);
+        }
+        if dx != 0 {
+            send_mouse(&[(MOUSEEVENTF_HWHEEL, dx)]);
+        }
+        Ok(())
+    }
+
+    fn key_down(&self, key: &str) -> Result<()> {
+        send_key(virtual_key(key)?, KEYBD_EVENT_FLAGS(0));
+        Ok(())
+    }
+
+    fn key_up(&self, key: &str) -> Result<()> {
+        send_key(virtual_key(key)?, KEYEVENTF_KEYUP);
+        Ok(())
+    }
+
+    fn type_char(&self, c: char) -> Result<()> {
+        // UTF-16 units outside the BMP are sent as a surrogate pair
+        let mut units = [0u16; 2];
+        for unit in c.encode_utf16(&mut units).iter() {
+            send_unicode(*unit);
+        }
+        Ok(())
+    }
+
+    fn release_all(&self) -> Result<()> {
+        for vk in [
+            VK_CONTROL,
+            VK_LCONTROL,
+            VK_RCONTROL,
+            VK_MENU,
+            VK_LMENU,
+            VK_RMENU,
+            VK_SHIFT,
+            VK_LSHIFT,
+            VK_RSHIFT,
+            VK_LWIN,
+            VK_RWIN,
+        ] {
+            send_key(vk, KEYEVENTF_KEYUP);
+        }
+        send_mouse(&[
+            (MOUSEEVENTF_LEFTUP, 0),
+            (MOUSEEVENTF_RIGHTUP, 0),
+            (MOUSEEVENTF_MIDDLEUP, 0),
+        ]);
+        Ok(())
+    }
+}
+
+/// Send mouse events (flag and wheel data)
+fn send_mouse(events: &[(MOUSE_EVENT_FLAGS, i32)]) {
+    let mut inputs: Vec<INPUT> = events
+        .iter()
+        .map(|&(flag, data)| INPUT {
+            r#type: INPUT_MOUSE,
+            Anonymous: INPUT_0 {
+                mi: MOUSEINPUT {
+                    dwFlags: flag,
+                    mouseData: data as u32,
+                    ..Default::default()
+                },
+            },
+        })
+        .collect();
+
+    unsafe {
+        SendInput(&mut inputs, std::mem::size_of::<INPUT>() as i32);
+    }
+}
+
+/// Send a virtual key event
+fn send_key(vk: VIRTUAL_KEY, flags: KEYBD_EVENT_FLAGS) {
+    let mut input = [INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: vk,
+                dwFlags: flags,
+                ..Default::default()
+            },
+        },
+    }];
+
+    unsafe {
+        SendInput(&mut input, std::mem::size_of::<INPUT>() as i32);
+    }
+}
+
+/// Press and release a UTF-16 unit
+fn send_unicode(unit: u16) {
+    let mut inputs = [KEYEVENTF_UNICODE, KEYEVENTF_UNICODE | KEYEVENTF_KEYUP].map(|flags| INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: VIRTUAL_KEY(0),
+                wScan: unit,
+                dwFlags: flags,
+                ..Default::default()
+            },
+        },
+    });
+
+    unsafe {
+        SendInput(&mut inputs, std::mem::size_of::<INPUT>() as i32);
+    }
+}
+
+/// Virtual key code of a key name
+fn virtual_key(key: &str) -> Result<VIRTUAL_KEY> {
+    lazy_static::lazy_static! {
+        static ref KEY_MAP: HashMap<&'static str, VIRTUAL_KEY> = build_key_map();
+    }
+    let name = keys::key(key)?;
+    KEY_MAP
+        .get(name)
+        .copied()
+        .ok_or_else(|| anyhow::anyhow!("Key {:?} is not supported on Windows", name))
+}
+
+/// Build canonical key name to virtual key code mapping
+fn build_key_map() -> HashMap<&'static str, VIRTUAL_KEY> {
+    let mut map = HashMap::new();
+
+    // Modifiers
+    map.insert("ctrl", VK_CONTROL);
+    map.insert("alt", VK_MENU);
+    map.insert("shift", VK_SHIFT);
+    map.insert("win", VK_LWIN);
+
+    // Special keys
+    map.insert("enter", VK_RETURN);
+    map.insert("tab", VK_TAB);
+    map.insert("escape", VK_ESCAPE);
+    map.insert("backspace", VK_BACK);
+    map.insert("delete", VK_DELETE);
+    map.insert("insert", VK_INSERT);
+    map.insert("home", VK_HOME);
+    map.insert("end", VK_END);
+    map.insert("pageup", VK_PRIOR);
+    map.insert("pagedown", VK_NEXT);
+    map.insert("space", VK_SPACE);
+    map.insert("capslock", VK_CAPITAL);
+    map.insert("numlock", VK_NUMLOCK);
+    map.insert("scrolllock", VK_SCROLL);
+    map.insert("printscreen", VK_SNAPSHOT);
+    map.insert("pause", VK_PAUSE);
+    map.insert("apps", VK_APPS);
+
+    // Arrow keys
+    map.insert("up", VK_UP);
+    map.insert("down", VK_DOWN);
+    map.insert("left", VK_LEFT);
+    map.insert("right", VK_RIGHT);
+
+    // Punctuation (US layout)
+    map.insert(";", VK_OEM_1);
+    map.insert("=", VK_OEM_PLUS);
+    map.insert(",", VK_OEM_COMMA);
+    map.insert("-", VK_OEM_MINUS);
+    map.insert(".", VK_OEM_PERIOD);
+    map.insert("/", VK_OEM_2);
+    map.insert("`", VK_OEM_3);
+    map.insert("[", VK_OEM_4);
+    map.insert("\\", VK_OEM_5);
+    map.insert("]", VK_OEM_6);
+    map.insert("'", VK_OEM_7);
+
+    // Numpad
+    map.insert("numadd", VK_ADD);
+    map.insert("numsubtract", VK_SUBTRACT);
+    map.insert("nummultiply", VK_MULTIPLY);
+    map.insert("numdivide", VK_DIVIDE);
+    map.insert("numdecimal", VK_DECIMAL);
+    for n in 0..=9u16 {
+        let key = Box::leak(format!("num{}", n).into_boxed_str());
+        map.insert(key, VIRTUAL_KEY(VK_NUMPAD0.0 + n));
+    }
+
+    // Media keys
+    map.insert("volumeup", VK_VOLUME_UP);
+    map.insert("volumedown", VK_VOLUME_DOWN);
+    map.insert("volumemute", VK_VOLUME_MUTE);
+    map.insert("playpause", VK_MEDIA_PLAY_PAUSE);
+    map.insert("mediastop", VK_MEDIA_STOP);
+    map.insert("nexttrack", VK_MEDIA_NEXT_TRACK);
+    map.insert("prevtrack", VK_MEDIA_PREV_TRACK);
+
+    // Browser keys
+    map.insert("browserback", VK_BROWSER_BACK);
+    map.insert("browserforward", VK_BROWSER_FORWARD);
+    map.insert("browserrefresh", VK_BROWSER_REFRESH);
+    map.insert("browserstop", VK_BROWSER_STOP);
+    map.insert("browsersearch", VK_BROWSER_SEARCH);
+    map.insert("browserfavorites", VK_BROWSER_FAVORITES);
+    map.insert("browserhome", VK_BROWSER_HOME);
+
+    // IME keys (Hangul/Kana and Hanja/Kanji share a code)
+    map.insert("hangul", VK_HANGUL);
+    map.insert("hanja", VK_HANJA);
+    map.insert("kana", VK_KANA);
+    map.insert("kanji", VK_KANJI);
+    map.insert("convert", VK_CONVERT);
+    map.insert("nonconvert", VK_NONCONVERT);
+
+    // Function keys (F1-F24 are consecutive)
+    for n in 1..=24u16 {
+        let key = Box::leak(format!("f{}", n).into_boxed_str());
+        map.insert(key, VIRTUAL_KEY(VK_F1.0 + n - 1));
+    }
+
+    // Letters (A-Z)
+    for c in 'a'..='z' {
+        let key = Box::leak(c.to_string().into_boxed_str());
+        map.insert(key, VIRTUAL_KEY(c.to_ascii_uppercase() as u16));
+    }
+
+    // Numbers (0-9)
+    for c in '0'..='9' {
+        let key = Box::leak(c.to_string().into_boxed_str());
+        map.insert(key, VIRTUAL_KEY(c as u16));
+    }
+
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_key_name_has_a_virtual_key() {
+        for name in keys::all() {
+            assert!(virtual_key(name).is_ok(), "{} is not mapped", name);
+        }
+        assert_eq!(virtual_key("F24").unwrap(), VK_F24);
+        assert_eq!(virtual_key("numpad3").unwrap(), VK_NUMPAD3);
+        assert!(virtual_key("nope").is_err());
+    }
+}
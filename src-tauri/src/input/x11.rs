@@ -0,0 +1,475 @@
+// Input backend (Linux) - XTest events sent to the X server in $DISPLAY
+//
+// Works against any X server, including Xvfb, so the agent can be driven
+// headless. Characters without a key in the current layout are typed by
+// binding them to a spare keycode. The spares are used in turn and a binding
+// is only replaced when its keycode comes round again, so a client still
+// handling the keystroke never sees the key unbound. The core protocol only
+// knows whole wheel notches, so partial ones are carried over.
+
+use crate::commands::input::MouseButton;
+use crate::input::backend::{InputBackend, WHEEL_NOTCH};
+use crate::input::keys;
+use anyhow::Result;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{
+    ConnectionExt as _, Keycode, Keysym, Window, BUTTON_PRESS_EVENT, BUTTON_RELEASE_EVENT,
+    KEY_PRESS_EVENT, KEY_RELEASE_EVENT, MOTION_NOTIFY_EVENT,
+};
+use x11rb::protocol::xtest::ConnectionExt as _;
+use x11rb::rust_connection::RustConnection;
+use x11rb::CURRENT_TIME;
+
+const XK_SHIFT_L: Keysym = 0xffe1;
+
+/// Most unbound keycodes borrowed for characters missing from the layout
+const MAX_SPARE_KEYCODES: usize = 8;
+
+/// Modifier keys released by the emergency stop
+const MODIFIER_KEYSYMS: [Keysym; 8] = [
+    0xffe1, 0xffe2, // Shift
+    0xffe3, 0xffe4, // Control
+    0xffe9, 0xffea, // Alt
+    0xffeb, 0xffec, // Super
+];
+
+/// XTest backend
+pub struct X11Input {
+    conn: RustConnection,
+    root: Window,
+    keymap: Mutex<Keymap>,
+    /// Wheel delta not yet sent as a notch (horizontal, vertical)
+    wheel: Mutex<(i32, i32)>,
+}
+
+impl X11Input {
+    /// Connect to the X server in $DISPLAY
+    pub fn connect() -> Result<Self> {
+        let (conn, screen) = x11rb::connect(None)
+            .map_err(|e| anyhow::anyhow!("Cannot connect to X server: {}", e))?;
+        let root = conn.setup().roots[screen].root;
+        let keymap = Keymap::load(&conn)?;
+        Ok(Self {
+            conn,
+            root,
+            keymap: Mutex::new(keymap),
+            wheel: Mutex::new((0, 0)),
+        })
+    }
+
+    fn fake(&self, event: u8, detail: u8, x: i16, y: i16) -> Result<()> {
+        self.conn
+            .xtest_fake_input(event, detail, CURRENT_TIME, self.root, x, y, 0)?;
+        Ok(())
+    }
+
+    fn fake_key(&self, keycode: Keycode, down: bool) -> Result<()> {
+        let event = if down {
+            KEY_PRESS_EVENT
+        } else {
+            KEY_RELEASE_EVENT
+        };
+        self.fake(event, keycode, 0, 0)
+    }
+
+    fn fake_button(&self, button: u8, down: bool) -> Result<()> {
+        let event = if down {
+            BUTTON_PRESS_EVENT
+        } else {
+            BUTTON_RELEASE_EVENT
+        };
+        self.fake(event, button, 0, 0)
+    }
+
+    fn named_key(&self, key: &str, down: bool) -> Result<()> {
+        let name = keys::key(key)?;
+        let keysym = keysym(name)
+            .ok_or_else(|| anyhow::anyhow!("Key {:?} is not supported on X11", name))?;
+        let (keycode, _) = self
+            .keymap()
+            .keycode(keysym)
+            .ok_or_else(|| anyhow::anyhow!("Key {:?} is not on the keyboard layout", name))?;
+        self.fake_key(keycode, down)?;
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    fn keymap(&self) -> std::sync::MutexGuard<'_, Keymap> {
+        self.keymap.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Press and release a keycode (with shift if the keysym is on the shifted level)
+    fn stroke(&self, keycode: Keycode, shift: Option<Keycode>) -> Result<()> {
+        if let Some(shift) = shift {
+            self.fake_key(shift, true)?;
+        }
+        self.fake_key(keycode, true)?;
+        self.fake_key(keycode, false)?;
+        if let Some(shift) = shift {
+            self.fake_key(shift, false)?;
+        }
+        self.conn.flush()?;
+        Ok(())
+    }
+}
+
+impl InputBackend for X11Input {
+    fn cursor_position(&self) -> Result<(i32, i32)> {
+        let pointer = self.conn.query_pointer(self.root)?.reply()?;
+        Ok((pointer.root_x as i32, pointer.root_y as i32))
+    }
+
+    fn move_cursor(&self, x: i32, y: i32) -> Result<()> {
+        let clamp = |v: i32| v.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+        self.fake(MOTION_NOTIFY_EVENT, 0, clamp(x), clamp(y))?;
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    fn button_down(&self, button: MouseButton) -> Result<()> {
+        self.fake_button(button_number(button), true)?;
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    fn button_up(&self, button: MouseButton) -> Result<()> {
+        self.fake_button(button_number(button), false)?;
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    fn scroll(&self, dx: i32, dy: i32) -> Result<()> {
+        let (dx, dy) = {
+            let mut wheel = self.wheel.lock().unwrap_or_else(|e| e.into_inner());
+            wheel.0 += dx;
+            wheel.1 += dy;
+            let notches = (wheel.0 / WHEEL_NOTCH, wheel.1 / WHEEL_NOTCH);
+            wheel.0 -= notches.0 * WHEEL_NOTCH;
+            wheel.1 -= notches.1 * WHEEL_NOTCH;
+            notches
+        };
+
+        // Buttons 4/5 turn the wheel up/down, 6/7 left/right
+        let vertical = if dy < 0 { 4 } else { 5 };
+        let horizontal = if dx < 0 { 6 } else { 7 };
+        for (button, notches) in [(vertical, dy.abs()), (horizontal, dx.abs())] {
+            for _ in 0..notches {
+                self.fake_button(button, true)?;
+                self.fake_button(button, false)?;
+            }
+        }
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    fn key_down(&self, key: &str) -> Result<()> {
+        self.named_key(key, true)
+    }
+
+    fn key_up(&self, key: &str) -> Result<()> {
+        self.named_key(key, false)
+    }
+
+    fn type_char(&self, c: char) -> Result<()> {
+        let keysym = char_keysym(c);
+        let mut keymap = self.keymap();
+
+        match keymap.keycode(keysym) {
+            Some((keycode, 0)) => self.stroke(keycode, None),
+            Some((keycode, 1)) => self.stroke(keycode, keymap.keycode(XK_SHIFT_L).map(|k| k.0)),
+            _ => {
+                // Not in the layout - bind it to a spare keycode (it stays bound)
+                let spare = keymap
+                    .bind_spare(&self.conn, keysym)?
+                    .ok_or_else(|| anyhow::anyhow!("No free keycode to type {:?}", c))?;
+                self.stroke(spare, None)
+            }
+        }
+    }
+
+    fn release_all(&self) -> Result<()> {
+        let keycodes: Vec<Keycode> = {
+            let keymap = self.keymap();
+            MODIFIER_KEYSYMS
+                .iter()
+                .filter_map(|keysym| keymap.keycode(*keysym).map(|k| k.0))
+                .collect()
+        };
+        for keycode in keycodes {
+            self.fake_key(keycode, false)?;
+        }
+        for button in [1, 2, 3] {
+            self.fake_button(button, false)?;
+        }
+        self.conn.flush()?;
+        Ok(())
+    }
+}
+
+impl Drop for X11Input {
+    /// Give the borrowed keycodes back to the layout
+    fn drop(&mut self) {
+        let keymap = self.keymap.get_mut().unwrap_or_else(|e| e.into_inner());
+        while let Some(keycode) = keymap.bound.pop_front() {
+            let _ = keymap.rebind(&self.conn, keycode, 0);
+        }
+        let _ = self.conn.flush();
+    }
+}
+
+fn button_number(button: MouseButton) -> u8 {
+    match button {
+        MouseButton::Left => 1,
+        MouseButton::Middle => 2,
+        MouseButton::Right => 3,
+    }
+}
+
+/// Keyboard mapping of the X server (keysyms per keycode)
+struct Keymap {
+    min_keycode: Keycode,
+    keysyms_per_keycode: u8,
+    keysyms: Vec<Keysym>,
+    /// Keycodes without any keysym, used to type characters missing from the layout
+    spares: Vec<Keycode>,
+    /// Spares currently bound to a character, the longest bound first
+    bound: VecDeque<Keycode>,
+}
+
+impl Keymap {
+    fn load(conn: &RustConnection) -> Result<Self> {
+        let setup = conn.setup();
+        let min_keycode = setup.min_keycode;
+        let count = setup.max_keycode - min_keycode + 1;
+        let reply = conn.get_keyboard_mapping(min_keycode, count)?.reply()?;
+        let per = reply.keysyms_per_keycode;
+
+        let spares = reply
+            .keysyms
+            .chunks(per.max(1) as usize)
+            .enumerate()
+            .filter(|(_, syms)| syms.iter().all(|&sym| sym == 0))
+            .map(|(index, _)| min_keycode + index as u8)
+            .take(MAX_SPARE_KEYCODES)
+            .collect();
+
+        Ok(Self {
+            min_keycode,
+            keysyms_per_keycode: per,
+            keysyms: reply.keysyms,
+            spares,
+            bound: VecDeque::new(),
+        })
+    }
+
+    /// Keycode producing `keysym` and the level it is on (0 = plain, 1 = shifted)
+    fn keycode(&self, keysym: Keysym) -> Option<(Keycode, usize)> {
+        let per = self.keysyms_per_keycode.max(1) as usize;
+        for level in 0..per.min(2) {
+            let found = self
+                .keysyms
+                .chunks(per)
+                .position(|syms| syms.get(level) == Some(&keysym));
+            if let Some(index) = found {
+                return Some((self.min_keycode + index as u8, level));
+            }
+        }
+        None
+    }
+
+    /// Bind a spare keycode to `keysym`
+    /// Returns None when the layout has no spare keycodes
+    fn bind_spare(&mut self, conn: &RustConnection, keysym: Keysym) -> Result<Option<Keycode>> {
+        let Some(keycode) = self.next_spare() else {
+            return Ok(None);
+        };
+        self.rebind(conn, keycode, keysym)?;
+        Ok(Some(keycode))
+    }
+
+    /// Spare keycode to bind next: an unused one, or else the one bound longest ago
+    fn next_spare(&mut self) -> Option<Keycode> {
+        let unused = self
+            .spares
+            .iter()
+            .copied()
+            .find(|spare| !self.bound.contains(spare));
+        let keycode = unused.or_else(|| self.bound.pop_front())?;
+        self.bound.push_back(keycode);
+        Some(keycode)
+    }
+
+    /// Bind `keycode` to `keysym` on every level (0 unbinds it)
+    fn rebind(&mut self, conn: &RustConnection, keycode: Keycode, keysym: Keysym) -> Result<()> {
+        let per = self.keysyms_per_keycode.max(1);
+        let syms = vec![keysym; per as usize];
+        conn.change_keyboard_mapping(1, keycode, per, &syms)?;
+        // Round trip so the new mapping is in effect before the keystroke
+        conn.get_input_focus()?.reply()?;
+
+        let start = (keycode - self.min_keycode) as usize * per as usize;
+        if let Some(slot) = self.keysyms.get_mut(start..start + per as usize) {
+            slot.copy_from_slice(&syms);
+        }
+        Ok(())
+    }
+}
+
+/// Keysym typing a character (Latin-1 keysyms match the code point)
+fn char_keysym(c: char) -> Keysym {
+    match c {
+        '\n' | '\r' => 0xff0d,
+        '\t' => 0xff09,
+        '\u{20}'..='\u{7e}' | '\u{a0}'..='\u{ff}' => c as Keysym,
+        _ => 0x0100_0000 | c as Keysym,
+    }
+}
+
+/// Keysym of a canonical key name
+fn keysym(name: &str) -> Option<Keysym> {
+    lazy_static::lazy_static! {
+        static ref KEYSYM_MAP: HashMap<&'static str, Keysym> = build_keysym_map();
+    }
+    KEYSYM_MAP.get(name).copied()
+}
+
+/// Build canonical key name to keysym mapping
+fn build_keysym_map() -> HashMap<&'static str, Keysym> {
+    let mut map = HashMap::new();
+
+    // Modifiers
+    map.insert("ctrl", 0xffe3);
+    map.insert("alt", 0xffe9);
+    map.insert("shift", 0xffe1);
+    map.insert("win", 0xffeb);
+
+    // Special keys
+    map.insert("enter", 0xff0d);
+    map.insert("tab", 0xff09);
+    map.insert("escape", 0xff1b);
+    map.insert("backspace", 0xff08);
+    map.insert("delete", 0xffff);
+    map.insert("insert", 0xff63);
+    map.insert("home", 0xff50);
+    map.insert("end", 0xff57);
+    map.insert("pageup", 0xff55);
+    map.insert("pagedown", 0xff56);
+    map.insert("space", 0x0020);
+    map.insert("capslock", 0xffe5);
+    map.insert("numlock", 0xff7f);
+    map.insert("scrolllock", 0xff14);
+    map.insert("printscreen", 0xff61);
+    map.insert("pause", 0xff13);
+    map.insert("apps", 0xff67);
+
+    // Arrow keys
+    map.insert("up", 0xff52);
+    map.insert("down", 0xff54);
+    map.insert("left", 0xff51);
+    map.insert("right", 0xff53);
+
+    // Numpad (KP_0 = 0xffb0)
+    map.insert("numadd", 0xffab);
+    map.insert("numsubtract", 0xffad);
+    map.insert("nummultiply", 0xffaa);
+    map.insert("numdivide", 0xffaf);
+    map.insert("numdecimal", 0xffae);
+    for n in 0..=9 {
+        let key = Box::leak(format!("num{}", n).into_boxed_str());
+        map.insert(key, 0xffb0 + n);
+    }
+
+    // Media keys (XF86Audio*)
+    map.insert("volumeup", 0x1008ff13);
+    map.insert("volumedown", 0x1008ff11);
+    map.insert("volumemute", 0x1008ff12);
+    map.insert("playpause", 0x1008ff14);
+    map.insert("mediastop", 0x1008ff15);
+    map.insert("prevtrack", 0x1008ff16);
+    map.insert("nexttrack", 0x1008ff17);
+
+    // Browser keys (XF86Back, XF86Forward, ...)
+    map.insert("browserback", 0x1008ff26);
+    map.insert("browserforward", 0x1008ff27);
+    map.insert("browserrefresh", 0x1008ff29);
+    map.insert("browserstop", 0x1008ff28);
+    map.insert("browsersearch", 0x1008ff1b);
+    map.insert("browserfavorites", 0x1008ff30);
+    map.insert("browserhome", 0x1008ff18);
+
+    // IME keys
+    map.insert("hangul", 0xff31);
+    map.insert("hanja", 0xff34);
+    map.insert("kana", 0xff27);
+    map.insert("kanji", 0xff21);
+    map.insert("convert", 0xff23);
+    map.insert("nonconvert", 0xff22);
+
+    // Function keys (F1 = 0xffbe)
+    for n in 1..=24 {
+        let key = Box::leak(format!("f{}", n).into_boxed_str());
+        map.insert(key, 0xffbd + n);
+    }
+
+    // Letters, numbers and punctuation (keysym = ASCII code)
+    for c in ('a'..='z').chain('0'..='9').chain(";=,-./`[\\]'".chars()) {
+        let key = Box::leak(c.to_string().into_boxed_str());
+        map.insert(key, c as Keysym);
+    }
+
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn characters_map_to_keysyms() {
+        assert_eq!(char_keysym('a'), 0x61);
+        assert_eq!(char_keysym('é'), 0xe9);
+        assert_eq!(char_keysym('\n'), 0xff0d);
+        assert_eq!(char_keysym('한'), 0x0100_d55c);
+    }
+
+    #[test]
+    fn every_key_name_has_a_keysym() {
+        for name in keys::all() {
+            assert!(keysym(name).is_some(), "{} is not mapped", name);
+        }
+        assert_eq!(keysym("f12"), Some(0xffc9));
+        assert_eq!(keysym("f24"), Some(0xffd5));
+        assert_eq!(keysym("num7"), Some(0xffb7));
+        assert_eq!(keysym("nope"), None);
+    }
+
+    #[test]
+    fn spare_keycodes_are_used_in_turn() {
+        let mut keymap = Keymap {
+            min_keycode: 8,
+            keysyms_per_keycode: 2,
+            keysyms: Vec::new(),
+            spares: vec![200, 201, 202],
+            bound: VecDeque::new(),
+        };
+
+        let picked: Vec<_> = (0..5).map(|_| keymap.next_spare().unwrap()).collect();
+        // The binding typed last is never the one replaced next
+        assert_eq!(picked, [200, 201, 202, 200, 201]);
+
+        keymap.spares.clear();
+        keymap.bound.clear();
+        assert_eq!(keymap.next_spare(), None);
+    }
+
+    /// Needs an X server: `xvfb-run cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn moves_the_pointer() {
+        let input = X11Input::connect().unwrap();
+        input.move_cursor(40, 30).unwrap();
+        assert_eq!(input.cursor_position().unwrap(), (40, 30));
+    }
+}
@@ -7,6 +7,9 @@ mod guides;
 mod input;
 mod llm;
 mod screen;
+mod sessions;
+mod transcripts;
+mod usage;
 
 use tauri::menu::{Menu, MenuItem};
 use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
@@ -79,6 +82,25 @@ pub fn run() {
                 })
                 .build(app)?;
 
+            // Register global shortcuts (a bad shortcut must not prevent startup)
+            let config = config::storage::load_config().unwrap_or_default();
+            if let Err(e) = config::shortcuts::register_shortcuts(app.handle(), &config) {
+                log::warn!("Failed to register global shortcuts: {}", e);
+            }
+
+            // Remove expired transcripts and their screenshots in the background
+            let retention_days = config.transcripts.retention_days;
+            if retention_days > 0 {
+                std::thread::spawn(move || {
+                    let max_age = std::time::Duration::from_secs(retention_days * 24 * 60 * 60);
+                    let removed = transcripts::recorder::get_transcripts_dir()
+                        .and_then(|dir| transcripts::recorder::remove_expired(&dir, max_age));
+                    if let Err(e) = removed {
+                        log::warn!("Failed to remove expired transcripts: {}", e);
+                    }
+                });
+            }
+
             Ok(())
         })
         // Commands
@@ -90,10 +112,17 @@ pub fn run() {
             commands::input::mouse_click,
             commands::input::keyboard_type,
             commands::input::keyboard_press,
+            commands::input::emergency_stop,
             commands::config::get_config,
             commands::config::save_config,
             commands::config::test_api_connection,
             commands::llm::send_message,
+            commands::llm::start_agent_run,
+            commands::llm::subscribe_agent_run,
+            commands::llm::cancel_agent_run,
+            commands::llm::pause_agent_run,
+            commands::llm::resume_agent_run,
+            commands::llm::respond_approval,
             commands::guides::guide_list,
             commands::guides::guide_preview,
             commands::guides::guide_read,
@@ -106,6 +135,12 @@ pub fn run() {
             commands::overlay::overlay_click,
             commands::overlay::overlay_status,
             commands::overlay::overlay_set_control,
+            commands::usage::get_usage,
+            commands::sessions::list_sessions,
+            commands::sessions::load_session,
+            commands::sessions::rename_session,
+            commands::sessions::delete_session,
+            commands::transcripts::export_run,
         ])
         // Window events - hide instead of close to support tray
         .on_window_event(|window, event| {
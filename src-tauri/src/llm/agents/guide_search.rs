@@ -1,11 +1,14 @@
 // Guide search sub-agent - uses LLM to autonomously search guides
 
+use crate::commands::config::ToolCallMode;
 use crate::guides::storage::{list_guides, preview_guide, read_guide, GuideEntry};
-use crate::llm::client::chat_completion;
 use crate::llm::prompts::GUIDE_SEARCH_AGENT_PROMPT;
-use crate::llm::tools::Tool;
+use crate::llm::runner::text_protocol::{
+    invalid_call_message, parse_response, tool_protocol_prompt, tool_result_message,
+};
+use crate::llm::tools::{parse_arguments, SideEffect, Tool, ToolCategory};
 use crate::llm::types::{
-    FunctionDef, Message, MessageContent, ToolContext, ToolDef, ToolResult,
+    FunctionDef, Message, ToolCall, ToolContext, ToolDef, ToolResult,
 };
 use anyhow::Result;
 use async_trait::async_trait;
@@ -37,6 +40,14 @@ impl Tool for GuideSearchAgentTool {
         })
     }
 
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Agent
+    }
+
+    fn side_effect(&self) -> SideEffect {
+        SideEffect::SubAgent
+    }
+
     async fn execute(&self, params: Value, ctx: &ToolContext) -> anyhow::Result<ToolResult> {
         let query = params["query"]
             .as_str()
@@ -49,8 +60,8 @@ impl Tool for GuideSearchAgentTool {
     }
 }
 
-/// Get tools available to the guide search sub-agent
-fn get_guide_tools() -> Vec<ToolDef> {
+/// Get tools available to the guide search sub-agent (also used by the planner)
+pub(super) fn get_guide_tools() -> Vec<ToolDef> {
     vec![
         ToolDef {
             tool_type: "function".to_string(),
@@ -106,6 +117,21 @@ fn get_guide_tools() -> Vec<ToolDef> {
     ]
 }
 
+/// Validate a guide tool call's arguments and execute it
+pub(super) fn run_guide_tool_call(tools: &[ToolDef], call: &ToolCall) -> String {
+    let name = &call.function.name;
+    let schema = tools
+        .iter()
+        .find(|tool| tool.function.name == *name)
+        .map(|tool| tool.function.parameters.clone())
+        .unwrap_or(Value::Null);
+
+    match parse_arguments(&call.function.arguments, &schema) {
+        Ok(args) => execute_guide_tool(name, &args),
+        Err(e) => format!("Error: {}", e.to_tool_error(name, &schema)),
+    }
+}
+
 /// Execute a guide tool call
 fn execute_guide_tool(name: &str, args: &Value) -> String {
     match name {
@@ -161,65 +187,175 @@ fn format_ls_result(entries: &[GuideEntry]) -> String {
 
 /// Run the guide search sub-agent loop
 async fn run_guide_search_agent(query: &str, ctx: &ToolContext) -> Result<String> {
+    let ctx = ctx.sub_agent()?;
     let tools = get_guide_tools();
 
+    // In text mode tools are described in the prompt instead of sent natively
+    let text_mode = ctx.tool_mode == ToolCallMode::Text;
+    let api_tools = if text_mode { None } else { Some(tools.clone()) };
+
+    let mut system_prompt = GUIDE_SEARCH_AGENT_PROMPT.to_string();
+    if text_mode {
+        system_prompt.push_str(&tool_protocol_prompt(&tools));
+    }
+
     // Initial messages
     let mut messages = vec![
-        Message {
-            role: "system".to_string(),
-            content: MessageContent::Text(GUIDE_SEARCH_AGENT_PROMPT.to_string()),
-            tool_call_id: None,
-        },
-        Message {
-            role: "user".to_string(),
-            content: MessageContent::Text(format!("Find a guide for: {}", query)),
-            tool_call_id: None,
-        },
+        Message::system(system_prompt),
+        Message::user(format!("Find a guide for: {}", query)),
     ];
 
-    // Agent loop - max 10 iterations to prevent infinite loops
-    for _ in 0..10 {
-        let response = chat_completion(&ctx.api_config, messages.clone(), Some(tools.clone())).await?;
+    // Agent loop - bounded by the run budget's sub-agent iterations
+    for _ in 0..ctx.budget.sub_agent_max_iterations {
+        ctx.check_limits()?;
+        let response = ctx.provider.chat(messages.clone(), api_tools.clone()).await?;
 
         let choice = response
             .choices
             .first()
             .ok_or_else(|| anyhow::anyhow!("No response from API"))?;
 
+        let content = choice.message.content.clone().unwrap_or_default();
+
+        // Resolve tool calls - native, or parsed from the text in text mode
+        let (thought, tool_calls) = if text_mode {
+            match parse_response(&content, &tools) {
+                Ok(turn) => (turn.thought, turn.tool_calls),
+                Err(e) => {
+                    messages.push(Message::assistant(content));
+                    messages.push(invalid_call_message(&e));
+                    continue;
+                }
+            }
+        } else {
+            (content.clone(), choice.message.tool_calls.clone().unwrap_or_default())
+        };
+
         // Check if there are tool calls
-        if let Some(tool_calls) = &choice.message.tool_calls {
+        if !tool_calls.is_empty() {
             // Add assistant message with tool calls
-            messages.push(Message {
-                role: "assistant".to_string(),
-                content: MessageContent::Text(choice.message.content.clone().unwrap_or_default()),
-                tool_call_id: None,
+            messages.push(if text_mode {
+                Message::assistant(content)
+            } else {
+                choice.message.to_message()
             });
 
             // Execute each tool call and add results
-            for tool_call in tool_calls {
-                let args: Value =
-                    serde_json::from_str(&tool_call.function.arguments).unwrap_or(json!({}));
+            for tool_call in &tool_calls {
+                let result = run_guide_tool_call(&tools, tool_call);
 
-                let result = execute_guide_tool(&tool_call.function.name, &args);
-
-                messages.push(Message {
-                    role: "tool".to_string(),
-                    content: MessageContent::Text(result),
-                    tool_call_id: Some(tool_call.id.clone()),
-                });
+                if text_mode {
+                    messages.push(tool_result_message(tool_call, &result));
+                } else {
+                    messages.push(Message::tool(tool_call, result));
+                }
             }
         } else {
             // No tool calls - agent is done, return the response
-            let response_text = choice
-                .message
-                .content
-                .clone()
-                .unwrap_or_else(|| "없음".to_string());
-
-            return Ok(response_text);
+            if thought.is_empty() {
+                return Ok("없음".to_string());
+            }
+            return Ok(thought);
         }
     }
 
     // Max iterations reached
     Ok("없음".to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::fake::{agent_context, FakeBackend};
+    use crate::llm::mock_server::{MockResponse, MockServer};
+
+    /// Contents of the tool messages sent in a recorded request
+    fn tool_results(body: &str) -> Vec<String> {
+        let request: Value = serde_json::from_str(body).unwrap();
+        request["messages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|m| m["role"] == "tool")
+            .map(|m| m["content"].as_str().unwrap_or_default().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn answer_of_the_sub_agent_is_the_result() {
+        let server = MockServer::start(vec![
+            MockResponse::tool_calls(&[("guide_read", json!({}))]),
+            MockResponse::answer("Save with Ctrl+S."),
+        ])
+        .await;
+        let ctx = agent_context(&FakeBackend::new(), &server.url);
+
+        let result = GuideSearchAgentTool
+            .execute(json!({ "query": "save a file" }), &ctx)
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.output, "Save with Ctrl+S.");
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].body.contains("Find a guide for: save a file"));
+        // The invalid call got the argument error back instead of reading a guide
+        let results = tool_results(&requests[1].body);
+        assert_eq!(results.len(), 1);
+        assert!(results[0].starts_with("Error: "), "{}", results[0]);
+        assert!(results[0].contains("file_path"), "{}", results[0]);
+    }
+
+    #[tokio::test]
+    async fn nothing_found_when_the_sub_agent_gives_up() {
+        let server = MockServer::start(vec![
+            MockResponse::tool_calls(&[("guide_read", json!({}))]),
+            MockResponse::answer(""),
+        ])
+        .await;
+        let ctx = agent_context(&FakeBackend::new(), &server.url);
+
+        let result = GuideSearchAgentTool
+            .execute(json!({ "query": "save a file" }), &ctx)
+            .await
+            .unwrap();
+
+        assert_eq!(result.output, "없음");
+    }
+
+    #[tokio::test]
+    async fn nothing_found_after_the_iteration_limit() {
+        let server = MockServer::start(vec![
+            MockResponse::tool_calls(&[("guide_read", json!({}))]),
+            MockResponse::answer("Save with Ctrl+S."),
+        ])
+        .await;
+        let mut ctx = agent_context(&FakeBackend::new(), &server.url);
+        ctx.budget.sub_agent_max_iterations = 1;
+
+        let result = GuideSearchAgentTool
+            .execute(json!({ "query": "save a file" }), &ctx)
+            .await
+            .unwrap();
+
+        assert_eq!(result.output, "없음");
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn depth_limit_stops_nested_searches() {
+        let server = MockServer::start(vec![]).await;
+        let mut ctx = agent_context(&FakeBackend::new(), &server.url);
+        ctx.depth = ctx.budget.max_sub_agent_depth;
+
+        let result = GuideSearchAgentTool
+            .execute(json!({ "query": "save a file" }), &ctx)
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        assert!(result.error.unwrap().starts_with("Sub-agent depth limit"));
+        assert!(server.requests().is_empty());
+    }
+}
@@ -0,0 +1,309 @@
+// Planner sub-agent - turns a request into a numbered plan and revises it
+
+use super::guide_search::{get_guide_tools, run_guide_tool_call};
+use crate::commands::config::ToolCallMode;
+use crate::llm::prompts::PLANNER_AGENT_PROMPT;
+use crate::llm::runner::plan::{Plan, SharedPlan};
+use crate::llm::runner::text_protocol::{
+    invalid_call_message, parse_response, tool_protocol_prompt, tool_result_message,
+};
+use crate::llm::tools::{SideEffect, Tool, ToolCategory};
+use crate::llm::types::{ContentPart, ImageUrl, Message, ToolContext, ToolResult};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+/// Replan tool - asks the planner for new remaining steps (plan mode only)
+pub struct PlannerAgentTool;
+
+#[async_trait]
+impl Tool for PlannerAgentTool {
+    fn name(&self) -> &str {
+        "replan"
+    }
+
+    fn description(&self) -> &str {
+        "Replace the remaining steps of the plan. A planner sub-agent plans the rest of the task from the finished steps and the reason given."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "reason": {
+                    "type": "string",
+                    "description": "Why the current plan doesn't work and what the screen shows now"
+                }
+            },
+            "required": ["reason"]
+        })
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Plan
+    }
+
+    fn side_effect(&self) -> SideEffect {
+        SideEffect::SubAgent
+    }
+
+    async fn execute(&self, params: Value, ctx: &ToolContext) -> anyhow::Result<ToolResult> {
+        let reason = params["reason"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing reason"))?;
+        let Some(plan) = &ctx.plan else {
+            return Ok(ToolResult::error("This run has no plan"));
+        };
+
+        match replan(plan, reason, ctx).await {
+            Ok(rendered) => Ok(ToolResult::success(format!("New plan:\n{}", rendered))),
+            Err(e) => Ok(ToolResult::error(e.to_string())),
+        }
+    }
+}
+
+/// Replace the remaining steps of a plan and return the rendered new plan
+pub async fn replan(plan: &SharedPlan, reason: &str, ctx: &ToolContext) -> Result<String> {
+    let current = plan.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let request = format!(
+        "Task: {}\n\nCurrent plan:\n{}\n\nThe plan has to change: {}\n\nPlan the remaining work.",
+        current.task,
+        current.render(),
+        reason
+    );
+
+    let steps = run_planner_agent(request, None, ctx).await?;
+    let mut plan = plan.lock().unwrap_or_else(|e| e.into_inner());
+    plan.revise(steps);
+    Ok(plan.render())
+}
+
+/// Create the first plan for a task from the request and the current screen
+pub async fn create_plan(
+    task: &str,
+    screen: Option<(&str, &str)>,
+    ctx: &ToolContext,
+) -> Result<Plan> {
+    let steps = run_planner_agent(format!("Task: {}", task), screen, ctx).await?;
+    Ok(Plan::new(task, steps))
+}
+
+/// Run the planner sub-agent loop and return the planned steps
+/// `screen` is the screenshot (data URL) and UI tree to plan from
+async fn run_planner_agent(
+    request: String,
+    screen: Option<(&str, &str)>,
+    ctx: &ToolContext,
+) -> Result<Vec<String>> {
+    let ctx = ctx.sub_agent()?;
+    let tools = get_guide_tools();
+
+    // In text mode tools are described in the prompt instead of sent natively
+    let text_mode = ctx.tool_mode == ToolCallMode::Text;
+    let api_tools = if text_mode { None } else { Some(tools.clone()) };
+
+    let mut system_prompt = PLANNER_AGENT_PROMPT.to_string();
+    if text_mode {
+        system_prompt.push_str(&tool_protocol_prompt(&tools));
+    }
+
+    let user_message = match screen {
+        Some((screenshot, ui_tree)) => Message::user_parts(vec![
+            ContentPart::Text {
+                text: format!(
+                    "Current screen state:\n\nUI Elements:\n{}\n\n{}",
+                    ui_tree, request
+                ),
+            },
+            ContentPart::ImageUrl {
+                image_url: ImageUrl {
+                    url: screenshot.to_string(),
+                    detail: Some("high".to_string()),
+                },
+            },
+        ]),
+        None => Message::user(request),
+    };
+    let mut messages = vec![Message::system(system_prompt), user_message];
+
+    // Agent loop - bounded by the run budget's sub-agent iterations
+    for _ in 0..ctx.budget.sub_agent_max_iterations {
+        ctx.check_limits()?;
+        // Abandon the request as soon as the run is cancelled
+        let response = tokio::select! {
+            response = ctx.provider.chat(messages.clone(), api_tools.clone()) => response?,
+            _ = ctx.cancel.cancelled() => return Err(anyhow::anyhow!("Cancelled")),
+        };
+
+        let choice = response
+            .choices
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("No response from API"))?;
+
+        let content = choice.message.content.clone().unwrap_or_default();
+
+        // Resolve tool calls - native, or parsed from the text in text mode
+        let (thought, tool_calls) = if text_mode {
+            match parse_response(&content, &tools) {
+                Ok(turn) => (turn.thought, turn.tool_calls),
+                Err(e) => {
+                    messages.push(Message::assistant(content));
+                    messages.push(invalid_call_message(&e));
+                    continue;
+                }
+            }
+        } else {
+            (
+                content.clone(),
+                choice.message.tool_calls.clone().unwrap_or_default(),
+            )
+        };
+
+        // No tool calls - the answer is the plan
+        if tool_calls.is_empty() {
+            let steps = Plan::parse_steps(&thought);
+            if steps.is_empty() {
+                return Err(anyhow::anyhow!("The planner returned no steps"));
+            }
+            return Ok(steps);
+        }
+
+        messages.push(if text_mode {
+            Message::assistant(content)
+        } else {
+            choice.message.to_message()
+        });
+
+        // Guide lookups
+        for tool_call in &tool_calls {
+            let result = run_guide_tool_call(&tools, tool_call);
+
+            if text_mode {
+                messages.push(tool_result_message(tool_call, &result));
+            } else {
+                messages.push(Message::tool(tool_call, result));
+            }
+        }
+    }
+
+    Err(anyhow::anyhow!("The planner did not finish a plan"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::fake::{agent_context, FakeBackend};
+    use crate::llm::mock_server::{MockResponse, MockServer};
+    use crate::llm::runner::plan::PlanStepStatus;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn plan_is_parsed_from_the_numbered_answer() {
+        let server = MockServer::start(vec![MockResponse::answer(
+            "Here is the plan:\n1. Open Notepad\n2) Type hello\nThat's all.",
+        )])
+        .await;
+        let ctx = agent_context(&FakeBackend::new(), &server.url);
+
+        let plan = create_plan("Type hello in Notepad", None, &ctx)
+            .await
+            .unwrap();
+
+        assert_eq!(plan.task, "Type hello in Notepad");
+        assert_eq!(
+            plan.render(),
+            "1. [pending] Open Notepad\n2. [pending] Type hello"
+        );
+        assert!(server.requests()[0]
+            .body
+            .contains("Task: Type hello in Notepad"));
+    }
+
+    #[tokio::test]
+    async fn answer_without_steps_is_an_error() {
+        let server = MockServer::start(vec![MockResponse::answer("I can't plan this.")]).await;
+        let ctx = agent_context(&FakeBackend::new(), &server.url);
+
+        let error = create_plan("Type hello in Notepad", None, &ctx)
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "The planner returned no steps");
+    }
+
+    #[tokio::test]
+    async fn cancelling_the_run_abandons_the_request() {
+        let server = MockServer::start(vec![
+            MockResponse::answer("1. Open Notepad").with_delay(Duration::from_secs(30))
+        ])
+        .await;
+        let ctx = agent_context(&FakeBackend::new(), &server.url);
+        let cancel = ctx.cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            cancel.cancel();
+        });
+
+        let error = tokio::time::timeout(
+            Duration::from_secs(5),
+            create_plan("Type hello in Notepad", None, &ctx),
+        )
+        .await
+        .expect("the planner kept waiting for the model")
+        .unwrap_err();
+
+        assert_eq!(error.to_string(), "Cancelled");
+    }
+
+    #[tokio::test]
+    async fn replan_keeps_the_finished_steps() {
+        let server = MockServer::start(vec![MockResponse::answer(
+            "1. Click Save\n2. Close Notepad",
+        )])
+        .await;
+        let mut plan = Plan::new(
+            "Save the note",
+            vec![
+                "Open Notepad".to_string(),
+                "Open the Save dialog".to_string(),
+            ],
+        );
+        plan.update(1, PlanStepStatus::Done, None, None).unwrap();
+        let plan = Arc::new(Mutex::new(plan));
+        let ctx = ToolContext {
+            plan: Some(plan.clone()),
+            ..agent_context(&FakeBackend::new(), &server.url)
+        };
+
+        let result = PlannerAgentTool
+            .execute(json!({ "reason": "The dialog opened by itself" }), &ctx)
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        let expected =
+            "1. [done] Open Notepad\n2. [pending] Click Save\n3. [pending] Close Notepad";
+        assert_eq!(result.output, format!("New plan:\n{}", expected));
+        let plan = plan.lock().unwrap();
+        assert_eq!(plan.render(), expected);
+        assert_eq!(plan.revision, 1);
+        let request = &server.requests()[0].body;
+        assert!(request.contains("The plan has to change: The dialog opened by itself"));
+        assert!(request.contains("1. [done] Open Notepad"));
+    }
+
+    #[tokio::test]
+    async fn replan_needs_a_plan() {
+        let server = MockServer::start(vec![]).await;
+        let ctx = agent_context(&FakeBackend::new(), &server.url);
+
+        let result = PlannerAgentTool
+            .execute(json!({ "reason": "Stuck" }), &ctx)
+            .await
+            .unwrap();
+
+        assert_eq!(result.error.as_deref(), Some("This run has no plan"));
+        assert!(server.requests().is_empty());
+    }
+}
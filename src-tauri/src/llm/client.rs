@@ -1,10 +1,14 @@
 // OpenAI-compatible API client
 
 use crate::commands::config::ApiConfig;
+use crate::llm::error::LlmError;
+use crate::llm::retry::with_retry;
+use crate::llm::stream::{StreamAccumulator, StreamDelta};
 use crate::llm::types::{Message, ToolDef};
 use anyhow::Result;
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder, Response};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 /// Chat completion request
 #[derive(Debug, Serialize)]
@@ -17,6 +21,16 @@ pub struct ChatRequest {
     pub tool_choice: Option<String>,
     pub max_tokens: u32,
     pub temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<StreamOptions>,
+}
+
+/// Streaming options (ask for usage in the final chunk)
+#[derive(Debug, Serialize)]
+pub struct StreamOptions {
+    pub include_usage: bool,
 }
 
 /// Chat completion response
@@ -24,7 +38,6 @@ pub struct ChatRequest {
 pub struct ChatResponse {
     pub choices: Vec<Choice>,
     #[serde(default)]
-    #[allow(dead_code)]
     pub usage: Option<Usage>,
 }
 
@@ -47,92 +60,543 @@ pub struct ResponseMessage {
     pub tool_calls: Option<Vec<crate::llm::types::ToolCall>>,
 }
 
+impl ResponseMessage {
+    /// Convert into the history message echoed back on the next request
+    /// (tool calls are kept so the following `tool` messages have a parent)
+    pub fn to_message(&self) -> Message {
+        match &self.tool_calls {
+            Some(calls) if !calls.is_empty() => {
+                Message::assistant_tool_calls(self.content.clone(), calls.clone())
+            }
+            _ => Message::assistant(self.content.clone().unwrap_or_default()),
+        }
+    }
+}
+
 /// Token usage
-#[allow(dead_code)]
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub struct Usage {
     pub prompt_tokens: u32,
     pub completion_tokens: u32,
     pub total_tokens: u32,
 }
 
-/// Test API connection
-pub async fn test_connection(config: &ApiConfig) -> Result<String> {
-    use crate::llm::types::MessageContent;
-
-    let client = Client::new();
-
-    let request = ChatRequest {
-        model: config.model.clone(),
-        messages: vec![Message {
-            role: "user".to_string(),
-            content: MessageContent::Text("Say 'Hello' in one word.".to_string()),
-            tool_call_id: None,
-        }],
-        tools: None,
-        tool_choice: None,
-        max_tokens: 10,
-        temperature: 0.0,
-    };
+/// Build an HTTP client with the configured connect timeout
+pub fn http_client(config: &ApiConfig) -> Result<Client, LlmError> {
+    Client::builder()
+        .connect_timeout(Duration::from_secs(config.connect_timeout_secs))
+        .build()
+        .map_err(LlmError::from_reqwest)
+}
 
-    let response = client
+/// Build a POST to /chat/completions with auth headers
+/// The Bearer header is omitted when no key is set (local servers)
+fn request_builder(client: &Client, config: &ApiConfig) -> RequestBuilder {
+    let builder = client
         .post(format!(
             "{}/chat/completions",
             config.endpoint.trim_end_matches('/')
         ))
-        .header("Authorization", format!("Bearer {}", config.api_key))
-        .header("Content-Type", "application/json")
-        .json(&request)
-        .send()
-        .await?;
+        .header("Content-Type", "application/json");
 
-    if response.status().is_success() {
-        let chat_response: ChatResponse = response.json().await?;
-        let reply = chat_response
-            .choices
-            .first()
-            .and_then(|c| c.message.content.clone())
-            .unwrap_or_else(|| "Connected".to_string());
-        Ok(reply)
+    if config.api_key.is_empty() {
+        builder
     } else {
-        let error_text = response.text().await?;
-        Err(anyhow::anyhow!("API error: {}", error_text))
+        builder.header("Authorization", format!("Bearer {}", config.api_key))
+    }
+}
+
+/// Send a request and turn non-success statuses into typed errors
+pub async fn send_checked(request: RequestBuilder) -> Result<Response, LlmError> {
+    let response = request.send().await.map_err(LlmError::from_reqwest)?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let headers = response.headers().clone();
+    let body = response.text().await.map_err(LlmError::from_reqwest)?;
+    Err(LlmError::from_response(status, &headers, &body))
+}
+
+/// Read and parse a JSON response body
+pub async fn read_json<T: serde::de::DeserializeOwned>(response: Response) -> Result<T, LlmError> {
+    let body = response.text().await.map_err(LlmError::from_reqwest)?;
+    serde_json::from_str(&body).map_err(|e| LlmError::MalformedResponse(e.to_string()))
+}
+
+/// Drop fields the Chat Completions API doesn't accept on messages
+fn wire_messages(mut messages: Vec<Message>) -> Vec<Message> {
+    for message in &mut messages {
+        message.is_error = false;
     }
+    messages
 }
 
 /// Send chat completion request
+/// Transient failures are retried according to `config.retry`
 pub async fn chat_completion(
     config: &ApiConfig,
     messages: Vec<Message>,
     tools: Option<Vec<ToolDef>>,
 ) -> Result<ChatResponse> {
-    let client = Client::new();
+    let client = http_client(config)?;
 
+    let tool_choice = tools.as_ref().map(|_| "auto".to_string());
     let request = ChatRequest {
         model: config.model.clone(),
-        messages,
+        messages: wire_messages(messages),
         tools,
-        tool_choice: Some("auto".to_string()),
+        tool_choice,
         max_tokens: config.max_tokens,
         temperature: config.temperature,
+        stream: None,
+        stream_options: None,
     };
 
-    let response = client
-        .post(format!(
-            "{}/chat/completions",
-            config.endpoint.trim_end_matches('/')
-        ))
-        .header("Authorization", format!("Bearer {}", config.api_key))
-        .header("Content-Type", "application/json")
-        .json(&request)
-        .send()
+    let chat_response = with_retry(&config.retry, || async {
+        let response = send_checked(
+            request_builder(&client, config)
+                .timeout(Duration::from_secs(config.timeout_secs))
+                .json(&request),
+        )
         .await?;
+        read_json::<ChatResponse>(response).await
+    })
+    .await?;
 
-    if response.status().is_success() {
-        let chat_response: ChatResponse = response.json().await?;
-        Ok(chat_response)
-    } else {
-        let error_text = response.text().await?;
-        Err(anyhow::anyhow!("API error: {}", error_text))
+    Ok(chat_response)
+}
+
+/// Send chat completion request with SSE streaming
+/// `on_delta` is called for every content or tool call fragment as it arrives;
+/// the assembled response is returned once the stream ends.
+/// Only opening the stream is retried - once deltas were emitted a failure is final.
+pub async fn chat_completion_stream<F>(
+    config: &ApiConfig,
+    messages: Vec<Message>,
+    tools: Option<Vec<ToolDef>>,
+    mut on_delta: F,
+) -> Result<ChatResponse>
+where
+    F: FnMut(StreamDelta) + Send,
+{
+    let client = http_client(config)?;
+
+    let tool_choice = tools.as_ref().map(|_| "auto".to_string());
+    let request = ChatRequest {
+        model: config.model.clone(),
+        messages: wire_messages(messages),
+        tools,
+        tool_choice,
+        max_tokens: config.max_tokens,
+        temperature: config.temperature,
+        stream: Some(true),
+        stream_options: Some(StreamOptions {
+            include_usage: true,
+        }),
+    };
+
+    // The timeout applies to the response headers and then to the gap
+    // between chunks, not the whole stream
+    let idle_timeout = Duration::from_secs(config.timeout_secs);
+
+    let mut response = with_retry(&config.retry, || async {
+        let send = send_checked(
+            request_builder(&client, config)
+                .header("Accept", "text/event-stream")
+                .json(&request),
+        );
+        tokio::time::timeout(idle_timeout, send)
+            .await
+            .map_err(|_| LlmError::Timeout)?
+    })
+    .await?;
+
+    let mut accumulator = StreamAccumulator::new();
+    loop {
+        let chunk = tokio::time::timeout(idle_timeout, response.chunk())
+            .await
+            .map_err(|_| LlmError::Timeout)?
+            .map_err(LlmError::from_reqwest)?;
+        let Some(chunk) = chunk else {
+            break;
+        };
+
+        let deltas = accumulator
+            .push(&chunk)
+            .map_err(|e| LlmError::MalformedResponse(e.to_string()))?;
+        for delta in deltas {
+            on_delta(delta);
+        }
+        if accumulator.is_done() {
+            break;
+        }
+    }
+
+    Ok(accumulator
+        .finish()
+        .map_err(|e| LlmError::MalformedResponse(e.to_string()))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::config::RetryConfig;
+    use crate::llm::mock_server::{MockResponse, MockServer};
+    use crate::llm::types::{FunctionDef, MessageContent};
+    use serde_json::{json, Value};
+    use std::time::Instant;
+
+    const TOOL_CALL_RESPONSE: &str =
+        include_str!("../../tests/fixtures/chat_tool_call_response.json");
+    const TOOL_CALL_RESPONSE_SSE: &str =
+        include_str!("../../tests/fixtures/chat_tool_call_response.sse");
+    const FINAL_RESPONSE: &str = include_str!("../../tests/fixtures/chat_final_response.json");
+    const FOLLOWUP_REQUEST: &str =
+        include_str!("../../tests/fixtures/chat_tool_call_followup_request.json");
+
+    fn click_tool() -> ToolDef {
+        ToolDef {
+            tool_type: "function".to_string(),
+            function: FunctionDef {
+                name: "mouse_click".to_string(),
+                description: "Click at a position".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "x": { "type": "integer" },
+                        "y": { "type": "integer" }
+                    },
+                    "required": ["x", "y"]
+                }),
+            },
+        }
+    }
+
+    /// Rebuild the second-iteration history the agent loops send
+    fn followup_history(response: &ChatResponse) -> Vec<Message> {
+        let message = &response.choices[0].message;
+        let mut messages = vec![
+            Message::system("You are an automation agent."),
+            Message::user("Open notepad"),
+            message.to_message(),
+        ];
+
+        let results = ["Clicked at (640, 360)", "Typed: notepad"];
+        for (call, result) in message.tool_calls.as_ref().unwrap().iter().zip(results) {
+            messages.push(Message::tool(call, result));
+        }
+        messages
+    }
+
+    #[test]
+    fn tool_call_history_matches_recorded_request() {
+        let response: ChatResponse = serde_json::from_str(TOOL_CALL_RESPONSE).unwrap();
+
+        let request = ChatRequest {
+            model: "gpt-4o".to_string(),
+            messages: followup_history(&response),
+            tools: Some(vec![click_tool()]),
+            tool_choice: Some("auto".to_string()),
+            max_tokens: 4096,
+            temperature: 0.5,
+            stream: None,
+            stream_options: None,
+        };
+
+        let expected: Value = serde_json::from_str(FOLLOWUP_REQUEST).unwrap();
+        assert_eq!(serde_json::to_value(&request).unwrap(), expected);
+    }
+
+    #[test]
+    fn failed_tool_results_are_not_flagged_on_the_wire() {
+        let response: ChatResponse = serde_json::from_str(TOOL_CALL_RESPONSE).unwrap();
+        let call = &response.choices[0].message.tool_calls.as_ref().unwrap()[0];
+        let failed = Message::tool(call, "Error: Missing x").with_error(true);
+
+        // Kept with the history, not sent to Chat Completions servers
+        assert_eq!(serde_json::to_value(&failed).unwrap()["is_error"], true);
+        let sent = serde_json::to_value(wire_messages(vec![failed])).unwrap();
+        assert!(sent[0].get("is_error").is_none());
+    }
+
+    #[test]
+    fn assistant_tool_call_turn_has_null_content() {
+        let response: ChatResponse = serde_json::from_str(TOOL_CALL_RESPONSE).unwrap();
+        let value = serde_json::to_value(response.choices[0].message.to_message()).unwrap();
+
+        assert_eq!(value["content"], Value::Null);
+        assert!(value.as_object().unwrap().contains_key("content"));
+        assert_eq!(value["tool_calls"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn recorded_history_round_trips_unchanged() {
+        let expected: Value = serde_json::from_str(FOLLOWUP_REQUEST).unwrap();
+        let messages: Vec<Message> =
+            serde_json::from_value(expected["messages"].clone()).unwrap();
+
+        assert!(messages[2].content.is_none());
+        assert_eq!(messages[3].name.as_deref(), Some("mouse_click"));
+        assert_eq!(
+            serde_json::to_value(&messages).unwrap(),
+            expected["messages"]
+        );
+    }
+
+    #[test]
+    fn streamed_tool_calls_match_non_streamed_history() {
+        let mut accumulator = StreamAccumulator::new();
+        accumulator.push(TOOL_CALL_RESPONSE_SSE.as_bytes()).unwrap();
+        assert!(accumulator.is_done());
+        let streamed = accumulator.finish().unwrap();
+
+        let response: ChatResponse = serde_json::from_str(TOOL_CALL_RESPONSE).unwrap();
+        assert_eq!(
+            serde_json::to_value(followup_history(&streamed)).unwrap(),
+            serde_json::to_value(followup_history(&response)).unwrap()
+        );
+    }
+
+    #[test]
+    fn far_out_tool_call_index_is_rejected() {
+        let chunk = json!({
+            "choices": [{
+                "index": 0,
+                "delta": {"tool_calls": [{"index": 4_000_000_000u64, "function": {"arguments": "{}"}}]}
+            }]
+        });
+        let mut accumulator = StreamAccumulator::new();
+        let error = accumulator
+            .push(format!("data: {}\n\n", chunk).as_bytes())
+            .unwrap_err();
+        assert!(error.to_string().contains("out of range"));
+    }
+
+    #[test]
+    fn final_answer_is_plain_assistant_text() {
+        let response: ChatResponse = serde_json::from_str(FINAL_RESPONSE).unwrap();
+        let message = response.choices[0].message.to_message();
+
+        assert!(message.tool_calls.is_none());
+        assert!(matches!(
+            message.content,
+            Some(MessageContent::Text(ref text)) if text == "Notepad is open."
+        ));
+        assert_eq!(
+            serde_json::to_value(&message).unwrap(),
+            json!({ "role": "assistant", "content": "Notepad is open." })
+        );
+    }
+
+    /// Config pointing at the mock server with fast retries
+    fn mock_config(server: &MockServer) -> ApiConfig {
+        ApiConfig {
+            endpoint: server.url.clone(),
+            api_key: "test-key".to_string(),
+            timeout_secs: 1,
+            retry: RetryConfig {
+                max_retries: 2,
+                initial_backoff_ms: 1,
+                max_backoff_ms: 10,
+                multiplier: 2.0,
+            },
+            ..ApiConfig::default()
+        }
+    }
+
+    fn ok_response() -> MockResponse {
+        MockResponse::json(200, serde_json::from_str(FINAL_RESPONSE).unwrap())
+    }
+
+    fn llm_error(error: anyhow::Error) -> LlmError {
+        error.downcast::<LlmError>().expect("expected LlmError")
+    }
+
+    #[tokio::test]
+    async fn rate_limit_is_retried_until_success() {
+        let server = MockServer::start(vec![
+            MockResponse::json(429, json!({ "error": { "message": "Slow down" } }))
+                .with_header("Retry-After", "0"),
+            ok_response(),
+        ])
+        .await;
+
+        let response = chat_completion(&mock_config(&server), vec![Message::user("hi")], None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.choices[0].message.content.as_deref(),
+            Some("Notepad is open.")
+        );
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].path, "/chat/completions");
+        assert_eq!(requests[0].headers["authorization"], "Bearer test-key");
+    }
+
+    #[tokio::test]
+    async fn retry_after_header_sets_the_delay() {
+        let server = MockServer::start(vec![
+            MockResponse::json(429, json!({ "error": { "message": "Slow down" } }))
+                .with_header("retry-after-ms", "300"),
+            ok_response(),
+        ])
+        .await;
+
+        let mut config = mock_config(&server);
+        config.retry.max_backoff_ms = 1000;
+
+        let started = Instant::now();
+        chat_completion(&config, vec![Message::user("hi")], None)
+            .await
+            .unwrap();
+
+        // The configured backoff is 1ms, so the wait came from the header
+        assert!(started.elapsed() >= Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn auth_error_is_not_retried() {
+        let server = MockServer::start(vec![MockResponse::json(
+            401,
+            json!({ "error": { "message": "Incorrect API key provided" } }),
+        )])
+        .await;
+
+        let error = chat_completion(&mock_config(&server), vec![Message::user("hi")], None)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(llm_error(error), LlmError::Auth(m) if m == "Incorrect API key provided"));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn context_length_error_is_classified() {
+        let server = MockServer::start(vec![MockResponse::json(
+            400,
+            json!({ "error": {
+                "message": "This model's maximum context length is 128000 tokens.",
+                "type": "invalid_request_error",
+                "code": "context_length_exceeded"
+            } }),
+        )])
+        .await;
+
+        let error = chat_completion(&mock_config(&server), vec![Message::user("hi")], None)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(llm_error(error), LlmError::ContextLengthExceeded(_)));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn server_errors_stop_after_max_retries() {
+        let unavailable = MockResponse::raw(503, "text/plain", "upstream unavailable");
+        let server = MockServer::start(vec![
+            unavailable.clone(),
+            unavailable.clone(),
+            unavailable,
+            ok_response(),
+        ])
+        .await;
+
+        let error = chat_completion(&mock_config(&server), vec![Message::user("hi")], None)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(llm_error(error), LlmError::Server { status: 503, .. }));
+        // First attempt plus max_retries
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn slow_response_times_out() {
+        let server =
+            MockServer::start(vec![ok_response().with_delay(Duration::from_secs(3))]).await;
+        let mut config = mock_config(&server);
+        config.retry.max_retries = 0;
+
+        let error = chat_completion(&config, vec![Message::user("hi")], None)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(llm_error(error), LlmError::Timeout));
+    }
+
+    #[tokio::test]
+    async fn silent_stream_server_times_out() {
+        let server = MockServer::start(vec![
+            MockResponse::sse(TOOL_CALL_RESPONSE_SSE).with_delay(Duration::from_secs(3))
+        ])
+        .await;
+        let mut config = mock_config(&server);
+        config.retry.max_retries = 0;
+
+        let started = Instant::now();
+        let error = chat_completion_stream(&config, vec![Message::user("hi")], None, |_| {})
+            .await
+            .unwrap_err();
+
+        assert!(matches!(llm_error(error), LlmError::Timeout));
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+
+    #[tokio::test]
+    async fn malformed_body_is_reported() {
+        let server =
+            MockServer::start(vec![MockResponse::raw(200, "application/json", "<html>")]).await;
+
+        let error = chat_completion(&mock_config(&server), vec![Message::user("hi")], None)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(llm_error(error), LlmError::MalformedResponse(_)));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn stream_is_retried_before_the_first_chunk() {
+        let server = MockServer::start(vec![
+            MockResponse::json(529, json!({ "error": { "message": "Overloaded" } })),
+            MockResponse::sse(TOOL_CALL_RESPONSE_SSE),
+        ])
+        .await;
+
+        let mut deltas = 0;
+        let response = chat_completion_stream(
+            &mock_config(&server),
+            vec![Message::user("Open notepad")],
+            Some(vec![click_tool()]),
+            |_| deltas += 1,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.choices[0].message.tool_calls.as_ref().unwrap().len(), 2);
+        assert!(deltas > 0);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        let body: Value = serde_json::from_str(&requests[1].body).unwrap();
+        assert_eq!(body["stream"], json!(true));
+        assert_eq!(body["tool_choice"], json!("auto"));
+    }
+
+    #[tokio::test]
+    async fn empty_api_key_sends_no_authorization() {
+        let server = MockServer::start(vec![ok_response()]).await;
+        let mut config = mock_config(&server);
+        config.api_key.clear();
+
+        chat_completion(&config, vec![Message::user("hi")], None)
+            .await
+            .unwrap();
+
+        assert!(!server.requests()[0].headers.contains_key("authorization"));
     }
 }
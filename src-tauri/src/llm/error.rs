@@ -0,0 +1,215 @@
+// Typed LLM API errors
+//
+// Classifies HTTP failures so the retry loop can tell transient errors
+// (rate limits, overloaded servers, timeouts) from permanent ones (bad key,
+// prompt too long).
+
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Error returned by LLM providers
+#[derive(Debug, Error)]
+pub enum LlmError {
+    /// 429 - too many requests or tokens per minute
+    #[error("Rate limited: {message}")]
+    RateLimited {
+        message: String,
+        retry_after: Option<Duration>,
+    },
+    /// 401 / 403 - missing or invalid API key
+    #[error("Authentication failed: {0}")]
+    Auth(String),
+    /// Prompt plus max_tokens does not fit the model's context window
+    #[error("Context length exceeded: {0}")]
+    ContextLengthExceeded(String),
+    /// 5xx (including Anthropic's 529 overloaded)
+    #[error("Server error ({status}): {message}")]
+    Server {
+        status: u16,
+        message: String,
+        retry_after: Option<Duration>,
+    },
+    /// Request or stream did not finish within the configured timeout
+    #[error("Request timed out")]
+    Timeout,
+    /// Connection failed before a response was received
+    #[error("Network error: {0}")]
+    Network(String),
+    /// Response body could not be parsed
+    #[error("Malformed response: {0}")]
+    MalformedResponse(String),
+    /// Task or daily spending limit reached (request not sent)
+    #[error("Budget exceeded: {0}")]
+    BudgetExceeded(String),
+    /// Any other non-success status (bad request, not found, ...)
+    #[error("API error ({status}): {message}")]
+    Api { status: u16, message: String },
+}
+
+impl LlmError {
+    /// Whether the request may succeed if sent again
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            LlmError::RateLimited { .. }
+                | LlmError::Server { .. }
+                | LlmError::Timeout
+                | LlmError::Network(_)
+        )
+    }
+
+    /// Delay requested by the server
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            LlmError::RateLimited { retry_after, .. } | LlmError::Server { retry_after, .. } => {
+                *retry_after
+            }
+            _ => None,
+        }
+    }
+
+    /// Classify a non-success HTTP response
+    pub fn from_response(status: StatusCode, headers: &HeaderMap, body: &str) -> Self {
+        let message = error_message(body);
+        let retry_after = parse_retry_after(headers);
+
+        match status.as_u16() {
+            400 | 413 if is_context_length_error(body) => LlmError::ContextLengthExceeded(message),
+            401 | 403 => LlmError::Auth(message),
+            429 => LlmError::RateLimited {
+                message,
+                retry_after,
+            },
+            408 => LlmError::Timeout,
+            code @ 500..=599 => LlmError::Server {
+                status: code,
+                message,
+                retry_after,
+            },
+            code => LlmError::Api {
+                status: code,
+                message,
+            },
+        }
+    }
+
+    /// Classify a transport error from reqwest
+    pub fn from_reqwest(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            LlmError::Timeout
+        } else if error.is_decode() {
+            LlmError::MalformedResponse(error.to_string())
+        } else {
+            LlmError::Network(error.to_string())
+        }
+    }
+}
+
+/// Extract the human-readable message from an error body
+/// (`{"error": {"message": ...}}` for both OpenAI and Anthropic)
+fn error_message(body: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|v| {
+            v.pointer("/error/message")
+                .or_else(|| v.get("message"))
+                .and_then(|m| m.as_str())
+                .map(String::from)
+        })
+        .unwrap_or_else(|| body.trim().to_string())
+}
+
+/// Detect context window errors across providers
+fn is_context_length_error(body: &str) -> bool {
+    let lower = body.to_lowercase();
+    lower.contains("context_length_exceeded")
+        || lower.contains("maximum context length")
+        || lower.contains("prompt is too long")
+        || lower.contains("context window")
+}
+
+/// Parse `retry-after-ms`, then `Retry-After` (seconds or HTTP date)
+fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+
+    if let Some(ms) = header("retry-after-ms").and_then(|v| v.trim().parse::<f64>().ok()) {
+        return Some(Duration::from_millis(ms.max(0.0) as u64));
+    }
+
+    let value = header("retry-after")?.trim();
+    if let Ok(secs) = value.parse::<f64>() {
+        return Some(Duration::from_millis((secs.max(0.0) * 1000.0) as u64));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = date.signed_duration_since(chrono::Utc::now());
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(name: &'static str, value: &str) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        map.insert(name, HeaderValue::from_str(value).unwrap());
+        map
+    }
+
+    #[test]
+    fn retry_after_accepts_seconds_and_milliseconds() {
+        assert_eq!(
+            parse_retry_after(&headers("retry-after", "7")),
+            Some(Duration::from_secs(7))
+        );
+        assert_eq!(
+            parse_retry_after(&headers("retry-after-ms", "1500")),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(parse_retry_after(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn retry_after_accepts_http_dates() {
+        let past = headers("retry-after", "Wed, 21 Oct 2015 07:28:00 GMT");
+        assert_eq!(parse_retry_after(&past), Some(Duration::ZERO));
+
+        let future = (chrono::Utc::now() + chrono::Duration::seconds(30)).to_rfc2822();
+        let wait = parse_retry_after(&headers("retry-after", &future)).unwrap();
+        assert!(wait > Duration::from_secs(25) && wait <= Duration::from_secs(30));
+    }
+
+    #[test]
+    fn anthropic_errors_are_classified() {
+        let overloaded = LlmError::from_response(
+            StatusCode::from_u16(529).unwrap(),
+            &HeaderMap::new(),
+            r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#,
+        );
+        assert!(overloaded.is_retryable());
+        assert_eq!(overloaded.to_string(), "Server error (529): Overloaded");
+
+        let too_long = LlmError::from_response(
+            StatusCode::BAD_REQUEST,
+            &HeaderMap::new(),
+            r#"{"type":"error","error":{"type":"invalid_request_error","message":"prompt is too long: 210000 tokens > 200000 maximum"}}"#,
+        );
+        assert!(matches!(too_long, LlmError::ContextLengthExceeded(_)));
+        assert!(!too_long.is_retryable());
+    }
+
+    #[test]
+    fn status_is_checked_before_context_length_text() {
+        let body = r#"{"error":{"message":"Rate limit reached; requests near the maximum context length count double"}}"#;
+        let rate_limited =
+            LlmError::from_response(StatusCode::TOO_MANY_REQUESTS, &HeaderMap::new(), body);
+        assert!(matches!(rate_limited, LlmError::RateLimited { .. }));
+        assert!(rate_limited.is_retryable());
+
+        let server = LlmError::from_response(StatusCode::BAD_GATEWAY, &HeaderMap::new(), body);
+        assert!(matches!(server, LlmError::Server { status: 502, .. }));
+    }
+}
@@ -0,0 +1,204 @@
+// Mock HTTP server for provider tests
+//
+// Serves a scripted list of responses (one per connection, in order) on a
+// local port and records every request it receives.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Scripted response
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+    delay: Duration,
+}
+
+impl MockResponse {
+    pub fn json(status: u16, body: serde_json::Value) -> Self {
+        Self::raw(status, "application/json", &body.to_string())
+    }
+
+    pub fn sse(body: &str) -> Self {
+        Self::raw(200, "text/event-stream", body)
+    }
+
+    pub fn raw(status: u16, content_type: &str, body: &str) -> Self {
+        Self {
+            status,
+            headers: vec![("Content-Type".to_string(), content_type.to_string())],
+            body: body.to_string(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Chat completion calling the given tools (IDs `call_0`, `call_1`, ...)
+    pub fn tool_calls(calls: &[(&str, serde_json::Value)]) -> Self {
+        let tool_calls: Vec<_> = calls
+            .iter()
+            .enumerate()
+            .map(|(index, (name, arguments))| {
+                serde_json::json!({
+                    "id": format!("call_{}", index),
+                    "type": "function",
+                    "function": { "name": name, "arguments": arguments.to_string() }
+                })
+            })
+            .collect();
+        Self::json(
+            200,
+            serde_json::json!({
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": null, "tool_calls": tool_calls },
+                    "finish_reason": "tool_calls"
+                }]
+            }),
+        )
+    }
+
+    /// Chat completion answering with text
+    pub fn answer(text: &str) -> Self {
+        Self::json(
+            200,
+            serde_json::json!({
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": text },
+                    "finish_reason": "stop"
+                }]
+            }),
+        )
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Wait before answering (to trigger client timeouts)
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+/// Request received by the server
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub path: String,
+    /// Header names are lowercased
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+/// Running mock server
+pub struct MockServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl MockServer {
+    /// Start serving `responses`; requests beyond the script get a 500
+    pub async fn start(responses: Vec<MockResponse>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            let mut script = responses.into_iter();
+            while let Ok((stream, _)) = listener.accept().await {
+                let response = script.next().unwrap_or_else(|| {
+                    MockResponse::raw(500, "text/plain", "no scripted response")
+                });
+                let recorded = recorded.clone();
+                tokio::spawn(async move {
+                    handle_connection(stream, response, recorded).await;
+                });
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    /// Requests received so far
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// Read one request and write the scripted response
+async fn handle_connection(
+    mut stream: TcpStream,
+    response: MockResponse,
+    recorded: Arc<Mutex<Vec<RecordedRequest>>>,
+) {
+    let Some(request) = read_request(&mut stream).await else {
+        return;
+    };
+    recorded.lock().unwrap().push(request);
+
+    tokio::time::sleep(response.delay).await;
+
+    let mut head = format!("HTTP/1.1 {} Mock\r\n", response.status);
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str(&format!(
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        response.body.len()
+    ));
+
+    let _ = stream.write_all(head.as_bytes()).await;
+    let _ = stream.write_all(response.body.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// Parse request line, headers and a Content-Length body
+async fn read_request(stream: &mut TcpStream) -> Option<RecordedRequest> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    let header_end = loop {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let mut lines = head.lines();
+    let path = lines.next()?.split_whitespace().nth(1)?.to_string();
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim().to_lowercase(), v.trim().to_string()))
+        .collect();
+
+    let length: usize = headers
+        .get("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    while buffer.len() < header_end + length {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+    }
+
+    let body = String::from_utf8_lossy(&buffer[header_end..]).to_string();
+    Some(RecordedRequest {
+        path,
+        headers,
+        body,
+    })
+}
@@ -3,6 +3,10 @@
 // Structure:
 // - types: Common types (Message, Tool, Response)
 // - client: API communication
+// - error: Typed API errors
+// - retry: Exponential backoff for transient errors
+// - provider: LLM backends (OpenAI-compatible, Anthropic) behind LlmProvider
+// - stream: SSE chunk assembly for streamed responses
 // - prompts: System prompts and builders
 // - tools: Primitive tools (mouse, keyboard, screen)
 // - agents: Sub-agent tools (guide_search)
@@ -10,10 +14,17 @@
 
 pub mod types;
 pub mod client;
+pub mod error;
+pub mod retry;
+pub mod provider;
+pub mod stream;
 pub mod prompts;
 pub mod tools;
 pub mod agents;
 pub mod runner;
 
+#[cfg(test)]
+pub mod mock_server;
+
 // Re-export commonly used items
 pub use runner::process_message;
@@ -1,25 +1,21 @@
 // Dynamic prompt builder
 
 use crate::guides::storage::get_guide_index;
+use crate::llm::tools::ToolSet;
 use super::system::MAIN_AGENT_PROMPT;
 
 /// Build the main agent system prompt with dynamic content
-pub fn build_main_agent_prompt() -> String {
+pub fn build_main_agent_prompt(tools: &ToolSet) -> String {
     let mut prompt = MAIN_AGENT_PROMPT.to_string();
 
     // Add available tools description
     prompt.push_str("\n\nAvailable tools:\n");
-    prompt.push_str("- mouse_move(x, y): Move mouse to coordinates\n");
-    prompt.push_str("- mouse_click(x, y, button): Click at coordinates (button: \"left\", \"right\", \"middle\")\n");
-    prompt.push_str("- mouse_double_click(x, y): Double click at coordinates\n");
-    prompt.push_str("- keyboard_type(text): Type text\n");
-    prompt.push_str("- keyboard_press(keys): Press key combination (e.g., [\"ctrl\", \"c\"])\n");
-    prompt.push_str("- scroll(direction, amount): Scroll (direction: \"up\", \"down\", \"left\", \"right\")\n");
-    prompt.push_str("- wait(ms): Wait for milliseconds\n");
-    prompt.push_str("- get_screen_update(): Request updated screen information\n");
-    prompt.push_str("- guide_search(query): Search for relevant guides to help with the current task\n");
+    prompt.push_str(&tools.prompt_list());
 
-    // Add guide index if available
+    // Add guide index if guide search is available
+    if !tools.contains("guide_search") {
+        return prompt;
+    }
     if let Ok(index) = get_guide_index() {
         if !index.is_empty() {
             prompt.push_str("\n## Available Guides\n");
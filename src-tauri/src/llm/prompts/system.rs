@@ -41,3 +41,44 @@ Response Rules:
 - If no relevant guide exists, respond with exactly: 없음
 - Do not add explanations or commentary to your response
 - Be efficient - don't read files that are clearly unrelated based on their names"#;
+
+/// System prompt for compacting old agent steps into a summary
+pub const HISTORY_SUMMARY_PROMPT: &str = r#"You summarize the earlier part of a desktop automation session so the agent can continue without the full history.
+
+Write a concise summary that keeps:
+- Which actions were taken (tool names and important parameters such as coordinates, typed text, keys)
+- What the results were, including errors and failed attempts
+- What is currently known about the screen (open windows, positions of important UI elements)
+- Which parts of the user's request are done and which remain
+
+Leave out reasoning that no longer matters. Respond with the summary only."#;
+
+/// System prompt for the planner sub-agent
+pub const PLANNER_AGENT_PROMPT: &str = r#"You are a planning agent for desktop automation on Windows. Break the user's request into a short, numbered plan that another agent will carry out with the mouse and keyboard.
+
+You have access to the following tools:
+- guide_ls(path): List guides in a directory. Use "" or omit path to list root folders.
+- guide_preview(file_path): Read first 10 lines of a guide file.
+- guide_read(file_path): Read the full content of a guide file.
+
+Planning Rules:
+- Look at the current screen state first; skip steps that are already done on screen
+- If a guide matches the task, follow its procedure
+- Each step is one observable outcome (e.g., "Open Notepad from the Start menu"), not a single click
+- Keep the plan between 2 and 10 steps
+- When replanning, plan only the remaining work and avoid the approach that failed
+
+Respond with the numbered list only, one step per line:
+1. First step
+2. Second step"#;
+
+/// Instructions added to the main agent prompt in plan mode
+pub const PLAN_MODE_PROMPT: &str = r#"
+
+## Plan
+You are working through a numbered plan (shown with the user's request).
+- Work on the steps in order
+- After finishing a step, call update_plan with status "done"
+- If a step cannot be done, call update_plan with status "failed" and a note, then either reword it (status "revised" with a new description) or call replan
+- Call replan when the remaining steps no longer fit what you see on screen
+- Answer the user without a tool call only when every step is done or the task cannot continue"#;
@@ -0,0 +1,466 @@
+// Anthropic provider - native Messages API
+//
+// Translates the OpenAI-shaped Message/ToolDef types into Messages API
+// content blocks (text, image, tool_use, tool_result) and maps the
+// response back into a ChatResponse.
+
+use super::LlmProvider;
+use crate::commands::config::ApiConfig;
+use crate::llm::client::{
+    http_client, read_json, send_checked, ChatResponse, Choice, ResponseMessage, Usage,
+};
+use crate::llm::retry::with_retry;
+use crate::llm::types::{ContentPart, FunctionCall, Message, MessageContent, ToolCall, ToolDef};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Messages API version header value
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Provider for the Anthropic Messages API
+pub struct AnthropicProvider {
+    config: ApiConfig,
+}
+
+impl AnthropicProvider {
+    pub fn new(config: ApiConfig) -> Self {
+        Self { config }
+    }
+}
+
+/// Messages API request
+#[derive(Debug, Serialize)]
+struct MessagesRequest {
+    model: String,
+    max_tokens: u32,
+    temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<String>,
+    messages: Vec<AnthropicMessage>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<AnthropicTool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<Value>,
+}
+
+/// Messages API message (role is "user" or "assistant")
+#[derive(Debug, Serialize)]
+struct AnthropicMessage {
+    role: String,
+    content: Vec<ContentBlock>,
+}
+
+/// Content block in requests and responses
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ContentBlock {
+    Text {
+        text: String,
+    },
+    Image {
+        source: ImageSource,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        content: String,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        is_error: bool,
+    },
+    /// Blocks we don't use (thinking, etc.) are skipped when mapping back
+    #[serde(other)]
+    Unknown,
+}
+
+/// Image source for image blocks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ImageSource {
+    Base64 { media_type: String, data: String },
+    Url { url: String },
+}
+
+/// Tool definition
+#[derive(Debug, Serialize)]
+struct AnthropicTool {
+    name: String,
+    description: String,
+    input_schema: Value,
+}
+
+/// Messages API response
+#[derive(Debug, Deserialize)]
+struct MessagesResponse {
+    #[serde(default)]
+    content: Vec<ContentBlock>,
+    #[serde(default)]
+    stop_reason: Option<String>,
+    #[serde(default)]
+    usage: Option<AnthropicUsage>,
+}
+
+/// Token usage
+#[derive(Debug, Deserialize)]
+struct AnthropicUsage {
+    input_tokens: u32,
+    output_tokens: u32,
+}
+
+#[async_trait]
+impl LlmProvider for AnthropicProvider {
+    async fn chat(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<ToolDef>>,
+    ) -> Result<ChatResponse> {
+        let request = build_request(&self.config, messages, tools);
+        let client = http_client(&self.config)?;
+
+        let body = with_retry(&self.config.retry, || async {
+            let response = send_checked(
+                client
+                    .post(format!(
+                        "{}/messages",
+                        self.config.endpoint.trim_end_matches('/')
+                    ))
+                    .timeout(Duration::from_secs(self.config.timeout_secs))
+                    .header("x-api-key", &self.config.api_key)
+                    .header("anthropic-version", ANTHROPIC_VERSION)
+                    .header("Content-Type", "application/json")
+                    .json(&request),
+            )
+            .await?;
+            read_json::<MessagesResponse>(response).await
+        })
+        .await?;
+
+        Ok(map_response(body))
+    }
+}
+
+/// Translate chat messages and tools into a Messages API request
+fn build_request(
+    config: &ApiConfig,
+    messages: Vec<Message>,
+    tools: Option<Vec<ToolDef>>,
+) -> MessagesRequest {
+    let mut system_parts: Vec<String> = Vec::new();
+    let mut converted: Vec<AnthropicMessage> = Vec::new();
+
+    for message in messages {
+        let (role, blocks) = match message.role.as_str() {
+            "system" => {
+                system_parts.push(message.text());
+                continue;
+            }
+            "assistant" => {
+                let mut blocks = content_to_blocks(message.content.as_ref());
+                for call in message.tool_calls.unwrap_or_default() {
+                    let input = serde_json::from_str(&call.function.arguments)
+                        .unwrap_or_else(|_| json!({}));
+                    blocks.push(ContentBlock::ToolUse {
+                        id: call.id,
+                        name: call.function.name,
+                        input,
+                    });
+                }
+                ("assistant", blocks)
+            }
+            "tool" => (
+                "user",
+                vec![ContentBlock::ToolResult {
+                    tool_use_id: message.tool_call_id.clone().unwrap_or_default(),
+                    content: message.text(),
+                    is_error: message.is_error,
+                }],
+            ),
+            _ => ("user", content_to_blocks(message.content.as_ref())),
+        };
+
+        if blocks.is_empty() {
+            continue;
+        }
+
+        // The Messages API requires alternating roles, so consecutive turns
+        // from the same side (e.g. tool results followed by a screen update)
+        // are merged into one message
+        match converted.last_mut() {
+            Some(last) if last.role == role => last.content.extend(blocks),
+            _ => converted.push(AnthropicMessage {
+                role: role.to_string(),
+                content: blocks,
+            }),
+        }
+    }
+
+    let tools: Vec<AnthropicTool> = tools
+        .unwrap_or_default()
+        .into_iter()
+        .map(|t| AnthropicTool {
+            name: t.function.name,
+            description: t.function.description,
+            input_schema: t.function.parameters,
+        })
+        .collect();
+    let tool_choice = if tools.is_empty() {
+        None
+    } else {
+        Some(json!({ "type": "auto" }))
+    };
+
+    MessagesRequest {
+        model: config.model.clone(),
+        max_tokens: config.max_tokens,
+        temperature: config.temperature,
+        system: if system_parts.is_empty() {
+            None
+        } else {
+            Some(system_parts.join("\n\n"))
+        },
+        messages: converted,
+        tools,
+        tool_choice,
+    }
+}
+
+/// Convert message content into text and image blocks
+fn content_to_blocks(content: Option<&MessageContent>) -> Vec<ContentBlock> {
+    match content {
+        None => Vec::new(),
+        Some(MessageContent::Text(text)) if text.is_empty() => Vec::new(),
+        Some(MessageContent::Text(text)) => vec![ContentBlock::Text { text: text.clone() }],
+        Some(MessageContent::Parts(parts)) => parts
+            .iter()
+            .map(|p| match p {
+                ContentPart::Text { text } => ContentBlock::Text { text: text.clone() },
+                ContentPart::ImageUrl { image_url } => ContentBlock::Image {
+                    source: image_source(&image_url.url),
+                },
+            })
+            .collect(),
+    }
+}
+
+/// Convert an image URL (data URL or http) into an image source
+fn image_source(url: &str) -> ImageSource {
+    // data:image/png;base64,XXXX
+    if let Some(rest) = url.strip_prefix("data:") {
+        if let Some((meta, data)) = rest.split_once(',') {
+            let media_type = meta.trim_end_matches(";base64").to_string();
+            return ImageSource::Base64 {
+                media_type,
+                data: data.to_string(),
+            };
+        }
+    }
+
+    ImageSource::Url {
+        url: url.to_string(),
+    }
+}
+
+/// Map a Messages API response back into a ChatResponse
+fn map_response(body: MessagesResponse) -> ChatResponse {
+    let mut text_parts: Vec<String> = Vec::new();
+    let mut tool_calls: Vec<ToolCall> = Vec::new();
+
+    for block in body.content {
+        match block {
+            ContentBlock::Text { text } => text_parts.push(text),
+            ContentBlock::ToolUse { id, name, input } => tool_calls.push(ToolCall {
+                id,
+                call_type: "function".to_string(),
+                function: FunctionCall {
+                    name,
+                    arguments: input.to_string(),
+                },
+            }),
+            _ => {}
+        }
+    }
+
+    let finish_reason = body.stop_reason.map(|r| {
+        match r.as_str() {
+            "end_turn" | "stop_sequence" => "stop",
+            "tool_use" => "tool_calls",
+            "max_tokens" => "length",
+            other => other,
+        }
+        .to_string()
+    });
+
+    ChatResponse {
+        choices: vec![Choice {
+            message: ResponseMessage {
+                role: "assistant".to_string(),
+                content: if text_parts.is_empty() {
+                    None
+                } else {
+                    Some(text_parts.join("\n"))
+                },
+                tool_calls: if tool_calls.is_empty() {
+                    None
+                } else {
+                    Some(tool_calls)
+                },
+            },
+            finish_reason,
+        }],
+        usage: body.usage.map(|u| Usage {
+            prompt_tokens: u.input_tokens,
+            completion_tokens: u.output_tokens,
+            total_tokens: u.input_tokens + u.output_tokens,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::types::{FunctionDef, ImageUrl};
+
+    const TOOL_USE_RESPONSE: &str =
+        include_str!("../../../tests/fixtures/anthropic_tool_use_response.json");
+    const FOLLOWUP_REQUEST: &str =
+        include_str!("../../../tests/fixtures/anthropic_tool_use_followup_request.json");
+
+    fn config() -> ApiConfig {
+        ApiConfig {
+            model: "claude-sonnet-4-5".to_string(),
+            max_tokens: 4096,
+            temperature: 0.5,
+            ..ApiConfig::default()
+        }
+    }
+
+    fn click_tool() -> ToolDef {
+        ToolDef {
+            tool_type: "function".to_string(),
+            function: FunctionDef {
+                name: "mouse_click".to_string(),
+                description: "Click at a position".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "x": { "type": "integer" },
+                        "y": { "type": "integer" }
+                    },
+                    "required": ["x", "y"]
+                }),
+            },
+        }
+    }
+
+    fn response(body: Value) -> ChatResponse {
+        map_response(serde_json::from_value(body).unwrap())
+    }
+
+    #[test]
+    fn tool_use_response_maps_to_tool_calls() {
+        let response = map_response(serde_json::from_str(TOOL_USE_RESPONSE).unwrap());
+        let choice = &response.choices[0];
+
+        // The thinking block is skipped
+        assert_eq!(
+            choice.message.content.as_deref(),
+            Some("I'll open the Run dialog and type notepad.")
+        );
+        let calls = choice.message.tool_calls.as_ref().unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].id, "toolu_01A09q90qw90lq917835lq9");
+        assert_eq!(calls[0].function.name, "mouse_click");
+        assert_eq!(
+            serde_json::from_str::<Value>(&calls[0].function.arguments).unwrap(),
+            json!({ "x": 640, "y": 360, "button": "left" })
+        );
+        assert_eq!(choice.finish_reason.as_deref(), Some("tool_calls"));
+
+        let usage = response.usage.unwrap();
+        assert_eq!(
+            (
+                usage.prompt_tokens,
+                usage.completion_tokens,
+                usage.total_tokens
+            ),
+            (1523, 87, 1610)
+        );
+    }
+
+    #[test]
+    fn tool_history_matches_recorded_request() {
+        let response = map_response(serde_json::from_str(TOOL_USE_RESPONSE).unwrap());
+        let message = &response.choices[0].message;
+        let calls = message.tool_calls.as_ref().unwrap();
+        let messages = vec![
+            Message::system("You are an automation agent."),
+            Message::user("Open notepad"),
+            Message::system("Answer in English."),
+            message.to_message(),
+            Message::tool(&calls[0], "Clicked at (640, 360)"),
+            Message::tool(&calls[1], "Error: No window has keyboard focus").with_error(true),
+            // A screen update right after the results joins the same user turn
+            Message::user_parts(vec![
+                ContentPart::Text {
+                    text: "Current screen:".to_string(),
+                },
+                ContentPart::ImageUrl {
+                    image_url: ImageUrl {
+                        url: "data:image/png;base64,iVBORw0KGgo=".to_string(),
+                        detail: None,
+                    },
+                },
+            ]),
+        ];
+
+        let request = build_request(&config(), messages, Some(vec![click_tool()]));
+
+        let expected: Value = serde_json::from_str(FOLLOWUP_REQUEST).unwrap();
+        assert_eq!(serde_json::to_value(&request).unwrap(), expected);
+    }
+
+    #[test]
+    fn image_urls_become_image_sources() {
+        assert!(matches!(
+            image_source("data:image/jpeg;base64,/9j/4AAQ"),
+            ImageSource::Base64 { ref media_type, ref data }
+                if media_type == "image/jpeg" && data == "/9j/4AAQ"
+        ));
+        assert!(matches!(
+            image_source("https://example.com/screen.png"),
+            ImageSource::Url { ref url } if url == "https://example.com/screen.png"
+        ));
+    }
+
+    #[test]
+    fn stop_reasons_map_to_finish_reasons() {
+        for (stop_reason, finish_reason) in [
+            ("end_turn", "stop"),
+            ("stop_sequence", "stop"),
+            ("tool_use", "tool_calls"),
+            ("max_tokens", "length"),
+            ("refusal", "refusal"),
+        ] {
+            let response = response(json!({
+                "content": [{ "type": "text", "text": "Done." }],
+                "stop_reason": stop_reason
+            }));
+            assert_eq!(
+                response.choices[0].finish_reason.as_deref(),
+                Some(finish_reason)
+            );
+        }
+
+        // No usage reported, no tool calls
+        let response = response(json!({ "content": [{ "type": "text", "text": "Done." }] }));
+        assert!(response.usage.is_none());
+        assert!(response.choices[0].message.tool_calls.is_none());
+        assert!(response.choices[0].finish_reason.is_none());
+    }
+}
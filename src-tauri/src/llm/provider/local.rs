@@ -0,0 +1,50 @@
+// Local provider - Ollama / llama.cpp servers
+//
+// Both expose an OpenAI-compatible /v1/chat/completions endpoint, so requests
+// go through the regular client. Local servers usually run without an API key
+// and many local models handle native function calling poorly, so this
+// provider is normally paired with the text tool-calling protocol.
+
+use super::{DeltaCallback, LlmProvider};
+use crate::commands::config::ApiConfig;
+use crate::llm::client::{chat_completion, chat_completion_stream, ChatResponse};
+use crate::llm::types::{Message, ToolDef};
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// Default endpoint of a local Ollama server
+pub const OLLAMA_DEFAULT_ENDPOINT: &str = "http://localhost:11434/v1";
+
+/// Provider for local model servers
+pub struct LocalProvider {
+    config: ApiConfig,
+}
+
+impl LocalProvider {
+    pub fn new(mut config: ApiConfig) -> Self {
+        if config.endpoint.trim().is_empty() {
+            config.endpoint = OLLAMA_DEFAULT_ENDPOINT.to_string();
+        }
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for LocalProvider {
+    async fn chat(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<ToolDef>>,
+    ) -> Result<ChatResponse> {
+        chat_completion(&self.config, messages, tools).await
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<ToolDef>>,
+        on_delta: DeltaCallback<'_>,
+    ) -> Result<ChatResponse> {
+        chat_completion_stream(&self.config, messages, tools, on_delta).await
+    }
+}
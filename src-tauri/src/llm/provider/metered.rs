@@ -0,0 +1,132 @@
+// Metered provider - records usage of every call and enforces budgets
+//
+// Wraps the configured backend so the main agent and sub-agents (which share
+// the provider through ToolContext) are accounted in one place.
+
+use super::{DeltaCallback, LlmProvider};
+use crate::llm::client::ChatResponse;
+use crate::llm::error::LlmError;
+use crate::llm::types::{Message, ToolDef};
+use crate::usage::UsageMeter;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Provider decorator feeding a UsageMeter
+pub struct MeteredProvider {
+    inner: Arc<dyn LlmProvider>,
+    meter: Arc<UsageMeter>,
+}
+
+impl MeteredProvider {
+    pub fn new(inner: Arc<dyn LlmProvider>, meter: Arc<UsageMeter>) -> Self {
+        Self { inner, meter }
+    }
+
+    /// Refuse to send anything once a budget is reached
+    fn check_budget(&self) -> Result<()> {
+        match self.meter.budget_exceeded() {
+            Some(reason) => Err(LlmError::BudgetExceeded(reason).into()),
+            None => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for MeteredProvider {
+    async fn chat(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<ToolDef>>,
+    ) -> Result<ChatResponse> {
+        self.check_budget()?;
+        let response = self.inner.chat(messages, tools).await?;
+        self.meter.record(response.usage.as_ref());
+        Ok(response)
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<ToolDef>>,
+        on_delta: DeltaCallback<'_>,
+    ) -> Result<ChatResponse> {
+        self.check_budget()?;
+        let response = self.inner.chat_stream(messages, tools, on_delta).await?;
+        self.meter.record(response.usage.as_ref());
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::config::{AppConfig, ModelPrice};
+    use crate::llm::client::{Choice, ResponseMessage, Usage};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Provider answering every request with 1000 prompt and 1000 completion tokens
+    #[derive(Default)]
+    struct Counting(AtomicUsize);
+
+    #[async_trait]
+    impl LlmProvider for Counting {
+        async fn chat(
+            &self,
+            _messages: Vec<Message>,
+            _tools: Option<Vec<ToolDef>>,
+        ) -> Result<ChatResponse> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(ChatResponse {
+                choices: vec![Choice {
+                    message: ResponseMessage {
+                        role: "assistant".to_string(),
+                        content: Some("Done.".to_string()),
+                        tool_calls: None,
+                    },
+                    finish_reason: None,
+                }],
+                usage: Some(Usage {
+                    prompt_tokens: 1000,
+                    completion_tokens: 1000,
+                    total_tokens: 2000,
+                }),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn requests_are_refused_once_the_budget_is_reached() {
+        let mut config = AppConfig::default();
+        config.api.model = "test-model".to_string();
+        config.usage.prices = vec![ModelPrice {
+            model: "test-model".to_string(),
+            input_per_mtok: 3.0,
+            output_per_mtok: 15.0,
+        }];
+        // Two requests at $0.018 each
+        config.usage.task_budget_usd = Some(0.03);
+        let inner = Arc::new(Counting::default());
+        let meter = Arc::new(UsageMeter::detached(&config));
+        let provider = MeteredProvider::new(inner.clone(), meter.clone());
+
+        for _ in 0..2 {
+            provider
+                .chat(vec![Message::user("hi")], None)
+                .await
+                .unwrap();
+        }
+        let error = provider
+            .chat(vec![Message::user("hi")], None)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<LlmError>(),
+            Some(LlmError::BudgetExceeded(_))
+        ));
+        // The refused request never reached the backend
+        assert_eq!(inner.0.load(Ordering::SeqCst), 2);
+        assert_eq!(meter.snapshot().requests, 2);
+    }
+}
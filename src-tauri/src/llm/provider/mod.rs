@@ -0,0 +1,97 @@
+// Provider module - LLM backends behind a common trait
+//
+// The agent loops only talk to `LlmProvider`; which backend is used is
+// selected by `ApiConfig.provider`.
+
+mod anthropic;
+mod local;
+mod metered;
+mod openai;
+
+pub use anthropic::*;
+pub use local::*;
+pub use metered::*;
+pub use openai::*;
+
+use crate::commands::config::{ApiConfig, AppConfig, ProviderKind};
+use crate::llm::client::ChatResponse;
+use crate::llm::stream::StreamDelta;
+use crate::llm::types::{Message, ToolDef};
+use crate::usage::UsageMeter;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Callback receiving streamed deltas
+pub type DeltaCallback<'a> = &'a mut (dyn FnMut(StreamDelta) + Send);
+
+/// Trait for all LLM backends
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    /// Send a chat request and wait for the full response
+    async fn chat(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<ToolDef>>,
+    ) -> Result<ChatResponse>;
+
+    /// Send a chat request, reporting deltas as they arrive
+    /// Backends without streaming report the whole text as a single delta
+    async fn chat_stream(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<ToolDef>>,
+        on_delta: DeltaCallback<'_>,
+    ) -> Result<ChatResponse> {
+        let response = self.chat(messages, tools).await?;
+
+        if let Some(text) = response
+            .choices
+            .first()
+            .and_then(|c| c.message.content.clone())
+        {
+            on_delta(StreamDelta::Content { text });
+        }
+
+        Ok(response)
+    }
+}
+
+/// Create the provider selected in config
+pub fn create_provider(config: &ApiConfig) -> Arc<dyn LlmProvider> {
+    match config.provider {
+        ProviderKind::OpenAi => Arc::new(OpenAiProvider::new(config.clone())),
+        ProviderKind::Anthropic => Arc::new(AnthropicProvider::new(config.clone())),
+        ProviderKind::Local => Arc::new(LocalProvider::new(config.clone())),
+    }
+}
+
+/// Create the configured provider with usage metering and budget checks
+/// The meter is returned so callers can read the task's totals
+pub fn create_metered_provider(config: &AppConfig) -> (Arc<dyn LlmProvider>, Arc<UsageMeter>) {
+    let meter = Arc::new(UsageMeter::new(config));
+    let provider = MeteredProvider::new(create_provider(&config.api), meter.clone());
+    (Arc::new(provider), meter)
+}
+
+/// Test API connection with a minimal request
+pub async fn test_connection(config: &ApiConfig) -> Result<String> {
+    let mut config = config.clone();
+    config.max_tokens = 10;
+    config.temperature = 0.0;
+
+    let provider = create_provider(&config);
+    let response = provider
+        .chat(
+            vec![Message::user("Say 'Hello' in one word.")],
+            None,
+        )
+        .await?;
+
+    let reply = response
+        .choices
+        .first()
+        .and_then(|c| c.message.content.clone())
+        .unwrap_or_else(|| "Connected".to_string());
+    Ok(reply)
+}
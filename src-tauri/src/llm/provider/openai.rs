@@ -0,0 +1,39 @@
+// OpenAI-compatible provider - /chat/completions with Bearer auth
+
+use super::{DeltaCallback, LlmProvider};
+use crate::commands::config::ApiConfig;
+use crate::llm::client::{chat_completion, chat_completion_stream, ChatResponse};
+use crate::llm::types::{Message, ToolDef};
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// Provider for OpenAI and compatible servers
+pub struct OpenAiProvider {
+    config: ApiConfig,
+}
+
+impl OpenAiProvider {
+    pub fn new(config: ApiConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiProvider {
+    async fn chat(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<ToolDef>>,
+    ) -> Result<ChatResponse> {
+        chat_completion(&self.config, messages, tools).await
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<ToolDef>>,
+        on_delta: DeltaCallback<'_>,
+    ) -> Result<ChatResponse> {
+        chat_completion_stream(&self.config, messages, tools, on_delta).await
+    }
+}
@@ -0,0 +1,102 @@
+// Retry with exponential backoff for transient API errors
+
+use crate::commands::config::RetryConfig;
+use crate::llm::error::LlmError;
+use std::future::Future;
+use std::time::Duration;
+
+/// Delay before retry number `attempt` (0-based)
+/// A server-provided Retry-After wins over the computed backoff; None when
+/// it asks for a longer wait than `max_backoff_ms` (not worth retrying)
+pub fn backoff_delay(policy: &RetryConfig, attempt: u32, error: &LlmError) -> Option<Duration> {
+    let max = Duration::from_millis(policy.max_backoff_ms);
+
+    if let Some(retry_after) = error.retry_after() {
+        return (retry_after <= max).then_some(retry_after);
+    }
+
+    let delay = policy.initial_backoff_ms as f64 * policy.multiplier.max(1.0).powi(attempt as i32);
+    Some(Duration::from_millis(delay as u64).min(max))
+}
+
+/// Run `op` until it succeeds, fails permanently, or retries run out
+pub async fn with_retry<T, F, Fut>(policy: &RetryConfig, mut op: F) -> Result<T, LlmError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, LlmError>>,
+{
+    let mut attempt = 0;
+    loop {
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(e) if e.is_retryable() && attempt < policy.max_retries => {
+                // Give up with the original error rather than wait too long
+                let Some(delay) = backoff_delay(policy, attempt, &e) else {
+                    return Err(e);
+                };
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RetryConfig {
+        RetryConfig {
+            max_retries: 3,
+            initial_backoff_ms: 100,
+            max_backoff_ms: 1000,
+            multiplier: 4.0,
+        }
+    }
+
+    fn rate_limited(retry_after: Option<Duration>) -> LlmError {
+        LlmError::RateLimited {
+            message: "Slow down".to_string(),
+            retry_after,
+        }
+    }
+
+    #[test]
+    fn backoff_grows_up_to_the_maximum() {
+        let delays: Vec<_> = (0..3)
+            .map(|attempt| backoff_delay(&policy(), attempt, &LlmError::Timeout))
+            .collect();
+
+        assert_eq!(
+            delays,
+            [100, 400, 1000].map(|ms| Some(Duration::from_millis(ms)))
+        );
+    }
+
+    #[test]
+    fn retry_after_is_honoured_within_the_maximum() {
+        let error = rate_limited(Some(Duration::from_millis(700)));
+        assert_eq!(
+            backoff_delay(&policy(), 0, &error),
+            Some(Duration::from_millis(700))
+        );
+
+        // Longer than max_backoff_ms: no retry at all
+        let error = rate_limited(Some(Duration::from_secs(3600)));
+        assert_eq!(backoff_delay(&policy(), 0, &error), None);
+    }
+
+    #[tokio::test]
+    async fn long_retry_after_fails_with_the_rate_limit() {
+        let mut calls = 0;
+        let result: Result<(), LlmError> = with_retry(&policy(), || {
+            calls += 1;
+            async { Err(rate_limited(Some(Duration::from_secs(3600)))) }
+        })
+        .await;
+
+        assert!(matches!(result, Err(LlmError::RateLimited { .. })));
+        assert_eq!(calls, 1);
+    }
+}
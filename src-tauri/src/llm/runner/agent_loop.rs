@@ -1,23 +1,36 @@
 // Agent loop - main agent processing loop with tool execution
 
-use crate::config::storage::load_config;
-use crate::llm::agents::get_agent_tool_defs;
-use crate::llm::client::chat_completion;
-use crate::llm::prompts::build_main_agent_prompt;
-use crate::llm::runner::ToolExecutor;
-use crate::llm::tools::{
-    KeyboardPressTool, KeyboardTypeTool, MouseClickTool, MouseDoubleClickTool, MouseMoveTool,
-    ScreenUpdateTool, ScrollTool, Tool, WaitTool,
+use crate::commands::config::{RunBudgetOverride, ToolCallMode};
+use crate::llm::agents::{create_plan, replan};
+use crate::llm::client::ChatResponse;
+use crate::llm::error::LlmError;
+use crate::llm::provider::LlmProvider;
+use crate::llm::prompts::{build_main_agent_prompt, PLAN_MODE_PROMPT};
+use crate::llm::runner::text_protocol::{
+    invalid_call_message, parse_response, tool_protocol_prompt, tool_result_message,
 };
+use crate::llm::runner::approval::{ApprovalPolicy, ApprovalRecord};
+use crate::llm::runner::control::{self, RunControl};
+use crate::llm::runner::environment::{Desktop, RunEnvironment};
+use crate::llm::runner::events::{new_run_id, AgentEvent, EventSink};
+use crate::llm::runner::history::HistoryManager;
+use crate::llm::runner::plan::{Plan, SharedPlan};
+use crate::llm::runner::stall::{corrective_note, Intervention, StallDetector};
+use crate::llm::runner::verify::execute_verified;
+use crate::llm::runner::ToolExecutor;
+use crate::llm::stream::StreamEvent;
+use crate::llm::tools::{is_input_tool, parse_arguments, TOOL_REGISTRY};
 use crate::llm::types::{
-    ActionResponse, ContentPart, ImageUrl, Message, MessageContent, ToolContext, ToolDef,
+    ActionResponse, ContentPart, ImageUrl, Message, ToolContext, ToolDef, ToolResult,
 };
+use crate::sessions::{self, Session, SessionRun};
+use crate::transcripts::TranscriptRecord;
+use crate::usage::TaskUsage;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-
-/// Maximum iterations to prevent infinite loops
-const MAX_ITERATIONS: usize = 20;
+use std::collections::HashMap;
+use tokio::time::{Duration, Instant};
 
 /// Single step result in the agent loop
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +39,29 @@ pub struct AgentStep {
     pub action: String,
     pub params: serde_json::Value,
     pub result: Option<String>,
+    /// Approval policy decision (absent when the policy is disabled)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval: Option<ApprovalRecord>,
+}
+
+/// Why the agent loop ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopReason {
+    /// The model answered without calling a tool
+    Completed,
+    /// Budget: iteration limit reached
+    MaxIterations,
+    /// Budget: wall-clock limit reached
+    MaxDuration,
+    /// Budget: a tool reached its call limit
+    ToolCallLimit,
+    /// Task or daily spending limit reached
+    BudgetExceeded,
+    /// Stopped by the user (cancel command or emergency stop)
+    Cancelled,
+    /// The run kept repeating itself after being corrected
+    Stalled,
 }
 
 /// Full agent execution result
@@ -34,145 +70,705 @@ pub struct AgentResult {
     pub steps: Vec<AgentStep>,
     pub final_response: String,
     pub success: bool,
+    pub stop_reason: StopReason,
+    /// Tokens and cost of every LLM call made for this task (including sub-agents)
+    pub usage: TaskUsage,
+    /// Session the run belongs to (pass it back to continue the conversation)
+    #[serde(default)]
+    pub session_id: String,
+    /// The steps were only planned (input tools described their effect)
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Final state of the plan (plan mode only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan: Option<Plan>,
+}
+
+/// Per-run settings chosen by the caller
+#[derive(Debug, Clone)]
+pub struct RunOptions {
+    /// Attach a screenshot and UI tree to the request
+    pub include_screen: bool,
+    /// Changes to the configured run budget
+    pub budget: Option<RunBudgetOverride>,
+    /// Session to continue (None starts a new one)
+    pub session_id: Option<String>,
+    /// Describe input actions instead of performing them (the run is not saved to the session)
+    pub dry_run: bool,
+    /// Plan the task with the planner sub-agent first and track progress on the plan
+    pub plan: bool,
+    /// Tool profile from the config (None = the default profile)
+    pub tool_profile: Option<String>,
+}
+
+/// Capture the screen and record it with the run (None where capture isn't available)
+fn capture_screen(
+    desktop: &dyn Desktop,
+    events: &EventSink,
+    iteration: usize,
+) -> Result<Option<(String, String)>> {
+    let Some((screenshot, ui_tree)) = desktop.capture()? else {
+        return Ok(None);
+    };
+
+    let file = events.store_screenshot(&screenshot);
+    events.record(TranscriptRecord::Screen {
+        iteration,
+        screenshot: file.clone(),
+        ui_tree: ui_tree.clone(),
+    });
+    events.emit(AgentEvent::ScreenshotCaptured {
+        iteration,
+        screenshot: file,
+    });
+
+    Ok(Some((screenshot, ui_tree)))
+}
+
+/// Hold the run while it is paused
+/// Returns true if the run has been cancelled and must stop
+async fn checkpoint(control: &RunControl, events: &EventSink, iteration: usize) -> bool {
+    if control.is_paused() && !control.is_cancelled() {
+        events.emit(AgentEvent::Paused { iteration });
+        control.wait_while_paused().await;
+        if !control.is_cancelled() {
+            events.emit(AgentEvent::Resumed { iteration });
+        }
+    }
+
+    control.is_cancelled()
+}
+
+/// Result of a run stopped before the model finished
+fn stopped_result(
+    steps: Vec<AgentStep>,
+    stop_reason: StopReason,
+    final_response: impl Into<String>,
+    usage: TaskUsage,
+) -> AgentResult {
+    AgentResult {
+        steps,
+        final_response: final_response.into(),
+        success: false,
+        stop_reason,
+        usage,
+        session_id: String::new(),
+        dry_run: false,
+        plan: None,
+    }
+}
+
+/// Stop the run if the provider refused a request over budget, fail it otherwise
+fn budget_refusal(
+    error: anyhow::Error,
+    steps: Vec<AgentStep>,
+    usage: TaskUsage,
+) -> Result<AgentResult> {
+    match error.downcast::<LlmError>() {
+        Ok(LlmError::BudgetExceeded(reason)) => Ok(stopped_result(
+            steps,
+            StopReason::BudgetExceeded,
+            reason,
+            usage,
+        )),
+        Ok(e) => Err(e.into()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Releases the buttons a run still holds when dropped
+struct ReleaseHeld<'a>(&'a ToolContext);
+
+impl Drop for ReleaseHeld<'_> {
+    fn drop(&mut self) {
+        if let Err(e) = self.0.release_held() {
+            log::warn!("Failed to release held mouse buttons: {}", e);
+        }
+    }
 }
 
-/// Get all tool definitions (primitives + agents) for API
-fn get_all_tool_defs() -> Vec<ToolDef> {
-    let primitive_tools: Vec<Box<dyn Tool>> = vec![
-        Box::new(MouseMoveTool),
-        Box::new(MouseClickTool),
-        Box::new(MouseDoubleClickTool),
-        Box::new(KeyboardTypeTool),
-        Box::new(KeyboardPressTool),
-        Box::new(ScreenUpdateTool),
-        Box::new(WaitTool),
-        Box::new(ScrollTool),
-    ];
-
-    let mut defs: Vec<ToolDef> = primitive_tools.iter().map(|t| t.to_tool_def()).collect();
-    defs.extend(get_agent_tool_defs());
-    defs
+/// Resolves when the deadline passes (never without one)
+async fn deadline_reached(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
 }
 
-/// Capture current screen state (Windows only)
-#[cfg(windows)]
-fn capture_screen_context() -> Result<(String, String)> {
-    let screenshot = crate::screen::capture::capture_screen_base64()?;
-    let ui_tree = crate::screen::ui_automation::get_active_window_tree(2)
-        .map(|t| serde_json::to_string_pretty(&t).unwrap_or_default())
-        .unwrap_or_default();
-    Ok((screenshot, ui_tree))
+/// Send one request, streaming deltas to the frontend when enabled
+async fn request_completion(
+    provider: &dyn LlmProvider,
+    messages: Vec<Message>,
+    tools: Option<Vec<ToolDef>>,
+    stream: bool,
+    events: &EventSink,
+    iteration: usize,
+) -> Result<ChatResponse> {
+    if !stream {
+        return provider.chat(messages, tools).await;
+    }
+
+    let mut on_delta = |delta| {
+        events.emit_stream(StreamEvent {
+            run_id: events.run_id().to_string(),
+            iteration,
+            delta,
+        });
+    };
+    provider.chat_stream(messages, tools, &mut on_delta).await
 }
 
 /// Run the main agent loop
 /// - Calls LLM, executes tools, feeds results back
-/// - Continues until no tool call or max iterations
-pub async fn run_agent_loop(user_message: &str, include_screen: bool) -> Result<AgentResult> {
-    let config = load_config()?;
+/// - Continues until no tool call or a limit of the run budget is hit
+/// - Prunes old screenshots and summarizes old steps near the context budget
+/// - Streams tokens as `llm-stream` events and reports progress as `agent-event` events
+/// - Ends every run with a `finished` or `error` event
+/// - Can be paused, resumed and cancelled through `control`
+///   (checked before each LLM call and tool call, and inside long input actions)
+/// - Continues the session in `options` (or starts one) and saves it afterwards
+pub async fn run_agent_loop(
+    user_message: &str,
+    options: &RunOptions,
+    events: &EventSink,
+    control: &RunControl,
+) -> Result<AgentResult> {
+    match RunEnvironment::live() {
+        Ok(env) => run_agent_loop_in(&env, user_message, options, events, control).await,
+        Err(e) => {
+            events.emit(AgentEvent::Error {
+                message: e.to_string(),
+            });
+            Err(e)
+        }
+    }
+}
+
+/// Run the agent loop against a given config, provider and desktop
+/// (`run_agent_loop` uses the live ones; replay tests pass recorded ones)
+pub async fn run_agent_loop_in(
+    env: &RunEnvironment,
+    user_message: &str,
+    options: &RunOptions,
+    events: &EventSink,
+    control: &RunControl,
+) -> Result<AgentResult> {
+    let started_at = chrono::Local::now().to_rfc3339();
+    let session = match &options.session_id {
+        Some(id) => sessions::storage::load_session(id),
+        None => Ok(Session::new(user_message)),
+    };
+
+    // Filled by the planner once the screen has been captured
+    let plan: Option<SharedPlan> = options.plan.then(SharedPlan::default);
+
+    let result = match session {
+        Ok(mut session) => {
+            let mut messages = std::mem::take(&mut session.messages);
+            let result = agent_loop(
+                env,
+                user_message,
+                options,
+                plan.clone(),
+                events,
+                control,
+                &mut messages,
+            )
+            .await;
+
+            // A new session that failed before the request was sent isn't worth keeping,
+            // and a dry run didn't really happen
+            let keep = !messages.is_empty() || options.session_id.is_some();
+            if env.persist && !options.dry_run && keep {
+                record_run(
+                    &mut session,
+                    messages,
+                    events.run_id(),
+                    user_message,
+                    started_at,
+                    &result,
+                );
+                // Persisting the conversation must never fail the run itself
+                if let Err(e) = sessions::storage::save_session(&mut session) {
+                    log::warn!("Failed to save session {}: {}", session.id, e);
+                }
+            }
+
+            result.map(|result| AgentResult {
+                session_id: session.id,
+                dry_run: options.dry_run,
+                plan: plan
+                    .as_ref()
+                    .map(|plan| plan.lock().unwrap_or_else(|e| e.into_inner()).clone()),
+                ..result
+            })
+        }
+        Err(e) => Err(e),
+    };
+
+    match &result {
+        Ok(result) => events.emit(AgentEvent::Finished {
+            result: result.clone(),
+        }),
+        Err(e) => events.emit(AgentEvent::Error {
+            message: e.to_string(),
+        }),
+    }
+
+    result
+}
+
+/// Record a run and the updated history in its session
+fn record_run(
+    session: &mut Session,
+    mut messages: Vec<Message>,
+    run_id: &str,
+    user_message: &str,
+    started_at: String,
+    result: &Result<AgentResult>,
+) {
+    // The system prompt is rebuilt for every run
+    if messages.first().is_some_and(|m| m.role == "system") {
+        messages.remove(0);
+    }
+
+    let (steps, final_response, stop_reason, error) = match result {
+        Ok(r) => (r.steps.clone(), r.final_response.clone(), Some(r.stop_reason), None),
+        Err(e) => (Vec::new(), String::new(), None, Some(e.to_string())),
+    };
+    let reason = error.as_deref().unwrap_or(&final_response);
+    answer_pending_calls(&mut messages, reason);
+    session.set_messages(messages);
+    session.runs.push(SessionRun {
+        run_id: run_id.to_string(),
+        user_message: user_message.to_string(),
+        started_at,
+        finished_at: chrono::Local::now().to_rfc3339(),
+        steps,
+        final_response,
+        stop_reason,
+        error,
+    });
+}
+
+/// Answer the tool calls a stopped run didn't get to
+/// (providers reject a history with tool calls that have no result)
+fn answer_pending_calls(messages: &mut Vec<Message>, reason: &str) {
+    let Some(index) = messages.iter().rposition(|m| m.tool_calls.is_some()) else {
+        return;
+    };
+    let calls = messages[index].tool_calls.clone().unwrap_or_default();
+    // Right after the results the turn did get
+    let mut insert_at = messages[index + 1..]
+        .iter()
+        .rposition(|m| m.role == "tool")
+        .map_or(index + 1, |last| index + 2 + last);
+    for call in calls {
+        let answered = messages[index + 1..]
+            .iter()
+            .any(|m| m.tool_call_id.as_deref() == Some(call.id.as_str()));
+        if !answered {
+            messages.insert(
+                insert_at,
+                Message::tool(&call, format!("Not executed: {}", reason)).with_error(true),
+            );
+            insert_at += 1;
+        }
+    }
+}
 
-    if config.api.api_key.is_empty() {
+/// Agent loop body (events for individual steps are emitted here)
+/// `messages` holds the session history and receives this run's messages
+async fn agent_loop(
+    env: &RunEnvironment,
+    user_message: &str,
+    options: &RunOptions,
+    plan: Option<SharedPlan>,
+    events: &EventSink,
+    control: &RunControl,
+    messages: &mut Vec<Message>,
+) -> Result<AgentResult> {
+    let config = &env.config;
+
+    if env.provider.is_none() && config.api.requires_api_key() && config.api.api_key.is_empty() {
         return Err(anyhow::anyhow!("API key not configured"));
     }
 
+    let budget = match &options.budget {
+        Some(changes) => changes.apply(&config.budget),
+        None => config.budget.clone(),
+    };
+    let deadline = budget
+        .max_duration_secs
+        .map(|secs| Instant::now() + Duration::from_secs(secs));
+
+    let (provider, meter) = env.metered_provider();
     let ctx = ToolContext {
-        api_config: config.api.clone(),
+        provider: provider.clone(),
+        tool_mode: config.api.tool_mode,
+        cancel: control.token(),
+        budget: budget.clone(),
+        depth: 0,
+        deadline,
+        dry_run: options.dry_run,
+        plan: plan.clone(),
+        input: env.desktop.input(),
+        held: Default::default(),
     };
+    // Whichever way the run ends, don't leave a button pressed
+    let _release = ReleaseHeld(&ctx);
+
+    events.record(TranscriptRecord::RunStarted {
+        user_message: user_message.to_string(),
+        model: config.api.model.clone(),
+        tool_mode: config.api.tool_mode,
+    });
 
-    let executor = ToolExecutor::new();
-    let tools = get_all_tool_defs();
+    let profile = config.tools.profile(options.tool_profile.as_deref())?;
+    let tool_set = TOOL_REGISTRY.tool_set(profile, plan.is_some(), env.desktop.os());
+    let tools = tool_set.tool_defs();
+    let policy = ApprovalPolicy::from_config(&config.approval)?.dry_run(options.dry_run);
+
+    // In text mode tools are described in the prompt instead of sent natively
+    let text_mode = config.api.tool_mode == ToolCallMode::Text;
+    let api_tools = if text_mode { None } else { Some(tools.clone()) };
 
     // Build initial messages
-    let system_prompt = build_main_agent_prompt();
-    let mut messages = vec![Message {
-        role: "system".to_string(),
-        content: MessageContent::Text(system_prompt),
-        tool_call_id: None,
-    }];
+    let mut system_prompt = build_main_agent_prompt(&tool_set);
+    let executor = ToolExecutor::new(tool_set.clone());
+    if plan.is_some() {
+        system_prompt.push_str(PLAN_MODE_PROMPT);
+    }
+    if text_mode {
+        system_prompt.push_str(&tool_protocol_prompt(&tools));
+    }
+    messages.insert(0, Message::system(system_prompt));
 
     // Add user message with optional screen capture
-    if include_screen && config.api.supports_vision {
-        #[cfg(windows)]
-        {
-            let (screenshot, ui_tree) = capture_screen_context()?;
-            messages.push(Message {
-                role: "user".to_string(),
-                content: MessageContent::Parts(vec![
-                    ContentPart::Text {
-                        text: format!(
-                            "Current screen state:\n\nUI Elements:\n{}\n\nUser request: {}",
-                            ui_tree, user_message
-                        ),
-                    },
-                    ContentPart::ImageUrl {
-                        image_url: ImageUrl {
-                            url: screenshot,
-                            detail: Some("high".to_string()),
-                        },
-                    },
-                ]),
-                tool_call_id: None,
-            });
-        }
-        #[cfg(not(windows))]
-        {
-            messages.push(Message {
-                role: "user".to_string(),
-                content: MessageContent::Text(user_message.to_string()),
-                tool_call_id: None,
-            });
-        }
+    let screen = if options.include_screen && config.api.supports_vision {
+        capture_screen(env.desktop.as_ref(), events, 0)?
     } else {
-        messages.push(Message {
-            role: "user".to_string(),
-            content: MessageContent::Text(user_message.to_string()),
-            tool_call_id: None,
+        None
+    };
+
+    // Plan mode - the planner breaks the request into steps before the first action
+    let mut request = user_message.to_string();
+    if let Some(plan) = &plan {
+        let planned = match create_plan(
+            user_message,
+            screen.as_ref().map(|(s, t)| (s.as_str(), t.as_str())),
+            &ctx,
+        )
+        .await
+        {
+            Ok(planned) => planned,
+            Err(_) if ctx.cancel.is_cancelled() => {
+                return Ok(stopped_result(
+                    Vec::new(),
+                    StopReason::Cancelled,
+                    "Cancelled by user",
+                    meter.snapshot(),
+                ));
+            }
+            Err(e) => return Err(e),
+        };
+        request.push_str(&format!("\n\nPlan:\n{}", planned.render()));
+        events.emit(AgentEvent::PlanUpdated {
+            iteration: 0,
+            plan: planned.clone(),
         });
+        *plan.lock().unwrap_or_else(|e| e.into_inner()) = planned;
+    }
+
+    let mut stall = StallDetector::new(&config.stall);
+    if let Some((screenshot, _)) = &screen {
+        stall.record_screen(screenshot);
     }
 
+    match screen {
+        Some((screenshot, ui_tree)) => messages.push(Message::user_parts(vec![
+            ContentPart::Text {
+                text: format!(
+                    "Current screen state:\n\nUI Elements:\n{}\n\nUser request: {}",
+                    ui_tree, request
+                ),
+            },
+            ContentPart::ImageUrl {
+                image_url: ImageUrl {
+                    url: screenshot,
+                    detail: Some("high".to_string()),
+                },
+            },
+        ])),
+        None => messages.push(Message::user(request)),
+    }
+
+    let history = HistoryManager::new(config.context.clone());
     let mut steps: Vec<AgentStep> = Vec::new();
+    let mut tool_counts: HashMap<String, u32> = HashMap::new();
+    // Messages already written to the transcript with an earlier request
+    let mut recorded_messages = 0;
+    let out_of_time = || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+    let time_limit_message = || {
+        format!(
+            "Time limit of {}s reached. Task may be incomplete.",
+            budget.max_duration_secs.unwrap_or_default()
+        )
+    };
 
     // Main agent loop
-    for _iteration in 0..MAX_ITERATIONS {
-        // Call LLM
-        let response = chat_completion(&config.api, messages.clone(), Some(tools.clone())).await?;
+    for iteration in 0..budget.max_iterations {
+        events.emit(AgentEvent::StepStarted { iteration });
+
+        if checkpoint(control, events, iteration).await {
+            return Ok(stopped_result(
+                steps,
+                StopReason::Cancelled,
+                "Cancelled by user",
+                meter.snapshot(),
+            ));
+        }
+        if out_of_time() {
+            return Ok(stopped_result(
+                steps,
+                StopReason::MaxDuration,
+                time_limit_message(),
+                meter.snapshot(),
+            ));
+        }
+
+        // Stop before spending more once a budget is reached
+        if let Some(reason) = meter.budget_exceeded() {
+            return Ok(stopped_result(
+                steps,
+                StopReason::BudgetExceeded,
+                reason,
+                meter.snapshot(),
+            ));
+        }
+
+        // Keep the history inside the context window
+        if let Err(e) = history.prepare(messages, provider.as_ref()).await {
+            return budget_refusal(e, steps, meter.snapshot());
+        }
+
+        // Compaction rewrites the history - record the whole prompt again
+        if recorded_messages > messages.len() {
+            recorded_messages = 0;
+        }
+        events.record(TranscriptRecord::Request {
+            iteration,
+            from_index: recorded_messages,
+            messages: messages[recorded_messages..].to_vec(),
+        });
+        recorded_messages = messages.len();
+        let request_started = Instant::now();
+
+        // Call LLM - if the prompt still doesn't fit, compact once more and retry
+        let request = |messages: Vec<Message>| {
+            request_completion(
+                provider.as_ref(),
+                messages,
+                api_tools.clone(),
+                config.api.stream,
+                events,
+                iteration,
+            )
+        };
+        let completion = async {
+            match request(messages.clone()).await {
+                Err(e)
+                    if matches!(
+                        e.downcast_ref::<LlmError>(),
+                        Some(LlmError::ContextLengthExceeded(_))
+                    ) && history.compact(messages, provider.as_ref()).await? =>
+                {
+                    request(messages.clone()).await
+                }
+                result => result,
+            }
+        };
+
+        // Abandon the request as soon as the run is cancelled or out of time
+        let cancel = control.token();
+        let response = tokio::select! {
+            response = completion => match response {
+                Ok(response) => response,
+                Err(e) => return budget_refusal(e, steps, meter.snapshot()),
+            },
+            _ = cancel.cancelled() => {
+                return Ok(stopped_result(
+                    steps,
+                    StopReason::Cancelled,
+                    "Cancelled by user",
+                    meter.snapshot(),
+                ));
+            }
+            _ = deadline_reached(deadline) => {
+                return Ok(stopped_result(
+                    steps,
+                    StopReason::MaxDuration,
+                    time_limit_message(),
+                    meter.snapshot(),
+                ));
+            }
+        };
 
         let choice = response
             .choices
             .first()
             .ok_or_else(|| anyhow::anyhow!("No response from API"))?;
 
-        let thought = choice
-            .message
-            .content
-            .clone()
-            .unwrap_or_else(|| String::new());
+        events.record(TranscriptRecord::Response {
+            iteration,
+            duration_ms: request_started.elapsed().as_millis() as u64,
+            message: choice.message.to_message(),
+            usage: response.usage,
+        });
 
-        // Check for tool calls
-        if let Some(tool_calls) = &choice.message.tool_calls {
-            if tool_calls.is_empty() {
-                // No tool calls - agent is done
-                return Ok(AgentResult {
-                    steps,
-                    final_response: thought,
-                    success: true,
-                });
+        let content = choice.message.content.clone().unwrap_or_default();
+
+        // Resolve tool calls - native, or parsed from the text in text mode
+        let (thought, tool_calls) = if text_mode {
+            match parse_response(&content, &tools) {
+                Ok(turn) => (turn.thought, turn.tool_calls),
+                Err(e) => {
+                    // Invalid invocation - show the model what went wrong and retry
+                    messages.push(Message::assistant(content));
+                    messages.push(invalid_call_message(&e));
+                    continue;
+                }
             }
+        } else {
+            (content.clone(), choice.message.tool_calls.clone().unwrap_or_default())
+        };
 
-            // Add assistant message to history
-            messages.push(Message {
-                role: "assistant".to_string(),
-                content: MessageContent::Text(thought.clone()),
-                tool_call_id: None,
+        if !thought.is_empty() {
+            events.emit(AgentEvent::Thought {
+                iteration,
+                text: thought.clone(),
+            });
+        }
+
+        // Check for tool calls
+        if !tool_calls.is_empty() {
+            // Add assistant message to history as received
+            // (text mode keeps the raw invocation, native mode the tool_calls)
+            messages.push(if text_mode {
+                Message::assistant(content)
+            } else {
+                choice.message.to_message()
             });
 
             // Execute each tool call
-            for tool_call in tool_calls {
+            for tool_call in &tool_calls {
+                if checkpoint(control, events, iteration).await {
+                    return Ok(stopped_result(
+                        steps,
+                        StopReason::Cancelled,
+                        "Cancelled by user",
+                        meter.snapshot(),
+                    ));
+                }
+                if out_of_time() {
+                    return Ok(stopped_result(
+                        steps,
+                        StopReason::MaxDuration,
+                        time_limit_message(),
+                        meter.snapshot(),
+                    ));
+                }
+
                 let tool_name = &tool_call.function.name;
-                let params: serde_json::Value =
-                    serde_json::from_str(&tool_call.function.arguments).unwrap_or(json!({}));
 
-                // Execute the tool
-                let tool_result = executor.execute(tool_name, params.clone(), &ctx).await;
+                // Per-tool call limit from the budget
+                let count = tool_counts.entry(tool_name.clone()).or_default();
+                if let Some(&max) = budget.max_tool_calls.get(tool_name) {
+                    if *count >= max {
+                        return Ok(stopped_result(
+                            steps,
+                            StopReason::ToolCallLimit,
+                            format!(
+                                "Tool call limit reached: {} may be called at most {} times",
+                                tool_name, max
+                            ),
+                            meter.snapshot(),
+                        ));
+                    }
+                }
+                *count += 1;
+
+                // Check the arguments against the tool's schema (unknown tools fail in the executor)
+                let raw_arguments = &tool_call.function.arguments;
+                let schema = tool_set
+                    .get(tool_name)
+                    .map(|tool| tool.parameters())
+                    .unwrap_or(serde_json::Value::Null);
+                let (params, argument_error) = match parse_arguments(raw_arguments, &schema) {
+                    Ok(params) => (params, None),
+                    Err(e) => {
+                        let params = serde_json::from_str(raw_arguments)
+                            .unwrap_or_else(|_| serde_json::Value::String(raw_arguments.clone()));
+                        (params, Some(e))
+                    }
+                };
+
+                events.emit(AgentEvent::ToolCall {
+                    iteration,
+                    call_id: tool_call.id.clone(),
+                    name: tool_name.clone(),
+                    params: params.clone(),
+                });
+
+                // Check the approval policy, then execute the tool
+                // (invalid arguments go back to the model without asking or running anything)
+                let tool_started = Instant::now();
+                let approval = match &argument_error {
+                    Some(_) => None,
+                    None => {
+                        policy
+                            .review(
+                                iteration,
+                                &tool_call.id,
+                                tool_name,
+                                &params,
+                                env.desktop.as_ref(),
+                                events,
+                                &ctx.cancel,
+                            )
+                            .await
+                    }
+                };
+                let tool_result = match (&argument_error, &approval) {
+                    (Some(error), _) => Ok(ToolResult::error(error.to_tool_error(tool_name, &schema))),
+                    (_, Some(record)) if !record.decision.permits() => {
+                        Ok(ToolResult::error(record.refusal()))
+                    }
+                    // Tell the model what the action changed on screen
+                    _ if config.verification.enabled && !ctx.dry_run && is_input_tool(tool_name) => {
+                        execute_verified(
+                            env.desktop.as_ref(),
+                            &executor,
+                            tool_name,
+                            params.clone(),
+                            &ctx,
+                            &config.verification,
+                        )
+                        .await
+                    }
+                    _ => {
+                        env.desktop
+                            .execute(&executor, tool_name, params.clone(), &ctx)
+                            .await
+                    }
+                };
+
+                // Show the action on the overlay (performed or planned in a dry run)
+                if let Ok(result) = &tool_result {
+                    for effect in &result.effects {
+                        events.emit_overlay(effect);
+                    }
+                }
 
                 let result_text = match &tool_result {
                     Ok(r) => {
@@ -185,46 +781,110 @@ pub async fn run_agent_loop(user_message: &str, include_screen: bool) -> Result<
                     Err(e) => format!("Error: {}", e),
                 };
 
+                let succeeded = matches!(&tool_result, Ok(r) if r.success);
+
+                events.emit(AgentEvent::ToolResult {
+                    iteration,
+                    call_id: tool_call.id.clone(),
+                    name: tool_name.clone(),
+                    success: succeeded,
+                    output: result_text.clone(),
+                    duration_ms: tool_started.elapsed().as_millis() as u64,
+                });
+
+                // Share plan changes with the UI
+                if let (Some(plan), Ok(result)) = (&plan, &tool_result) {
+                    if result.success && matches!(tool_name.as_str(), "update_plan" | "replan") {
+                        events.emit(AgentEvent::PlanUpdated {
+                            iteration,
+                            plan: plan.lock().unwrap_or_else(|e| e.into_inner()).clone(),
+                        });
+                    }
+                }
+
+                stall.record_call(tool_name, &params);
+
                 // Record the step
                 steps.push(AgentStep {
                     thought: thought.clone(),
                     action: tool_name.clone(),
                     params: params.clone(),
                     result: Some(result_text.clone()),
+                    approval,
                 });
 
                 // Add tool result to messages
-                messages.push(Message {
-                    role: "tool".to_string(),
-                    content: MessageContent::Text(result_text),
-                    tool_call_id: Some(tool_call.id.clone()),
-                });
+                if text_mode {
+                    messages.push(tool_result_message(tool_call, &result_text));
+                } else {
+                    messages.push(Message::tool(tool_call, result_text).with_error(!succeeded));
+                }
 
                 // Special handling for get_screen_update - capture new screen
-                if tool_name == "get_screen_update" {
-                    #[cfg(windows)]
-                    if config.api.supports_vision {
-                        if let Ok((screenshot, ui_tree)) = capture_screen_context() {
-                            messages.push(Message {
-                                role: "user".to_string(),
-                                content: MessageContent::Parts(vec![
-                                    ContentPart::Text {
-                                        text: format!("Updated screen state:\n\nUI Elements:\n{}", ui_tree),
-                                    },
-                                    ContentPart::ImageUrl {
-                                        image_url: ImageUrl {
-                                            url: screenshot,
-                                            detail: Some("high".to_string()),
-                                        },
-                                    },
-                                ]),
-                                tool_call_id: None,
-                            });
-                        }
+                if tool_name == "get_screen_update" && config.api.supports_vision {
+                    if let Ok(Some((screenshot, ui_tree))) =
+                        capture_screen(env.desktop.as_ref(), events, iteration)
+                    {
+                        stall.record_screen(&screenshot);
+                        messages.push(Message::user_parts(vec![
+                            ContentPart::Text {
+                                text: format!("Updated screen state:\n\nUI Elements:\n{}", ui_tree),
+                            },
+                            ContentPart::ImageUrl {
+                                image_url: ImageUrl {
+                                    url: screenshot,
+                                    detail: Some("high".to_string()),
+                                },
+                            },
+                        ]));
                     }
                 }
             }
 
+            // Correct a run that repeats itself, and stop it if that doesn't help
+            if let Some(detected) = stall.check() {
+                let intervention = stall.intervene(plan.is_some());
+                events.emit(AgentEvent::StallDetected {
+                    iteration,
+                    reason: detected.to_string(),
+                    intervention,
+                });
+
+                match (intervention, &plan) {
+                    (Intervention::Abort, _) => {
+                        return Ok(stopped_result(
+                            steps,
+                            StopReason::Stalled,
+                            format!(
+                                "Stopped because the run stalled: {} (still repeating after {} corrections)",
+                                detected,
+                                stall.interventions() - 1
+                            ),
+                            meter.snapshot(),
+                        ));
+                    }
+                    (Intervention::Replan, Some(plan)) => {
+                        let reason = format!("The run stalled: {}", detected);
+                        let note = match replan(plan, &reason, &ctx).await {
+                            Ok(rendered) => {
+                                events.emit(AgentEvent::PlanUpdated {
+                                    iteration,
+                                    plan: plan.lock().unwrap_or_else(|e| e.into_inner()).clone(),
+                                });
+                                format!(
+                                    "{}\n\nThe plan was revised:\n{}",
+                                    corrective_note(&detected),
+                                    rendered
+                                )
+                            }
+                            Err(_) => corrective_note(&detected),
+                        };
+                        messages.push(Message::user(note));
+                    }
+                    _ => messages.push(Message::user(corrective_note(&detected))),
+                }
+            }
+
             // Continue to next iteration
         } else {
             // No tool calls - agent is done
@@ -232,23 +892,45 @@ pub async fn run_agent_loop(user_message: &str, include_screen: bool) -> Result<
                 steps,
                 final_response: thought,
                 success: true,
+                stop_reason: StopReason::Completed,
+                usage: meter.snapshot(),
+                session_id: String::new(),
+                dry_run: false,
+                plan: None,
             });
         }
     }
 
     // Max iterations reached
-    Ok(AgentResult {
+    Ok(stopped_result(
         steps,
-        final_response: "Maximum iterations reached. Task may be incomplete.".to_string(),
-        success: false,
-    })
+        StopReason::MaxIterations,
+        format!(
+            "Maximum iterations ({}) reached. Task may be incomplete.",
+            budget.max_iterations
+        ),
+        meter.snapshot(),
+    ))
 }
 
 /// Legacy single-turn function for backward compatibility
 /// This calls the new loop but returns only the first action
 #[allow(dead_code)]
 pub async fn process_message(user_message: &str, include_screen: bool) -> Result<ActionResponse> {
-    let result = run_agent_loop(user_message, include_screen).await?;
+    let run_id = new_run_id();
+    let events = EventSink::new(run_id.clone(), None);
+    let control = control::register(&run_id);
+    let options = RunOptions {
+        include_screen,
+        budget: None,
+        session_id: None,
+        dry_run: false,
+        plan: false,
+        tool_profile: None,
+    };
+    let result = run_agent_loop(user_message, &options, &events, &control).await;
+    control::unregister(&run_id);
+    let result = result?;
 
     if let Some(first_step) = result.steps.first() {
         Ok(ActionResponse {
@@ -264,3 +946,464 @@ pub async fn process_message(user_message: &str, include_screen: bool) -> Result
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::config::{AppConfig, PolicyAction};
+    use crate::commands::input::MouseButton;
+    use crate::input::fake::{solid_screenshot, window, FakeBackend, FakeDesktop, InputAction};
+    use crate::llm::mock_server::{MockResponse, MockServer};
+    use crate::llm::runner::approval::ApprovalDecision;
+    use std::sync::Arc;
+
+    const TOOL_CALL_RESPONSE: &str =
+        include_str!("../../../tests/fixtures/chat_tool_call_response.json");
+    const FINAL_RESPONSE: &str = include_str!("../../../tests/fixtures/chat_final_response.json");
+
+    /// Model clicks at (640, 360) and types "notepad", then answers
+    async fn notepad_server() -> MockServer {
+        MockServer::start(vec![
+            MockResponse::json(200, serde_json::from_str(TOOL_CALL_RESPONSE).unwrap()),
+            MockResponse::json(200, serde_json::from_str(FINAL_RESPONSE).unwrap()),
+        ])
+        .await
+    }
+
+    /// Fake desktop with `screens` captures of an empty desktop scripted
+    fn desktop_fake(screens: usize) -> Arc<FakeBackend> {
+        let fake = FakeBackend::new();
+        for _ in 0..screens {
+            fake.push_screen(
+                solid_screenshot(8, 8, [0, 0, 0, 255]),
+                window("Desktop", vec![]),
+            );
+        }
+        fake
+    }
+
+    fn fake_environment(server: &MockServer, fake: &Arc<FakeBackend>) -> RunEnvironment {
+        let mut config = AppConfig::default();
+        config.api.endpoint = server.url.clone();
+        config.api.api_key = "test-key".to_string();
+        config.api.stream = false;
+        config.approval.enabled = false;
+        RunEnvironment {
+            config,
+            provider: None,
+            desktop: Arc::new(FakeDesktop {
+                backend: fake.clone(),
+            }),
+            persist: false,
+        }
+    }
+
+    fn options() -> RunOptions {
+        RunOptions {
+            include_screen: true,
+            budget: None,
+            session_id: None,
+            dry_run: false,
+            plan: false,
+            tool_profile: None,
+        }
+    }
+
+    async fn run(env: &RunEnvironment) -> AgentResult {
+        run_with(env, &options()).await
+    }
+
+    async fn run_with(env: &RunEnvironment, options: &RunOptions) -> AgentResult {
+        let events = EventSink::new(new_run_id(), None);
+        run_agent_loop_in(
+            env,
+            "Open notepad",
+            options,
+            &events,
+            &RunControl::default(),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn tool_calls_act_on_the_desktop() {
+        let server = notepad_server().await;
+        let fake = desktop_fake(1);
+
+        let result = run(&fake_environment(&server, &fake)).await;
+
+        assert_eq!(result.stop_reason, StopReason::Completed);
+        assert_eq!(result.final_response, "Notepad is open.");
+        assert_eq!(fake.cursor_path().last(), Some(&(640, 360)));
+        assert_eq!(
+            fake.events(),
+            [
+                InputAction::ButtonDown(MouseButton::Left),
+                InputAction::ButtonUp(MouseButton::Left)
+            ]
+        );
+        assert_eq!(fake.typed(), "notepad");
+
+        // The scripted screen was sent with the request
+        let first = &server.requests()[0].body;
+        assert!(first.contains("data:image/png;base64,"));
+        assert!(first.contains("Desktop"));
+    }
+
+    #[tokio::test]
+    async fn verification_compares_scripted_screens() {
+        let server = notepad_server().await;
+        let fake = FakeBackend::new();
+        let blank = solid_screenshot(32, 32, [255, 255, 255, 255]);
+        let dark = solid_screenshot(32, 32, [0, 0, 0, 255]);
+        fake.push_screen(blank.clone(), window("Desktop", vec![]));
+        fake.push_screen(blank, window("Desktop", vec![]));
+        fake.push_screen(dark, window("Run", vec![]));
+        let mut env = fake_environment(&server, &fake);
+        env.config.verification.enabled = true;
+        env.config.verification.settle_ms = 0;
+
+        let result = run(&env).await;
+
+        let click = result.steps[0].result.as_deref().unwrap();
+        assert!(click.contains("of the screen changed"), "{}", click);
+        let typing = result.steps[1].result.as_deref().unwrap();
+        assert!(typing.contains("nothing changed"), "{}", typing);
+    }
+
+    #[tokio::test]
+    async fn cancelling_while_planning_stops_the_run() {
+        let server = MockServer::start(vec![
+            MockResponse::answer("1. Open Notepad").with_delay(Duration::from_secs(30))
+        ])
+        .await;
+        let fake = desktop_fake(1);
+        let env = fake_environment(&server, &fake);
+        let options = RunOptions {
+            plan: true,
+            ..options()
+        };
+        let events = EventSink::new(new_run_id(), None);
+        let control = RunControl::default();
+        let token = control.token();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            token.cancel();
+        });
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            run_agent_loop_in(&env, "Open notepad", &options, &events, &control),
+        )
+        .await
+        .expect("the run kept waiting for the planner")
+        .unwrap();
+
+        assert_eq!(result.stop_reason, StopReason::Cancelled);
+        assert!(result.steps.is_empty());
+    }
+
+    #[tokio::test]
+    async fn paused_run_waits_for_resume() {
+        let server = notepad_server().await;
+        let fake = desktop_fake(1);
+        let env = fake_environment(&server, &fake);
+        let events = EventSink::new(new_run_id(), None);
+        let control = Arc::new(RunControl::default());
+        control.pause();
+
+        let run = {
+            let control = control.clone();
+            tokio::spawn(async move {
+                run_agent_loop_in(&env, "Open notepad", &options(), &events, &control).await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        // Nothing was sent to the model or done on the desktop while paused
+        assert!(server.requests().is_empty());
+        assert!(fake.events().is_empty());
+
+        control.resume();
+        let result = run.await.unwrap().unwrap();
+        assert_eq!(result.stop_reason, StopReason::Completed);
+        assert_eq!(fake.typed(), "notepad");
+    }
+
+    #[tokio::test]
+    async fn cancelling_a_paused_run_stops_it() {
+        let server = notepad_server().await;
+        let fake = desktop_fake(1);
+        let env = fake_environment(&server, &fake);
+        let events = EventSink::new(new_run_id(), None);
+        let control = RunControl::default();
+        control.pause();
+        let token = control.token();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            token.cancel();
+        });
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            run_agent_loop_in(&env, "Open notepad", &options(), &events, &control),
+        )
+        .await
+        .expect("the run stayed paused after cancel")
+        .unwrap();
+
+        assert_eq!(result.stop_reason, StopReason::Cancelled);
+        assert!(result.steps.is_empty());
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn dry_run_plans_calls_without_asking() {
+        let server = notepad_server().await;
+        let fake = desktop_fake(1);
+        let mut env = fake_environment(&server, &fake);
+        env.config.approval.enabled = true;
+        env.config.approval.default_action = PolicyAction::Ask;
+        env.config.approval.rules.clear();
+        let options = RunOptions {
+            dry_run: true,
+            ..options()
+        };
+
+        // Nobody answers - a prompt would stall the run until the timeout
+        let result = tokio::time::timeout(Duration::from_secs(5), run_with(&env, &options))
+            .await
+            .expect("the dry run waited for an approval");
+
+        assert_eq!(result.stop_reason, StopReason::Completed);
+        assert!(result.steps.iter().all(|step| {
+            step.approval.as_ref().map(|a| a.decision) == Some(ApprovalDecision::Planned)
+        }));
+        assert!(fake.actions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn buttons_held_by_mouse_down_are_released_when_the_run_ends() {
+        let server = MockServer::start(vec![
+            MockResponse::tool_calls(&[(
+                "mouse_down",
+                json!({ "x": 10, "y": 20, "button": "right" }),
+            )]),
+            MockResponse::json(200, serde_json::from_str(FINAL_RESPONSE).unwrap()),
+        ])
+        .await;
+        let fake = desktop_fake(1);
+
+        let result = run(&fake_environment(&server, &fake)).await;
+
+        assert_eq!(result.stop_reason, StopReason::Completed);
+        assert_eq!(
+            fake.events(),
+            [
+                InputAction::ButtonDown(MouseButton::Right),
+                InputAction::ButtonUp(MouseButton::Right)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn buttons_are_released_when_a_limit_stops_the_run() {
+        let server = MockServer::start(vec![MockResponse::tool_calls(&[
+            ("mouse_down", json!({ "x": 10, "y": 20 })),
+            ("mouse_up", json!({ "x": 30, "y": 40 })),
+        ])])
+        .await;
+        let fake = desktop_fake(1);
+        let options = RunOptions {
+            budget: Some(RunBudgetOverride {
+                max_tool_calls: Some([("mouse_up".to_string(), 0)].into()),
+                ..Default::default()
+            }),
+            ..options()
+        };
+
+        let result = run_with(&fake_environment(&server, &fake), &options).await;
+
+        assert_eq!(result.stop_reason, StopReason::ToolCallLimit);
+        assert_eq!(
+            fake.events(),
+            [
+                InputAction::ButtonDown(MouseButton::Left),
+                InputAction::ButtonUp(MouseButton::Left)
+            ]
+        );
+        // Released where the button went down, not at the mouse_up target
+        assert_eq!(fake.cursor_path().last(), Some(&(10, 20)));
+    }
+
+    #[tokio::test]
+    async fn iteration_limit_of_the_override_wins() {
+        let server = notepad_server().await;
+        let fake = desktop_fake(1);
+        let mut env = fake_environment(&server, &fake);
+        env.config.budget.max_iterations = 5;
+        let options = RunOptions {
+            budget: Some(RunBudgetOverride {
+                max_iterations: Some(1),
+                ..Default::default()
+            }),
+            ..options()
+        };
+
+        let result = run_with(&env, &options).await;
+
+        assert_eq!(result.stop_reason, StopReason::MaxIterations);
+        assert_eq!(
+            result.final_response,
+            "Maximum iterations (1) reached. Task may be incomplete."
+        );
+        // The first step's tools ran, the answer was never requested
+        assert_eq!(fake.typed(), "notepad");
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn tool_call_limits_merge_with_the_config() {
+        let server = notepad_server().await;
+        let fake = desktop_fake(1);
+        let mut env = fake_environment(&server, &fake);
+        env.config.budget.max_tool_calls = [
+            ("mouse_click".to_string(), 0),
+            ("keyboard_type".to_string(), 0),
+        ]
+        .into();
+        let options = RunOptions {
+            budget: Some(RunBudgetOverride {
+                max_tool_calls: Some([("mouse_click".to_string(), 1)].into()),
+                ..Default::default()
+            }),
+            ..options()
+        };
+
+        let result = run_with(&env, &options).await;
+
+        // The override allows the click, the config still forbids typing
+        assert_eq!(result.stop_reason, StopReason::ToolCallLimit);
+        assert_eq!(
+            result.final_response,
+            "Tool call limit reached: keyboard_type may be called at most 0 times"
+        );
+        assert_eq!(fake.cursor_path().last(), Some(&(640, 360)));
+        assert_eq!(fake.typed(), "");
+    }
+
+    #[tokio::test]
+    async fn time_limit_stops_before_the_next_request() {
+        let server = notepad_server().await;
+        let fake = FakeBackend::new();
+        let options = RunOptions {
+            include_screen: false,
+            budget: Some(RunBudgetOverride {
+                max_duration_secs: Some(0),
+                ..Default::default()
+            }),
+            ..options()
+        };
+
+        let result = run_with(&fake_environment(&server, &fake), &options).await;
+
+        assert_eq!(result.stop_reason, StopReason::MaxDuration);
+        assert_eq!(
+            result.final_response,
+            "Time limit of 0s reached. Task may be incomplete."
+        );
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn a_cancelled_run_can_be_continued() {
+        // The model waits, then would click; the run is cancelled during the wait
+        let server = MockServer::start(vec![
+            MockResponse::tool_calls(&[
+                ("wait", json!({ "ms": 5000 })),
+                ("mouse_click", json!({ "x": 1, "y": 2 })),
+            ]),
+            MockResponse::json(200, serde_json::from_str(FINAL_RESPONSE).unwrap()),
+        ])
+        .await;
+        let fake = FakeBackend::new();
+        let env = fake_environment(&server, &fake);
+        let options = RunOptions {
+            include_screen: false,
+            ..options()
+        };
+        let events = EventSink::new(new_run_id(), None);
+        let mut session = Session::new("Open notepad");
+
+        let control = RunControl::default();
+        let token = control.token();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            token.cancel();
+        });
+        let mut messages = Vec::new();
+        let result = agent_loop(
+            &env,
+            "Open notepad",
+            &options,
+            None,
+            &events,
+            &control,
+            &mut messages,
+        )
+        .await;
+        assert_eq!(result.as_ref().unwrap().stop_reason, StopReason::Cancelled);
+        record_run(
+            &mut session,
+            messages,
+            "run-1",
+            "Open notepad",
+            String::new(),
+            &result,
+        );
+
+        // Every call of the stopped turn has a result
+        let results: Vec<_> = session
+            .messages
+            .iter()
+            .filter(|m| m.role == "tool")
+            .map(|m| (m.tool_call_id.clone().unwrap(), m.text()))
+            .collect();
+        assert_eq!(
+            results,
+            [
+                ("call_0".to_string(), "Error: Cancelled".to_string()),
+                (
+                    "call_1".to_string(),
+                    "Not executed: Cancelled by user".to_string()
+                ),
+            ]
+        );
+
+        // Continuing the session sends a history the provider accepts
+        let mut messages = std::mem::take(&mut session.messages);
+        let result = agent_loop(
+            &env,
+            "Try again",
+            &options,
+            None,
+            &events,
+            &RunControl::default(),
+            &mut messages,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.stop_reason, StopReason::Completed);
+
+        let request: serde_json::Value = serde_json::from_str(&server.requests()[1].body).unwrap();
+        let sent = request["messages"].as_array().unwrap();
+        let roles: Vec<_> = sent.iter().map(|m| m["role"].as_str().unwrap()).collect();
+        assert_eq!(
+            roles,
+            ["system", "user", "assistant", "tool", "tool", "user"]
+        );
+        assert_eq!(sent[4]["tool_call_id"], "call_1");
+        assert!(fake.actions().is_empty());
+    }
+}
@@ -0,0 +1,664 @@
+// Approval policy - decides whether a tool call may run
+//
+// Rules from the config are checked in order; the first match decides
+// between allow, ask and deny. "Ask" emits an `approval_requested` agent
+// event and waits for `respond_approval` (or the timeout, which rejects).
+
+use crate::commands::config::{ApprovalConfig, PolicyAction, PolicyRule};
+use crate::input::cancel::CancelToken;
+use crate::llm::runner::environment::Desktop;
+use crate::llm::runner::events::{AgentEvent, EventSink};
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+lazy_static::lazy_static! {
+    /// Approval requests waiting for the user, by request ID
+    static ref PENDING: Mutex<HashMap<String, oneshot::Sender<bool>>> = Mutex::new(HashMap::new());
+}
+
+static REQUEST_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// How a tool call was decided
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalDecision {
+    /// Allowed by the policy without asking
+    Allowed,
+    /// The user approved it
+    Approved,
+    /// The user rejected it
+    Rejected,
+    /// The user didn't answer in time
+    TimedOut,
+    /// Denied by the policy without asking
+    Denied,
+    /// Would have asked, but a dry run only plans the call
+    Planned,
+}
+
+impl ApprovalDecision {
+    /// Whether the tool may run
+    pub fn permits(self) -> bool {
+        matches!(self, Self::Allowed | Self::Approved | Self::Planned)
+    }
+}
+
+/// Decision recorded with the step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRecord {
+    pub decision: ApprovalDecision,
+    /// Description of the matching rule (None when the default action applied)
+    pub rule: Option<String>,
+    /// Title of the window the call targets, if it was looked up
+    pub target_window: Option<String>,
+}
+
+impl ApprovalRecord {
+    /// Tool error fed back to the model when the call was not permitted
+    pub fn refusal(&self) -> String {
+        let rule = self
+            .rule
+            .as_deref()
+            .map(|r| format!(" ({})", r))
+            .unwrap_or_default();
+        match self.decision {
+            ApprovalDecision::Denied => format!("Blocked by the approval policy{}", rule),
+            ApprovalDecision::TimedOut => {
+                format!("Not performed: the user did not approve in time{}", rule)
+            }
+            _ => format!("The user rejected this action{}", rule),
+        }
+    }
+}
+
+/// Rule with its patterns compiled
+struct CompiledRule {
+    action: PolicyAction,
+    tool: Option<String>,
+    keys: Option<Vec<String>>,
+    text: Option<Regex>,
+    window: Option<Regex>,
+    description: Option<String>,
+}
+
+impl CompiledRule {
+    fn compile(rule: &PolicyRule) -> Result<Self> {
+        let regex = |pattern: &Option<String>| -> Result<Option<Regex>> {
+            pattern
+                .as_deref()
+                .map(|p| {
+                    Regex::new(p)
+                        .map_err(|e| anyhow::anyhow!("Invalid rule pattern '{}': {}", p, e))
+                })
+                .transpose()
+        };
+
+        Ok(Self {
+            action: rule.action,
+            tool: rule.tool.clone(),
+            keys: rule
+                .keys
+                .as_deref()
+                .map(|combo| normalize_keys(combo.split('+'))),
+            text: regex(&rule.text_pattern)?,
+            window: regex(&rule.window_pattern)?,
+            description: rule.description.clone(),
+        })
+    }
+
+    fn matches(&self, tool: &str, params: &Value, window: Option<&str>) -> bool {
+        if self.tool.as_deref().is_some_and(|t| t != tool) {
+            return false;
+        }
+
+        // Any chord of a key sequence can match
+        if let Some(keys) = &self.keys {
+            let Ok(chords) = crate::input::keys::parse_keys(&params["keys"]) else {
+                return false;
+            };
+            if !chords
+                .iter()
+                .any(|chord| normalize_keys(chord.keys.iter().copied()) == *keys)
+            {
+                return false;
+            }
+        }
+
+        if let Some(text) = &self.text {
+            if !params["text"].as_str().is_some_and(|t| text.is_match(t)) {
+                return false;
+            }
+        }
+
+        if let Some(pattern) = &self.window {
+            if !window.is_some_and(|w| pattern.is_match(w)) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Lowercase, de-alias and sort key names so combos compare order-insensitively
+fn normalize_keys<'a>(keys: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut keys: Vec<String> = keys
+        .flat_map(|k| match crate::input::keys::presses(k) {
+            Some(names) => names.into_iter().map(String::from).collect(),
+            None => vec![k.trim().to_lowercase()],
+        })
+        .filter(|k| !k.is_empty())
+        .collect();
+    keys.sort();
+    keys.dedup();
+    keys
+}
+
+/// Title of the window a call would act on, as seen on the run's desktop
+fn target_window(desktop: &dyn Desktop, params: &Value) -> Option<String> {
+    let screen = desktop.screen()?;
+    match (params["x"].as_i64(), params["y"].as_i64()) {
+        (Some(x), Some(y)) => screen.window_title_at(x as i32, y as i32),
+        _ => screen.foreground_window_title(),
+    }
+}
+
+/// Approval policy of a run
+pub struct ApprovalPolicy {
+    enabled: bool,
+    default_action: PolicyAction,
+    timeout: Duration,
+    rules: Vec<CompiledRule>,
+    dry_run: bool,
+}
+
+impl ApprovalPolicy {
+    /// Compile the configured rules (fails on an invalid pattern)
+    pub fn from_config(config: &ApprovalConfig) -> Result<Self> {
+        Ok(Self {
+            enabled: config.enabled,
+            default_action: config.default_action,
+            timeout: Duration::from_secs(config.timeout_secs),
+            rules: config
+                .rules
+                .iter()
+                .map(CompiledRule::compile)
+                .collect::<Result<_>>()?,
+            dry_run: false,
+        })
+    }
+
+    /// In a dry run, calls that would ask are recorded as planned without asking
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Action for a call and the description of the rule that decided it
+    pub fn classify(
+        &self,
+        tool: &str,
+        params: &Value,
+        window: Option<&str>,
+    ) -> (PolicyAction, Option<String>) {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(tool, params, window))
+            .map(|rule| (rule.action, rule.description.clone()))
+            .unwrap_or((self.default_action, None))
+    }
+
+    /// Decide a tool call, asking the user when a rule says so
+    /// Returns None when the policy is disabled
+    #[allow(clippy::too_many_arguments)]
+    pub async fn review(
+        &self,
+        iteration: usize,
+        call_id: &str,
+        tool: &str,
+        params: &Value,
+        desktop: &dyn Desktop,
+        events: &EventSink,
+        cancel: &CancelToken,
+    ) -> Option<ApprovalRecord> {
+        if !self.enabled {
+            return None;
+        }
+
+        let needs_window = self.rules.iter().any(|rule| rule.window.is_some());
+        let target_window = if needs_window {
+            target_window(desktop, params)
+        } else {
+            None
+        };
+
+        let (action, rule) = self.classify(tool, params, target_window.as_deref());
+        let decision = match action {
+            PolicyAction::Allow => ApprovalDecision::Allowed,
+            PolicyAction::Deny => ApprovalDecision::Denied,
+            PolicyAction::Ask if self.dry_run => ApprovalDecision::Planned,
+            PolicyAction::Ask => {
+                let request_id = format!(
+                    "{}-approval-{}",
+                    events.run_id(),
+                    REQUEST_COUNTER.fetch_add(1, Ordering::Relaxed)
+                );
+                let request = AgentEvent::ApprovalRequested {
+                    iteration,
+                    request_id: request_id.clone(),
+                    call_id: call_id.to_string(),
+                    name: tool.to_string(),
+                    params: params.clone(),
+                    reason: rule.clone(),
+                    timeout_secs: self.timeout.as_secs(),
+                };
+                self.ask(request_id, request, events, cancel).await
+            }
+        };
+
+        Some(ApprovalRecord {
+            decision,
+            rule,
+            target_window,
+        })
+    }
+
+    /// Emit an approval request and wait for the answer, the timeout or cancellation
+    async fn ask(
+        &self,
+        request_id: String,
+        request: AgentEvent,
+        events: &EventSink,
+        cancel: &CancelToken,
+    ) -> ApprovalDecision {
+        let (sender, receiver) = oneshot::channel();
+        PENDING
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(request_id.clone(), sender);
+
+        events.emit(request);
+
+        let decision = tokio::select! {
+            answer = tokio::time::timeout(self.timeout, receiver) => match answer {
+                Ok(Ok(true)) => ApprovalDecision::Approved,
+                Ok(_) => ApprovalDecision::Rejected,
+                Err(_) => ApprovalDecision::TimedOut,
+            },
+            _ = cancel.cancelled() => ApprovalDecision::Rejected,
+        };
+
+        PENDING
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&request_id);
+        events.emit(AgentEvent::ApprovalResolved {
+            request_id,
+            decision,
+        });
+
+        decision
+    }
+}
+
+/// Deliver the user's answer to a pending approval request
+/// Returns false if the request is unknown (already answered or timed out)
+pub fn respond(request_id: &str, approved: bool) -> bool {
+    let sender = PENDING
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(request_id);
+
+    match sender {
+        Some(sender) => sender.send(approved).is_ok(),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::fake::{window, FakeBackend, FakeDesktop};
+    use serde_json::json;
+
+    fn config(timeout_secs: u64, rules: Vec<PolicyRule>) -> ApprovalConfig {
+        ApprovalConfig {
+            enabled: true,
+            default_action: PolicyAction::Allow,
+            timeout_secs,
+            rules,
+        }
+    }
+
+    fn rule(action: PolicyAction) -> PolicyRule {
+        PolicyRule {
+            action,
+            tool: None,
+            keys: None,
+            text_pattern: None,
+            window_pattern: None,
+            description: None,
+        }
+    }
+
+    fn desktop() -> FakeDesktop {
+        FakeDesktop {
+            backend: FakeBackend::new(),
+        }
+    }
+
+    /// Request ID of the run's pending approval, once it has been emitted
+    async fn pending_request(run_id: &str) -> String {
+        loop {
+            let found = PENDING
+                .lock()
+                .unwrap()
+                .keys()
+                .find(|id| id.starts_with(&format!("{}-approval-", run_id)))
+                .cloned();
+            if let Some(id) = found {
+                return id;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    }
+
+    #[test]
+    fn default_rules_match_key_combos_and_destructive_text() {
+        let policy = ApprovalPolicy::from_config(&ApprovalConfig::default()).unwrap();
+        let action = |tool: &str, params: Value| policy.classify(tool, &params, None).0;
+
+        assert_eq!(
+            action("keyboard_press", json!({ "keys": "R+Windows" })),
+            PolicyAction::Ask
+        );
+        assert_eq!(
+            action("keyboard_press", json!({ "keys": "control+alt+del" })),
+            PolicyAction::Deny
+        );
+        assert_eq!(
+            action("keyboard_press", json!({ "keys": "ctrl+c" })),
+            PolicyAction::Allow
+        );
+        assert_eq!(
+            action("keyboard_type", json!({ "text": "RM -rf /" })),
+            PolicyAction::Ask
+        );
+        assert_eq!(
+            action("keyboard_type", json!({ "text": "hello" })),
+            PolicyAction::Allow
+        );
+    }
+
+    #[test]
+    fn first_matching_rule_decides() {
+        let policy = ApprovalPolicy::from_config(&config(
+            60,
+            vec![
+                PolicyRule {
+                    tool: Some("mouse_click".to_string()),
+                    window_pattern: Some("(?i)terminal".to_string()),
+                    description: Some("Clicks in a terminal".to_string()),
+                    ..rule(PolicyAction::Deny)
+                },
+                PolicyRule {
+                    tool: Some("mouse_click".to_string()),
+                    ..rule(PolicyAction::Ask)
+                },
+            ],
+        ))
+        .unwrap();
+        let params = json!({ "x": 10, "y": 20 });
+
+        assert_eq!(
+            policy.classify("mouse_click", &params, Some("Windows Terminal")),
+            (PolicyAction::Deny, Some("Clicks in a terminal".to_string()))
+        );
+        assert_eq!(
+            policy.classify("mouse_click", &params, Some("Notepad")),
+            (PolicyAction::Ask, None)
+        );
+        assert_eq!(
+            policy.classify("mouse_move", &params, Some("Windows Terminal")),
+            (PolicyAction::Allow, None)
+        );
+    }
+
+    #[tokio::test]
+    async fn window_rules_look_at_the_runs_desktop() {
+        let policy = ApprovalPolicy::from_config(&config(
+            60,
+            vec![PolicyRule {
+                window_pattern: Some("(?i)terminal".to_string()),
+                ..rule(PolicyAction::Deny)
+            }],
+        ))
+        .unwrap();
+        let desktop = desktop();
+        desktop
+            .backend
+            .push_screen(String::new(), window("Windows Terminal", vec![]));
+        let events = EventSink::new("approval-test-window".to_string(), None);
+
+        let record = policy
+            .review(
+                0,
+                "call_1",
+                "mouse_click",
+                &json!({ "x": 10, "y": 20 }),
+                &desktop,
+                &events,
+                &CancelToken::new(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(record.decision, ApprovalDecision::Denied);
+        assert_eq!(record.target_window.as_deref(), Some("Windows Terminal"));
+    }
+
+    #[test]
+    fn invalid_pattern_is_rejected() {
+        let error = ApprovalPolicy::from_config(&config(
+            60,
+            vec![PolicyRule {
+                text_pattern: Some("(".to_string()),
+                ..rule(PolicyAction::Deny)
+            }],
+        ))
+        .err()
+        .unwrap();
+        assert!(error.to_string().starts_with("Invalid rule pattern '('"));
+    }
+
+    #[tokio::test]
+    async fn asked_call_waits_for_the_answer() {
+        let policy =
+            ApprovalPolicy::from_config(&config(60, vec![rule(PolicyAction::Ask)])).unwrap();
+        let events = EventSink::new("approval-test-answer".to_string(), None);
+        let cancel = CancelToken::new();
+
+        let answer = tokio::spawn(async {
+            let request_id = pending_request("approval-test-answer").await;
+            assert!(respond(&request_id, true));
+            assert!(!respond(&request_id, true));
+        });
+        let record = policy
+            .review(
+                0,
+                "call_1",
+                "mouse_click",
+                &json!({}),
+                &desktop(),
+                &events,
+                &cancel,
+            )
+            .await
+            .unwrap();
+        answer.await.unwrap();
+
+        assert_eq!(record.decision, ApprovalDecision::Approved);
+        assert!(record.decision.permits());
+    }
+
+    #[tokio::test]
+    async fn unanswered_prompt_times_out() {
+        let policy = ApprovalPolicy::from_config(&config(
+            0,
+            vec![PolicyRule {
+                description: Some("Always ask".to_string()),
+                ..rule(PolicyAction::Ask)
+            }],
+        ))
+        .unwrap();
+        let events = EventSink::new("approval-test-timeout".to_string(), None);
+
+        let record = policy
+            .review(
+                0,
+                "call_1",
+                "mouse_click",
+                &json!({}),
+                &desktop(),
+                &events,
+                &CancelToken::new(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(record.decision, ApprovalDecision::TimedOut);
+        assert!(!record.decision.permits());
+        assert_eq!(
+            record.refusal(),
+            "Not performed: the user did not approve in time (Always ask)"
+        );
+        assert!(!PENDING
+            .lock()
+            .unwrap()
+            .keys()
+            .any(|id| id.starts_with("approval-test-timeout-")));
+    }
+
+    #[tokio::test]
+    async fn dry_run_plans_instead_of_asking() {
+        let policy = ApprovalPolicy::from_config(&config(
+            60,
+            vec![
+                PolicyRule {
+                    tool: Some("mouse_click".to_string()),
+                    ..rule(PolicyAction::Deny)
+                },
+                rule(PolicyAction::Ask),
+            ],
+        ))
+        .unwrap()
+        .dry_run(true);
+        let events = EventSink::new("approval-test-dry-run".to_string(), None);
+        let desktop = desktop();
+        let cancel = CancelToken::new();
+
+        // Answered at once - nothing is left waiting for the user
+        let record = policy
+            .review(
+                0,
+                "call_1",
+                "keyboard_type",
+                &json!({ "text": "hello" }),
+                &desktop,
+                &events,
+                &cancel,
+            )
+            .await
+            .unwrap();
+        assert_eq!(record.decision, ApprovalDecision::Planned);
+        assert!(record.decision.permits());
+        assert!(!PENDING
+            .lock()
+            .unwrap()
+            .keys()
+            .any(|id| id.starts_with("approval-test-dry-run-")));
+
+        // Denials still show in the plan
+        let record = policy
+            .review(
+                0,
+                "call_2",
+                "mouse_click",
+                &json!({}),
+                &desktop,
+                &events,
+                &cancel,
+            )
+            .await
+            .unwrap();
+        assert_eq!(record.decision, ApprovalDecision::Denied);
+    }
+
+    #[tokio::test]
+    async fn cancelling_the_run_rejects_the_prompt() {
+        let policy =
+            ApprovalPolicy::from_config(&config(60, vec![rule(PolicyAction::Ask)])).unwrap();
+        let events = EventSink::new("approval-test-cancel".to_string(), None);
+        let cancel = CancelToken::new();
+        cancel.cancel();
+
+        let record = policy
+            .review(
+                0,
+                "call_1",
+                "mouse_click",
+                &json!({}),
+                &desktop(),
+                &events,
+                &cancel,
+            )
+            .await
+            .unwrap();
+        assert_eq!(record.decision, ApprovalDecision::Rejected);
+    }
+
+    #[test]
+    fn key_rules_match_any_chord_of_a_sequence() {
+        let policy = ApprovalPolicy::from_config(&ApprovalConfig::default()).unwrap();
+        let action = |keys: &str| {
+            policy
+                .classify("keyboard_press", &json!({ "keys": keys }), None)
+                .0
+        };
+
+        assert_eq!(action("ctrl+k r+win"), PolicyAction::Ask);
+        assert_eq!(action("alt+tab control+alt+del"), PolicyAction::Deny);
+        assert_eq!(action("ctrl+k ctrl+c"), PolicyAction::Allow);
+
+        // Older calls give the keys as an array of names
+        let keys = json!({ "keys": ["Win", "r"] });
+        assert_eq!(
+            policy.classify("keyboard_press", &keys, None).0,
+            PolicyAction::Ask
+        );
+
+        // A rule for the plus key matches however the call spells it
+        let zoom = PolicyRule {
+            keys: Some("ctrl+plus".to_string()),
+            ..rule(PolicyAction::Deny)
+        };
+        let policy = ApprovalPolicy::from_config(&config(60, vec![zoom])).unwrap();
+        for keys in ["ctrl++", "Ctrl+Shift+=", "ctrl+plus"] {
+            assert_eq!(
+                policy
+                    .classify("keyboard_press", &json!({ "keys": keys }), None)
+                    .0,
+                PolicyAction::Deny,
+                "{}",
+                keys
+            );
+        }
+    }
+}
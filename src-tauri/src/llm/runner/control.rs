@@ -0,0 +1,84 @@
+// Run control - cancel, pause and resume handles for agent runs
+
+use crate::input::cancel::CancelToken;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How often a paused run checks whether it may continue
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+lazy_static::lazy_static! {
+    /// Handles of the runs in progress, by run ID
+    static ref ACTIVE_RUNS: Mutex<HashMap<String, Arc<RunControl>>> = Mutex::new(HashMap::new());
+}
+
+/// Control handle of one agent run
+#[derive(Debug, Default)]
+pub struct RunControl {
+    cancel: CancelToken,
+    paused: AtomicBool,
+}
+
+impl RunControl {
+    /// Token passed to tools (cancelled together with the run)
+    pub fn token(&self) -> CancelToken {
+        self.cancel.clone()
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Hold the run at its next checkpoint
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Wait while the run is paused (returns early if it is cancelled)
+    pub async fn wait_while_paused(&self) {
+        while self.is_paused() && !self.is_cancelled() {
+            tokio::time::sleep(PAUSE_POLL_INTERVAL).await;
+        }
+    }
+}
+
+/// Register a run and return its handle
+pub fn register(run_id: &str) -> Arc<RunControl> {
+    let control = Arc::new(RunControl::default());
+    ACTIVE_RUNS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(run_id.to_string(), control.clone());
+    control
+}
+
+/// Remove a finished run
+pub fn unregister(run_id: &str) {
+    ACTIVE_RUNS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(run_id);
+}
+
+/// Handle of a run in progress
+pub fn get(run_id: &str) -> Option<Arc<RunControl>> {
+    ACTIVE_RUNS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(run_id)
+        .cloned()
+}
@@ -0,0 +1,118 @@
+// Run environment - the config, model and desktop an agent run talks to
+//
+// Live runs use the saved config, the provider selected in it and the real
+// screen and input. Replay tests swap in recorded responses and screens and
+// keep the run off the usage ledger and the session store.
+
+use crate::commands::config::AppConfig;
+use crate::config::storage::load_config;
+use crate::input::backend::InputBackend;
+use crate::llm::provider::{create_provider, LlmProvider, MeteredProvider};
+use crate::llm::runner::ToolExecutor;
+use crate::llm::types::{ToolContext, ToolResult};
+use crate::screen::backend::ScreenBackend;
+use crate::usage::UsageMeter;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Screen the agent looks at and tools act on
+#[async_trait]
+pub trait Desktop: Send + Sync {
+    /// Screenshot (data URL) and UI tree of the active window
+    /// None where screen capture isn't available
+    fn capture(&self) -> Result<Option<(String, String)>>;
+
+    /// Operating system tools run on (as in `std::env::consts::OS`)
+    fn os(&self) -> &str {
+        std::env::consts::OS
+    }
+
+    /// Input backend tools act through (None = the platform's native backend)
+    fn input(&self) -> Option<Arc<dyn InputBackend>> {
+        None
+    }
+
+    /// Screen windows are looked up on (None where window titles aren't available)
+    fn screen(&self) -> Option<Arc<dyn ScreenBackend>> {
+        None
+    }
+
+    /// Run a tool call
+    async fn execute(
+        &self,
+        executor: &ToolExecutor,
+        tool_name: &str,
+        params: Value,
+        ctx: &ToolContext,
+    ) -> Result<ToolResult>;
+}
+
+/// The real desktop (through the platform's screen backend)
+pub struct LiveDesktop;
+
+#[async_trait]
+impl Desktop for LiveDesktop {
+    fn capture(&self) -> Result<Option<(String, String)>> {
+        let Ok(screen) = crate::screen::backend::native() else {
+            return Ok(None);
+        };
+        let screenshot = screen.capture()?;
+        let ui_tree = screen
+            .window_tree(2)
+            .map(|t| serde_json::to_string_pretty(&t).unwrap_or_default())
+            .unwrap_or_default();
+        Ok(Some((screenshot, ui_tree)))
+    }
+
+    fn screen(&self) -> Option<Arc<dyn ScreenBackend>> {
+        crate::screen::backend::native().ok()
+    }
+
+    async fn execute(
+        &self,
+        executor: &ToolExecutor,
+        tool_name: &str,
+        params: Value,
+        ctx: &ToolContext,
+    ) -> Result<ToolResult> {
+        executor.execute(tool_name, params, ctx).await
+    }
+}
+
+/// Everything a run depends on outside its options
+pub struct RunEnvironment {
+    pub config: AppConfig,
+    /// Replaces the provider selected in `config`
+    pub provider: Option<Arc<dyn LlmProvider>>,
+    pub desktop: Arc<dyn Desktop>,
+    /// Record usage in the ledger and save the conversation to its session
+    pub persist: bool,
+}
+
+impl RunEnvironment {
+    /// Saved config, configured provider and the real desktop
+    pub fn live() -> Result<Self> {
+        Ok(Self {
+            config: load_config()?,
+            provider: None,
+            desktop: Arc::new(LiveDesktop),
+            persist: true,
+        })
+    }
+
+    /// Provider for the run with usage metering and budget checks
+    pub fn metered_provider(&self) -> (Arc<dyn LlmProvider>, Arc<UsageMeter>) {
+        let meter = Arc::new(if self.persist {
+            UsageMeter::new(&self.config)
+        } else {
+            UsageMeter::detached(&self.config)
+        });
+        let inner = self
+            .provider
+            .clone()
+            .unwrap_or_else(|| create_provider(&self.config.api));
+        (Arc::new(MeteredProvider::new(inner, meter.clone())), meter)
+    }
+}
@@ -0,0 +1,404 @@
+// Agent events - live progress of an agent run
+//
+// Every run gets an ID. Events are emitted globally as `agent-event` Tauri
+// events and kept per run, so a subscriber that attaches late (through
+// `subscribe_agent_run`) first receives everything it missed. Sinks created
+// `with_transcript` also write every event to the run's transcript file.
+
+use crate::commands::overlay::OverlayEvent;
+use crate::llm::runner::approval::ApprovalDecision;
+use crate::llm::runner::plan::Plan;
+use crate::llm::runner::stall::Intervention;
+use crate::llm::runner::AgentResult;
+use crate::llm::stream::{StreamEvent, STREAM_EVENT};
+use crate::transcripts::recorder::{self, Transcript, TranscriptRecord};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::ipc::Channel;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Tauri event name for agent progress
+pub const AGENT_EVENT: &str = "agent-event";
+
+/// Finished runs kept for late subscribers
+const MAX_FINISHED_RUNS: usize = 16;
+
+/// Progress event of an agent run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AgentEvent {
+    /// A new loop iteration (LLM call) begins
+    StepStarted { iteration: usize },
+    /// The model's reasoning for this iteration
+    Thought { iteration: usize, text: String },
+    /// A tool is about to run
+    ToolCall {
+        iteration: usize,
+        call_id: String,
+        name: String,
+        params: serde_json::Value,
+    },
+    /// A tool finished
+    ToolResult {
+        iteration: usize,
+        call_id: String,
+        name: String,
+        success: bool,
+        output: String,
+        /// Time spent on approval and execution
+        #[serde(default)]
+        duration_ms: u64,
+    },
+    /// A tool call needs the user's approval (answer with `respond_approval`)
+    ApprovalRequested {
+        iteration: usize,
+        request_id: String,
+        call_id: String,
+        name: String,
+        params: serde_json::Value,
+        /// Description of the rule that asked
+        reason: Option<String>,
+        timeout_secs: u64,
+    },
+    /// An approval request was answered, timed out or abandoned
+    ApprovalResolved {
+        request_id: String,
+        decision: ApprovalDecision,
+    },
+    /// The run is held at a checkpoint until resumed
+    Paused { iteration: usize },
+    /// The run continues after a pause
+    Resumed { iteration: usize },
+    /// A screenshot was captured and added to the conversation
+    ScreenshotCaptured {
+        iteration: usize,
+        /// File name in the transcript's screenshot directory (when recorded)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        screenshot: Option<String>,
+    },
+    /// The plan was created or changed (plan mode only)
+    PlanUpdated { iteration: usize, plan: Plan },
+    /// The run was found repeating itself without progress
+    StallDetected {
+        iteration: usize,
+        reason: String,
+        intervention: Intervention,
+    },
+    /// The run ended (successfully or by hitting a limit)
+    Finished { result: AgentResult },
+    /// The run failed with an error
+    Error { message: String },
+}
+
+/// Event with run ID and sequence number (payload of `agent-event`)
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentEventEnvelope {
+    pub run_id: String,
+    pub seq: u64,
+    #[serde(flatten)]
+    pub event: AgentEvent,
+}
+
+/// Events and subscribers of one run
+#[derive(Default)]
+struct RunState {
+    events: Vec<AgentEventEnvelope>,
+    subscribers: Vec<Channel<AgentEventEnvelope>>,
+    finished: bool,
+}
+
+lazy_static::lazy_static! {
+    static ref RUNS: Mutex<HashMap<String, RunState>> = Mutex::new(HashMap::new());
+    /// Finished run IDs, oldest first (for pruning)
+    static ref FINISHED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+}
+
+static RUN_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Create a unique run ID
+pub fn new_run_id() -> String {
+    format!(
+        "run-{}-{}",
+        chrono::Local::now().format("%Y%m%d%H%M%S"),
+        RUN_COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Emits the events of one run
+pub struct EventSink {
+    run_id: String,
+    app: Option<AppHandle>,
+    seq: AtomicU64,
+    transcript: Mutex<Option<Transcript>>,
+}
+
+impl EventSink {
+    /// Register a run and return its sink
+    pub fn new(run_id: String, app: Option<AppHandle>) -> Self {
+        RUNS.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(run_id.clone())
+            .or_default();
+
+        Self {
+            run_id,
+            app,
+            seq: AtomicU64::new(0),
+            transcript: Mutex::new(None),
+        }
+    }
+
+    /// Also write the run's transcript to disk
+    /// (a transcript that can't be opened is skipped - recording must not fail the run)
+    pub fn with_transcript(self) -> Self {
+        let transcript =
+            recorder::get_transcripts_dir().and_then(|dir| Transcript::create(&dir, &self.run_id));
+        match transcript {
+            Ok(transcript) => {
+                *self.transcript.lock().unwrap_or_else(|e| e.into_inner()) = Some(transcript);
+            }
+            Err(e) => log::warn!("Failed to open transcript for {}: {}", self.run_id, e),
+        }
+        self
+    }
+
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    /// Whether a transcript is being written
+    pub fn is_recording(&self) -> bool {
+        self.transcript
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_some()
+    }
+
+    /// Append a record to the transcript (no-op when not recording)
+    pub fn record(&self, record: TranscriptRecord) {
+        let mut transcript = self.transcript.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(file) = transcript.as_mut() {
+            if let Err(e) = file.record(record) {
+                log::warn!("Failed to write transcript for {}: {}", self.run_id, e);
+            }
+        }
+    }
+
+    /// Store a screenshot with the transcript and return its file name
+    pub fn store_screenshot(&self, data_url: &str) -> Option<String> {
+        let transcript = self.transcript.lock().unwrap_or_else(|e| e.into_inner());
+        transcript.as_ref()?.store_screenshot(data_url).ok()
+    }
+
+    /// Record an event and deliver it to the app and all subscribers
+    pub fn emit(&self, event: AgentEvent) {
+        let finished = matches!(
+            event,
+            AgentEvent::Finished { .. } | AgentEvent::Error { .. }
+        );
+        if self.is_recording() {
+            self.record(TranscriptRecord::Event {
+                event: event.clone(),
+            });
+        }
+        let envelope = AgentEventEnvelope {
+            run_id: self.run_id.clone(),
+            seq: self.seq.fetch_add(1, Ordering::Relaxed),
+            event,
+        };
+
+        if let Some(app) = &self.app {
+            let _ = app.emit(AGENT_EVENT, envelope.clone());
+        }
+
+        {
+            let mut runs = RUNS.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(run) = runs.get_mut(&self.run_id) {
+                run.subscribers
+                    .retain(|channel| channel.send(envelope.clone()).is_ok());
+                run.events.push(envelope);
+                if finished {
+                    run.finished = true;
+                    run.subscribers.clear();
+                }
+            }
+        }
+
+        if finished {
+            prune_finished(&self.run_id);
+        }
+    }
+
+    /// Show an effect (cursor move, click) on the overlay window
+    pub fn emit_overlay(&self, event: &OverlayEvent) {
+        if let Some(window) = self
+            .app
+            .as_ref()
+            .and_then(|app| app.get_webview_window("overlay"))
+        {
+            let _ = window.emit("overlay-event", event);
+        }
+    }
+
+    /// Forward a streamed token delta to the frontend
+    pub fn emit_stream(&self, event: StreamEvent) {
+        if let Some(app) = &self.app {
+            let _ = app.emit(STREAM_EVENT, event);
+        }
+    }
+}
+
+/// Attach a channel to a run, replaying the events emitted so far
+/// Returns false if the run is unknown
+pub fn subscribe(run_id: &str, channel: Channel<AgentEventEnvelope>) -> bool {
+    let mut runs = RUNS.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(run) = runs.get_mut(run_id) else {
+        return false;
+    };
+
+    for envelope in &run.events {
+        if channel.send(envelope.clone()).is_err() {
+            return true;
+        }
+    }
+    if !run.finished {
+        run.subscribers.push(channel);
+    }
+
+    true
+}
+
+/// Forget the oldest finished runs beyond MAX_FINISHED_RUNS
+fn prune_finished(run_id: &str) {
+    let mut finished = FINISHED.lock().unwrap_or_else(|e| e.into_inner());
+    finished.push(run_id.to_string());
+
+    if finished.len() > MAX_FINISHED_RUNS {
+        let excess = finished.len() - MAX_FINISHED_RUNS;
+        let expired: Vec<String> = finished.drain(..excess).collect();
+        let mut runs = RUNS.lock().unwrap_or_else(|e| e.into_inner());
+        for id in expired {
+            runs.remove(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use tauri::ipc::InvokeResponseBody;
+
+    /// Channel collecting the JSON of every envelope it receives
+    fn collector() -> (Channel<AgentEventEnvelope>, Arc<Mutex<Vec<Value>>>) {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let sink = received.clone();
+        let channel = Channel::new(move |body| {
+            if let InvokeResponseBody::Json(json) = body {
+                sink.lock()
+                    .unwrap()
+                    .push(serde_json::from_str(&json).unwrap());
+            }
+            Ok(())
+        });
+        (channel, received)
+    }
+
+    #[test]
+    fn events_are_flat_tagged_objects() {
+        let envelope = AgentEventEnvelope {
+            run_id: "run-1".to_string(),
+            seq: 3,
+            event: AgentEvent::ToolResult {
+                iteration: 2,
+                call_id: "call_0".to_string(),
+                name: "click".to_string(),
+                success: true,
+                output: "Clicked".to_string(),
+                duration_ms: 40,
+            },
+        };
+        assert_eq!(
+            serde_json::to_value(&envelope).unwrap(),
+            json!({
+                "run_id": "run-1",
+                "seq": 3,
+                "type": "tool_result",
+                "iteration": 2,
+                "call_id": "call_0",
+                "name": "click",
+                "success": true,
+                "output": "Clicked",
+                "duration_ms": 40
+            })
+        );
+
+        // Unrecorded screenshots carry no file name
+        let event = AgentEvent::ScreenshotCaptured {
+            iteration: 0,
+            screenshot: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({ "type": "screenshot_captured", "iteration": 0 })
+        );
+
+        // Events from older transcripts lack the newer fields
+        let event: AgentEvent = serde_json::from_value(json!({
+            "type": "tool_result",
+            "iteration": 0,
+            "call_id": "call_0",
+            "name": "click",
+            "success": false,
+            "output": "Failed"
+        }))
+        .unwrap();
+        assert!(matches!(
+            event,
+            AgentEvent::ToolResult { duration_ms: 0, .. }
+        ));
+    }
+
+    #[test]
+    fn subscribers_only_receive_their_run() {
+        let first = EventSink::new(new_run_id(), None);
+        let second = EventSink::new(new_run_id(), None);
+        first.emit(AgentEvent::StepStarted { iteration: 0 });
+        second.emit(AgentEvent::StepStarted { iteration: 0 });
+
+        // A late subscriber first gets what it missed
+        let (channel, received) = collector();
+        assert!(subscribe(first.run_id(), channel));
+        first.emit(AgentEvent::Paused { iteration: 0 });
+        second.emit(AgentEvent::Paused { iteration: 0 });
+        first.emit(AgentEvent::Error {
+            message: "Stopped".to_string(),
+        });
+        second.emit(AgentEvent::Resumed { iteration: 0 });
+
+        let received = received.lock().unwrap();
+        let events: Vec<_> = received
+            .iter()
+            .map(|e| {
+                (
+                    e["run_id"].as_str().unwrap(),
+                    e["seq"].as_u64().unwrap(),
+                    e["type"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            events,
+            [
+                (first.run_id(), 0, "step_started"),
+                (first.run_id(), 1, "paused"),
+                (first.run_id(), 2, "error"),
+            ]
+        );
+
+        assert!(!subscribe("run-unknown", collector().0));
+    }
+}
@@ -0,0 +1,424 @@
+// History manager - keeps the agent conversation inside the context window
+//
+// - Only the last N screenshots stay inline; older ones become a placeholder
+// - Message sizes are estimated (characters / 4, fixed cost per image)
+// - Near the token budget, older steps are folded into an LLM-written summary
+//   (or simply dropped when the summary request fails)
+
+use crate::commands::config::ContextConfig;
+use crate::llm::error::LlmError;
+use crate::llm::prompts::HISTORY_SUMMARY_PROMPT;
+use crate::llm::provider::LlmProvider;
+use crate::llm::types::{ContentPart, Message, MessageContent};
+use anyhow::Result;
+
+/// Rough characters per token for English/JSON text
+const CHARS_PER_TOKEN: usize = 4;
+
+/// High-detail screenshot cost (1080p scales to 768x1365 = 6 tiles)
+const IMAGE_TOKENS: usize = 85 + 170 * 6;
+
+/// Role/formatting overhead per message
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+
+/// Replaces screenshots that fell out of the window
+const SCREENSHOT_PLACEHOLDER: &str = "[Earlier screenshot removed to save context]";
+
+/// Tool results longer than this are cut in the summary transcript
+const MAX_TRANSCRIPT_RESULT_CHARS: usize = 500;
+
+/// Messages at the start that are never compacted (system prompt, user request)
+const PINNED_MESSAGES: usize = 2;
+
+/// Estimate the tokens a message will use in a request
+pub fn estimate_message_tokens(message: &Message) -> usize {
+    let mut chars = 0;
+    let mut images = 0;
+
+    match &message.content {
+        Some(MessageContent::Text(text)) => chars += text.len(),
+        Some(MessageContent::Parts(parts)) => {
+            for part in parts {
+                match part {
+                    ContentPart::Text { text } => chars += text.len(),
+                    ContentPart::ImageUrl { .. } => images += 1,
+                }
+            }
+        }
+        None => {}
+    }
+
+    for call in message.tool_calls.iter().flatten() {
+        chars += call.function.name.len() + call.function.arguments.len();
+    }
+
+    MESSAGE_OVERHEAD_TOKENS + chars.div_ceil(CHARS_PER_TOKEN) + images * IMAGE_TOKENS
+}
+
+/// Estimate the tokens of a whole history
+pub fn estimate_tokens(messages: &[Message]) -> usize {
+    messages.iter().map(estimate_message_tokens).sum()
+}
+
+/// Applies the context limits to the agent's message history
+pub struct HistoryManager {
+    config: ContextConfig,
+}
+
+impl HistoryManager {
+    pub fn new(config: ContextConfig) -> Self {
+        Self { config }
+    }
+
+    /// Replace all but the last `max_screenshots` images with a placeholder
+    /// Returns how many images were removed
+    pub fn prune_screenshots(&self, messages: &mut [Message]) -> usize {
+        let mut kept = 0;
+        let mut removed = 0;
+
+        for message in messages.iter_mut().rev() {
+            let Some(MessageContent::Parts(parts)) = &mut message.content else {
+                continue;
+            };
+
+            for part in parts.iter_mut().rev() {
+                if !matches!(part, ContentPart::ImageUrl { .. }) {
+                    continue;
+                }
+                if kept < self.config.max_screenshots {
+                    kept += 1;
+                } else {
+                    *part = ContentPart::Text {
+                        text: SCREENSHOT_PLACEHOLDER.to_string(),
+                    };
+                    removed += 1;
+                }
+            }
+        }
+
+        removed
+    }
+
+    /// Whether the history is close enough to the budget to compact
+    pub fn needs_compaction(&self, messages: &[Message]) -> bool {
+        let threshold = self.config.token_budget as f32 * self.config.compact_threshold;
+        estimate_tokens(messages) as f32 >= threshold
+    }
+
+    /// Fold the messages between the pinned head and the recent tail into a summary
+    /// If summarizing fails they are dropped instead; only a budget refusal is returned
+    /// Returns false when there is nothing old enough to compact
+    pub async fn compact(
+        &self,
+        messages: &mut Vec<Message>,
+        provider: &dyn LlmProvider,
+    ) -> Result<bool> {
+        let Some(end) = self.compaction_end(messages) else {
+            return Ok(false);
+        };
+
+        let replacement = match summarize(&messages[PINNED_MESSAGES..end], provider).await {
+            Ok(summary) => format!(
+                "Summary of earlier steps (older messages were compacted):\n{}",
+                summary
+            ),
+            Err(e)
+                if matches!(
+                    e.downcast_ref::<LlmError>(),
+                    Some(LlmError::BudgetExceeded(_))
+                ) =>
+            {
+                return Err(e);
+            }
+            Err(e) => {
+                log::warn!("History summary failed, dropping older steps: {}", e);
+                format!(
+                    "{} earlier messages were removed to save context (they could not be summarized).",
+                    end - PINNED_MESSAGES
+                )
+            }
+        };
+        messages.splice(PINNED_MESSAGES..end, [Message::user(replacement)]);
+
+        Ok(true)
+    }
+
+    /// Prune screenshots, then compact if the history is near the budget
+    pub async fn prepare(
+        &self,
+        messages: &mut Vec<Message>,
+        provider: &dyn LlmProvider,
+    ) -> Result<()> {
+        self.prune_screenshots(messages);
+
+        if self.needs_compaction(messages) {
+            self.compact(messages, provider).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Index where the kept tail starts, or None if nothing can be compacted
+    fn compaction_end(&self, messages: &[Message]) -> Option<usize> {
+        let mut end = messages
+            .len()
+            .saturating_sub(self.config.keep_recent_messages);
+
+        // Tool results must stay with the assistant turn that requested them
+        while end > PINNED_MESSAGES && end < messages.len() && messages[end].role == "tool" {
+            end -= 1;
+        }
+
+        // Compacting a single message (e.g. a previous summary) gains nothing
+        (end > PINNED_MESSAGES + 1).then_some(end)
+    }
+}
+
+/// Ask the model for a summary of the given messages
+async fn summarize(messages: &[Message], provider: &dyn LlmProvider) -> Result<String> {
+    let response = provider
+        .chat(
+            vec![
+                Message::system(HISTORY_SUMMARY_PROMPT),
+                Message::user(render_transcript(messages)),
+            ],
+            None,
+        )
+        .await?;
+
+    response
+        .choices
+        .first()
+        .and_then(|c| c.message.content.as_deref())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| anyhow::anyhow!("Empty history summary"))
+}
+
+/// Render messages as plain text for the summarizer (images omitted)
+fn render_transcript(messages: &[Message]) -> String {
+    let mut lines = Vec::new();
+
+    for message in messages {
+        let text = message.text();
+        match message.role.as_str() {
+            "assistant" => {
+                if !text.is_empty() {
+                    lines.push(format!("Assistant: {}", text));
+                }
+                for call in message.tool_calls.iter().flatten() {
+                    lines.push(format!(
+                        "Action: {}({})",
+                        call.function.name, call.function.arguments
+                    ));
+                }
+            }
+            "tool" => lines.push(format!(
+                "Result ({}): {}",
+                message.name.as_deref().unwrap_or("tool"),
+                truncate(&text, MAX_TRANSCRIPT_RESULT_CHARS)
+            )),
+            _ => lines.push(format!(
+                "User: {}",
+                truncate(&text, MAX_TRANSCRIPT_RESULT_CHARS)
+            )),
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// Cut text to at most `max` characters
+fn truncate(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((index, _)) => format!("{}...", &text[..index]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::client::{ChatResponse, Choice, ResponseMessage};
+    use crate::llm::types::{FunctionCall, ImageUrl, ToolCall, ToolDef};
+    use async_trait::async_trait;
+
+    /// Provider answering every summary request with `answer()`
+    struct Summarizer(fn() -> Result<String>);
+
+    #[async_trait]
+    impl LlmProvider for Summarizer {
+        async fn chat(
+            &self,
+            _messages: Vec<Message>,
+            _tools: Option<Vec<ToolDef>>,
+        ) -> Result<ChatResponse> {
+            Ok(ChatResponse {
+                choices: vec![Choice {
+                    message: ResponseMessage {
+                        role: "assistant".to_string(),
+                        content: Some((self.0)()?),
+                        tool_calls: None,
+                    },
+                    finish_reason: None,
+                }],
+                usage: None,
+            })
+        }
+    }
+
+    fn manager(max_screenshots: usize, keep_recent_messages: usize) -> HistoryManager {
+        HistoryManager::new(ContextConfig {
+            max_screenshots,
+            keep_recent_messages,
+            ..Default::default()
+        })
+    }
+
+    fn screen(label: &str) -> Message {
+        Message::user_parts(vec![
+            ContentPart::Text {
+                text: label.to_string(),
+            },
+            ContentPart::ImageUrl {
+                image_url: ImageUrl {
+                    url: format!("data:image/png;base64,{}", label),
+                    detail: None,
+                },
+            },
+        ])
+    }
+
+    fn call(id: &str) -> ToolCall {
+        ToolCall {
+            id: id.to_string(),
+            call_type: "function".to_string(),
+            function: FunctionCall {
+                name: "mouse_click".to_string(),
+                arguments: "{}".to_string(),
+            },
+        }
+    }
+
+    /// Assistant turn calling `ids`, followed by their results
+    fn turn(ids: &[&str]) -> Vec<Message> {
+        let calls: Vec<ToolCall> = ids.iter().map(|id| call(id)).collect();
+        let mut messages = vec![Message::assistant_tool_calls(None, calls.clone())];
+        messages.extend(calls.iter().map(|call| Message::tool(call, "Clicked")));
+        messages
+    }
+
+    /// System prompt, request, then two turns of two calls each
+    fn conversation() -> Vec<Message> {
+        let mut messages = vec![Message::system("system"), Message::user("Open notepad")];
+        messages.extend(turn(&["call_0", "call_1"]));
+        messages.extend(turn(&["call_2", "call_3"]));
+        messages
+    }
+
+    fn images(messages: &[Message]) -> Vec<String> {
+        messages
+            .iter()
+            .filter_map(|m| match &m.content {
+                Some(MessageContent::Parts(parts)) => Some(parts),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|part| match part {
+                ContentPart::ImageUrl { image_url } => Some(image_url.url.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn only_the_latest_screenshots_stay_inline() {
+        let mut messages = vec![screen("a"), screen("b"), screen("c")];
+
+        assert_eq!(manager(2, 8).prune_screenshots(&mut messages), 1);
+        assert_eq!(
+            images(&messages),
+            ["data:image/png;base64,b", "data:image/png;base64,c"]
+        );
+        assert_eq!(messages[0].text(), format!("a\n{}", SCREENSHOT_PLACEHOLDER));
+
+        // Pruning again removes nothing more
+        assert_eq!(manager(2, 8).prune_screenshots(&mut messages), 0);
+    }
+
+    #[tokio::test]
+    async fn compaction_keeps_tool_results_with_their_call() {
+        let mut messages = conversation();
+
+        // The kept tail would start at call_3's result - it moves back to the turn
+        let compacted = manager(2, 2)
+            .compact(
+                &mut messages,
+                &Summarizer(|| Ok("Clicked twice.".to_string())),
+            )
+            .await
+            .unwrap();
+
+        assert!(compacted);
+        let roles: Vec<&str> = messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(
+            roles,
+            ["system", "user", "user", "assistant", "tool", "tool"]
+        );
+        assert!(messages[2].text().ends_with("\nClicked twice."));
+        assert_eq!(messages[3].tool_calls.as_ref().unwrap().len(), 2);
+        assert_eq!(messages[4].tool_call_id.as_deref(), Some("call_2"));
+        assert_eq!(messages[5].tool_call_id.as_deref(), Some("call_3"));
+    }
+
+    #[tokio::test]
+    async fn failed_summary_drops_the_older_steps() {
+        let mut messages = conversation();
+
+        let compacted = manager(2, 3)
+            .compact(&mut messages, &Summarizer(|| Err(LlmError::Timeout.into())))
+            .await
+            .unwrap();
+
+        assert!(compacted);
+        assert_eq!(messages.len(), 6);
+        assert_eq!(
+            messages[2].text(),
+            "3 earlier messages were removed to save context (they could not be summarized)."
+        );
+        assert_eq!(messages[3].role, "assistant");
+    }
+
+    #[tokio::test]
+    async fn budget_refusal_is_returned() {
+        let mut messages = conversation();
+
+        let error = manager(2, 3)
+            .compact(
+                &mut messages,
+                &Summarizer(|| Err(LlmError::BudgetExceeded("Task limit".to_string()).into())),
+            )
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<LlmError>(),
+            Some(LlmError::BudgetExceeded(_))
+        ));
+        assert_eq!(messages.len(), 8);
+    }
+
+    #[tokio::test]
+    async fn nothing_to_compact_in_a_short_history() {
+        let mut messages = conversation();
+
+        let compacted = manager(2, 8)
+            .compact(&mut messages, &Summarizer(|| unreachable!()))
+            .await
+            .unwrap();
+
+        assert!(!compacted);
+        assert_eq!(messages.len(), 8);
+    }
+}
@@ -2,6 +2,15 @@
 
 mod executor;
 mod agent_loop;
+pub mod approval;
+pub mod control;
+pub mod environment;
+pub mod events;
+pub mod history;
+pub mod plan;
+pub mod stall;
+pub mod text_protocol;
+pub mod verify;
 
 pub use executor::*;
 pub use agent_loop::*;
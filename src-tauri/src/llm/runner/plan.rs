@@ -0,0 +1,198 @@
+// Task plan - numbered steps produced by the planner sub-agent
+//
+// In plan mode the runner asks the planner for a plan before the first
+// action, and the main agent reports progress through `update_plan` (or asks
+// for a new plan with `replan`). The plan is shared with the tools through
+// ToolContext and returned with the result.
+
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+/// Plan shared between the runner and the plan tools
+pub type SharedPlan = Arc<Mutex<Plan>>;
+
+/// Progress of a plan step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanStepStatus {
+    Pending,
+    Done,
+    Failed,
+    /// The step was reworded and still has to be done
+    Revised,
+}
+
+/// One numbered step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanStep {
+    /// 1-based step number
+    pub number: usize,
+    pub description: String,
+    pub status: PlanStepStatus,
+    /// What happened (set by the agent when updating the step)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// Numbered plan with the status of each step
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Plan {
+    /// The user's request the plan is for
+    pub task: String,
+    pub steps: Vec<PlanStep>,
+    /// How often the plan was replaced by `replan` (0 for the first plan)
+    pub revision: u32,
+}
+
+impl Plan {
+    /// Plan of pending steps
+    pub fn new(task: &str, descriptions: Vec<String>) -> Self {
+        let mut plan = Self {
+            task: task.to_string(),
+            ..Self::default()
+        };
+        plan.push_pending(descriptions);
+        plan
+    }
+
+    /// Parse the planner's numbered list (`1. ...` or `1) ...` lines)
+    pub fn parse_steps(text: &str) -> Vec<String> {
+        text.lines()
+            .filter_map(|line| {
+                let line = line.trim();
+                let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+                if digits == 0 {
+                    return None;
+                }
+                let rest = line[digits..].strip_prefix(['.', ')'])?.trim();
+                (!rest.is_empty()).then(|| rest.to_string())
+            })
+            .collect()
+    }
+
+    /// Keep the finished steps and replace the rest with new ones
+    pub fn revise(&mut self, descriptions: Vec<String>) {
+        self.steps
+            .retain(|step| step.status == PlanStepStatus::Done);
+        self.push_pending(descriptions);
+        self.revision += 1;
+    }
+
+    fn push_pending(&mut self, descriptions: Vec<String>) {
+        for description in descriptions {
+            self.steps.push(PlanStep {
+                number: self.steps.len() + 1,
+                description,
+                status: PlanStepStatus::Pending,
+                note: None,
+            });
+        }
+    }
+
+    /// Set the status of a step (a new description is required for `revised`)
+    pub fn update(
+        &mut self,
+        number: usize,
+        status: PlanStepStatus,
+        note: Option<String>,
+        description: Option<String>,
+    ) -> anyhow::Result<()> {
+        let step = self
+            .steps
+            .iter_mut()
+            .find(|step| step.number == number)
+            .ok_or_else(|| anyhow::anyhow!("The plan has no step {}", number))?;
+
+        if status == PlanStepStatus::Revised {
+            step.description = description
+                .filter(|d| !d.trim().is_empty())
+                .ok_or_else(|| anyhow::anyhow!("A revised step needs a new description"))?;
+        }
+        step.status = status;
+        step.note = note.filter(|n| !n.trim().is_empty());
+        Ok(())
+    }
+
+    /// Whether every step is done
+    pub fn is_complete(&self) -> bool {
+        self.steps
+            .iter()
+            .all(|step| step.status == PlanStepStatus::Done)
+    }
+
+    /// Plan as shown to the model
+    pub fn render(&self) -> String {
+        self.steps
+            .iter()
+            .map(|step| {
+                let status = match step.status {
+                    PlanStepStatus::Pending => "pending",
+                    PlanStepStatus::Done => "done",
+                    PlanStepStatus::Failed => "failed",
+                    PlanStepStatus::Revised => "revised",
+                };
+                let note = step
+                    .note
+                    .as_deref()
+                    .map(|n| format!(" - {}", n))
+                    .unwrap_or_default();
+                format!("{}. [{}] {}{}", step.number, status, step.description, note)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_numbered_steps() {
+        let text = "Here is the plan:\n1. Open Notepad\n2) Type hello\n\n  3. Save the file\nDone.";
+        assert_eq!(
+            Plan::parse_steps(text),
+            ["Open Notepad", "Type hello", "Save the file"]
+        );
+        assert!(Plan::parse_steps("No steps here").is_empty());
+    }
+
+    #[test]
+    fn revise_keeps_finished_steps() {
+        let mut plan = Plan::new(
+            "Type hello",
+            vec!["Open Notepad".into(), "Type hello".into()],
+        );
+        plan.update(1, PlanStepStatus::Done, None, None).unwrap();
+        plan.update(2, PlanStepStatus::Failed, Some("no editor".into()), None)
+            .unwrap();
+
+        plan.revise(vec!["Click the editor".into(), "Type hello".into()]);
+
+        assert_eq!(plan.revision, 1);
+        assert_eq!(
+            plan.render(),
+            "1. [done] Open Notepad\n2. [pending] Click the editor\n3. [pending] Type hello"
+        );
+    }
+
+    #[test]
+    fn update_checks_step_and_description() {
+        let mut plan = Plan::new("Open Notepad", vec!["Open Notepad".into()]);
+        assert!(plan.update(2, PlanStepStatus::Done, None, None).is_err());
+        assert!(plan.update(1, PlanStepStatus::Revised, None, None).is_err());
+
+        plan.update(
+            1,
+            PlanStepStatus::Revised,
+            None,
+            Some("Open Notepad from Start".into()),
+        )
+        .unwrap();
+        assert_eq!(plan.steps[0].description, "Open Notepad from Start");
+        assert!(!plan.is_complete());
+
+        plan.update(1, PlanStepStatus::Done, None, None).unwrap();
+        assert!(plan.is_complete());
+    }
+}
@@ -0,0 +1,289 @@
+// Stall detection - notices runs that repeat themselves without progress
+//
+// The detector watches a run's tool calls and screens for three patterns: the
+// same call over and over, two calls alternating (A-B-A-B) and input actions
+// that leave the screen unchanged. Each detection escalates: corrective notes
+// first, a re-plan in plan mode, then the run is aborted.
+
+use crate::commands::config::StallConfig;
+use crate::llm::tools::is_input_tool;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// A pattern of no progress
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stall {
+    /// The same call with the same arguments, `times` in a row
+    Repeated { tool: String, times: usize },
+    /// Two calls alternating for `cycles` A-B cycles
+    Oscillating {
+        first: String,
+        second: String,
+        cycles: usize,
+    },
+    /// Input actions since the screen last changed
+    ScreenUnchanged { actions: usize },
+}
+
+impl fmt::Display for Stall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Repeated { tool, times } => write!(
+                f,
+                "{} was called {} times in a row with the same arguments",
+                tool, times
+            ),
+            Self::Oscillating {
+                first,
+                second,
+                cycles,
+            } => write!(
+                f,
+                "the run alternated between {} and {} {} times",
+                first, second, cycles
+            ),
+            Self::ScreenUnchanged { actions } => write!(
+                f,
+                "the screen did not change over the last {} input actions",
+                actions
+            ),
+        }
+    }
+}
+
+/// What the runner does about a stall
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Intervention {
+    /// Add a corrective note to the conversation
+    Note,
+    /// Ask the planner for new remaining steps
+    Replan,
+    /// Stop the run
+    Abort,
+}
+
+/// One tool call as compared by the detector
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CallSignature {
+    tool: String,
+    arguments: String,
+}
+
+/// Watches one run for stalls
+pub struct StallDetector {
+    config: StallConfig,
+    /// Recent tool calls, oldest first
+    calls: Vec<CallSignature>,
+    /// Fingerprint of the last captured screen
+    last_screen: Option<u64>,
+    /// Input actions since the last capture
+    actions_since_capture: usize,
+    /// Input actions since the screen last changed
+    unchanged_actions: usize,
+    interventions: u32,
+}
+
+impl StallDetector {
+    pub fn new(config: &StallConfig) -> Self {
+        Self {
+            config: config.clone(),
+            calls: Vec::new(),
+            last_screen: None,
+            actions_since_capture: 0,
+            unchanged_actions: 0,
+            interventions: 0,
+        }
+    }
+
+    /// Record a tool call
+    pub fn record_call(&mut self, tool: &str, params: &Value) {
+        self.calls.push(CallSignature {
+            tool: tool.to_string(),
+            arguments: params.to_string(),
+        });
+        let keep = self
+            .config
+            .repeat_limit
+            .max(self.config.oscillation_cycles * 2);
+        if self.calls.len() > keep {
+            self.calls.drain(..self.calls.len() - keep);
+        }
+
+        if is_input_tool(tool) {
+            self.actions_since_capture += 1;
+        }
+    }
+
+    /// Record a captured screen (screenshot data URL)
+    pub fn record_screen(&mut self, screenshot: &str) {
+        let mut hasher = DefaultHasher::new();
+        screenshot.hash(&mut hasher);
+        let fingerprint = hasher.finish();
+
+        if self.last_screen == Some(fingerprint) {
+            self.unchanged_actions += self.actions_since_capture;
+        } else {
+            self.unchanged_actions = 0;
+        }
+        self.last_screen = Some(fingerprint);
+        self.actions_since_capture = 0;
+    }
+
+    /// The stall the recent calls and screens show, if any
+    pub fn check(&self) -> Option<Stall> {
+        if !self.config.enabled {
+            return None;
+        }
+
+        let limit = self.config.repeat_limit;
+        if limit >= 2 && self.calls.len() >= limit {
+            let recent = &self.calls[self.calls.len() - limit..];
+            if recent.iter().all(|call| *call == recent[0]) {
+                return Some(Stall::Repeated {
+                    tool: recent[0].tool.clone(),
+                    times: limit,
+                });
+            }
+        }
+
+        let span = self.config.oscillation_cycles * 2;
+        if span >= 4 && self.calls.len() >= span {
+            let recent = &self.calls[self.calls.len() - span..];
+            let (first, second) = (&recent[0], &recent[1]);
+            let alternating = first != second
+                && recent
+                    .iter()
+                    .enumerate()
+                    .all(|(i, call)| call == if i % 2 == 0 { first } else { second });
+            if alternating {
+                return Some(Stall::Oscillating {
+                    first: first.tool.clone(),
+                    second: second.tool.clone(),
+                    cycles: self.config.oscillation_cycles,
+                });
+            }
+        }
+
+        let limit = self.config.unchanged_limit;
+        if limit > 0 && self.unchanged_actions >= limit {
+            return Some(Stall::ScreenUnchanged {
+                actions: self.unchanged_actions,
+            });
+        }
+
+        None
+    }
+
+    /// Choose the response to a detected stall and start watching afresh
+    /// `can_replan` is set in plan mode
+    pub fn intervene(&mut self, can_replan: bool) -> Intervention {
+        self.interventions += 1;
+        self.calls.clear();
+        self.unchanged_actions = 0;
+        self.actions_since_capture = 0;
+
+        let max = self.config.max_interventions;
+        if self.interventions > max {
+            Intervention::Abort
+        } else if self.interventions == max && can_replan {
+            Intervention::Replan
+        } else {
+            Intervention::Note
+        }
+    }
+
+    /// Corrections made so far
+    pub fn interventions(&self) -> u32 {
+        self.interventions
+    }
+}
+
+/// Note added to the conversation after a stall
+pub fn corrective_note(stall: &Stall) -> String {
+    format!(
+        "System note: {}. Repeating the same actions will not help. Check the current screen \
+         with get_screen_update and try a different approach (other coordinates, a keyboard \
+         shortcut or another element), or answer the user if the task cannot be done.",
+        stall
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn detector() -> StallDetector {
+        StallDetector::new(&StallConfig::default())
+    }
+
+    #[test]
+    fn detects_repeated_calls() {
+        let mut stall = detector();
+        stall.record_call("mouse_click", &json!({"x": 10, "y": 20}));
+        stall.record_call("mouse_click", &json!({"x": 10, "y": 20}));
+        assert_eq!(stall.check(), None);
+
+        stall.record_call("mouse_click", &json!({"x": 10, "y": 20}));
+        assert_eq!(
+            stall.check(),
+            Some(Stall::Repeated {
+                tool: "mouse_click".to_string(),
+                times: 3
+            })
+        );
+    }
+
+    #[test]
+    fn detects_oscillation() {
+        let mut stall = detector();
+        for _ in 0..2 {
+            stall.record_call("keyboard_press", &json!({"key": "tab"}));
+            stall.record_call("mouse_click", &json!({"x": 1, "y": 1}));
+        }
+        assert!(matches!(
+            stall.check(),
+            Some(Stall::Oscillating { cycles: 2, .. })
+        ));
+
+        // Different arguments break the pattern
+        let mut stall = detector();
+        stall.record_call("mouse_click", &json!({"x": 1, "y": 1}));
+        stall.record_call("mouse_click", &json!({"x": 2, "y": 2}));
+        stall.record_call("mouse_click", &json!({"x": 3, "y": 3}));
+        stall.record_call("mouse_click", &json!({"x": 2, "y": 2}));
+        assert_eq!(stall.check(), None);
+    }
+
+    #[test]
+    fn detects_unchanged_screen() {
+        let mut stall = detector();
+        stall.record_screen("data:image/png;base64,AAAA");
+        for x in 0..4 {
+            stall.record_call("mouse_click", &json!({"x": x, "y": 0}));
+            stall.record_call("get_screen_update", &json!({}));
+            stall.record_screen("data:image/png;base64,AAAA");
+        }
+        assert_eq!(stall.check(), Some(Stall::ScreenUnchanged { actions: 4 }));
+
+        stall.record_screen("data:image/png;base64,BBBB");
+        assert_eq!(stall.check(), None);
+    }
+
+    #[test]
+    fn escalates_to_abort() {
+        let mut stall = detector();
+        assert_eq!(stall.intervene(true), Intervention::Note);
+        assert_eq!(stall.intervene(true), Intervention::Replan);
+        assert_eq!(stall.intervene(true), Intervention::Abort);
+
+        let mut stall = detector();
+        assert_eq!(stall.intervene(false), Intervention::Note);
+        assert_eq!(stall.intervene(false), Intervention::Note);
+        assert_eq!(stall.intervene(false), Intervention::Abort);
+    }
+}
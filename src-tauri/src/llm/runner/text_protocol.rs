@@ -0,0 +1,397 @@
+// Text protocol - tool calling for models without native `tool_calls`
+//
+// The model is asked to answer with a JSON object
+//   {"thought": "...", "action": "tool_name", "params": {...}}
+// (the same shape MAIN_AGENT_PROMPT describes). Hermes-style
+//   <tool_call>{"name": "tool_name", "arguments": {...}}</tool_call>
+// blocks are accepted as well. Parsed calls are validated against the
+// ToolDef schemas and converted into regular ToolCalls.
+
+use crate::llm::tools::validate_arguments;
+use crate::llm::types::{FunctionCall, Message, ToolCall, ToolDef};
+use serde_json::{json, Value};
+
+/// Actions that end the turn without calling a tool
+const FINISH_ACTIONS: &[&str] = &["done", "finish", "final", "none", "respond"];
+
+/// Parsed assistant turn
+#[derive(Debug, Clone, Default)]
+pub struct TextTurn {
+    pub thought: String,
+    pub tool_calls: Vec<ToolCall>,
+}
+
+/// Build the system prompt section describing the text protocol and tools
+pub fn tool_protocol_prompt(tools: &[ToolDef]) -> String {
+    let mut prompt = String::from(
+        "\n\n## Tool calling\n\
+         You cannot call functions natively. To use a tool, respond with exactly one JSON object:\n\
+         {\"thought\": \"what you see and plan to do\", \"action\": \"tool_name\", \"params\": { ... }}\n\
+         Use one tool per response and wait for its result before the next one.\n\
+         When the task is complete, respond with:\n\
+         {\"thought\": \"...\", \"action\": \"done\", \"params\": {\"response\": \"message to the user\"}}\n\
+         \nTool schemas:\n",
+    );
+
+    for tool in tools {
+        prompt.push_str(&format!(
+            "- {}: {}\n  params: {}\n",
+            tool.function.name, tool.function.description, tool.function.parameters
+        ));
+    }
+
+    prompt
+}
+
+/// Parse tool invocations from response text
+/// - Ok with no tool calls: the text is a final answer
+/// - Err: an invocation was found but is invalid (message is fed back to the model)
+pub fn parse_response(content: &str, tools: &[ToolDef]) -> Result<TextTurn, String> {
+    let candidates = extract_invocations(content);
+
+    if candidates.is_empty() {
+        return Ok(TextTurn {
+            thought: content.trim().to_string(),
+            tool_calls: Vec::new(),
+        });
+    }
+
+    let mut turn = TextTurn::default();
+    let mut thoughts: Vec<String> = Vec::new();
+    let mut final_response: Option<String> = None;
+
+    for (index, candidate) in candidates.into_iter().enumerate() {
+        if let Some(thought) = candidate.thought {
+            thoughts.push(thought);
+        }
+
+        if FINISH_ACTIONS.contains(&candidate.name.to_lowercase().as_str()) {
+            if let Some(response) = candidate.params.get("response").and_then(|v| v.as_str()) {
+                final_response = Some(response.to_string());
+            }
+            continue;
+        }
+
+        let tool = tools
+            .iter()
+            .find(|t| t.function.name == candidate.name)
+            .ok_or_else(|| {
+                format!(
+                    "Unknown tool '{}'. Available tools: {}",
+                    candidate.name,
+                    tools
+                        .iter()
+                        .map(|t| t.function.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })?;
+
+        let params = validate_arguments(candidate.params, &tool.function.parameters)
+            .map_err(|e| format!("Invalid params for '{}': {}", candidate.name, e))?;
+
+        turn.tool_calls.push(ToolCall {
+            id: format!("text_call_{}", index),
+            call_type: "function".to_string(),
+            function: FunctionCall {
+                name: candidate.name,
+                arguments: params.to_string(),
+            },
+        });
+    }
+
+    // A finishing turn reports its response; otherwise the model's reasoning
+    turn.thought = match final_response {
+        Some(response) => response,
+        None if thoughts.is_empty() => content.trim().to_string(),
+        None => thoughts.join("\n"),
+    };
+
+    Ok(turn)
+}
+
+/// Build the history message carrying a tool result back to the model
+/// (sent as a user turn because the server does not know about tools)
+pub fn tool_result_message(call: &ToolCall, result: &str) -> Message {
+    Message::user(format!("Tool result ({}): {}", call.function.name, result))
+}
+
+/// Build the correction message sent when an invocation could not be used
+pub fn invalid_call_message(error: &str) -> Message {
+    Message::user(format!(
+        "Your tool call could not be executed: {}\nRespond again with a single valid JSON object.",
+        error
+    ))
+}
+
+/// Tool invocation found in text
+#[derive(Debug)]
+struct Invocation {
+    thought: Option<String>,
+    name: String,
+    params: Value,
+}
+
+/// Find tool invocations in XML blocks or bare JSON objects
+fn extract_invocations(content: &str) -> Vec<Invocation> {
+    // <tool_call>...</tool_call> blocks take precedence
+    let xml_blocks = extract_tagged(content, "tool_call");
+    let sources: Vec<String> = if xml_blocks.is_empty() {
+        vec![content.to_string()]
+    } else {
+        xml_blocks
+    };
+
+    sources
+        .iter()
+        .flat_map(|s| extract_json_objects(s))
+        .filter_map(to_invocation)
+        .collect()
+}
+
+/// Convert a JSON object into an invocation if it names a tool
+fn to_invocation(value: Value) -> Option<Invocation> {
+    let obj = value.as_object()?;
+
+    let name = ["action", "name", "tool"]
+        .iter()
+        .find_map(|k| obj.get(*k).and_then(|v| v.as_str()))?
+        .to_string();
+
+    let params = ["params", "arguments", "parameters", "args"]
+        .iter()
+        .find_map(|k| obj.get(*k))
+        .cloned()
+        .map(|v| match v {
+            // Some models double-encode arguments as a string
+            Value::String(s) => serde_json::from_str(&s).unwrap_or(Value::String(s)),
+            other => other,
+        })
+        .unwrap_or_else(|| json!({}));
+
+    let thought = obj
+        .get("thought")
+        .and_then(|v| v.as_str())
+        .map(String::from);
+
+    Some(Invocation {
+        thought,
+        name,
+        params,
+    })
+}
+
+/// Extract the inner text of every <tag>...</tag> block
+fn extract_tagged(content: &str, tag: &str) -> Vec<String> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let mut blocks = Vec::new();
+    let mut rest = content;
+
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        match after.find(&close) {
+            Some(end) => {
+                blocks.push(after[..end].to_string());
+                rest = &after[end + close.len()..];
+            }
+            None => {
+                // Unterminated block - take the rest of the text
+                blocks.push(after.to_string());
+                break;
+            }
+        }
+    }
+
+    blocks
+}
+
+/// Extract every top-level JSON object embedded in free text
+fn extract_json_objects(text: &str) -> Vec<Value> {
+    let mut objects = Vec::new();
+    let bytes = text.as_bytes();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] != b'{' {
+            i += 1;
+            continue;
+        }
+
+        match find_object_end(bytes, i) {
+            Some(end) => {
+                if let Ok(value) = serde_json::from_str::<Value>(&text[i..=end]) {
+                    objects.push(value);
+                    i = end + 1;
+                    continue;
+                }
+                i += 1;
+            }
+            None => break,
+        }
+    }
+
+    objects
+}
+
+/// Find the index of the brace closing the object starting at `start`
+fn find_object_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, &b) in bytes.iter().enumerate().skip(start) {
+        if in_string {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match b {
+            b'"' => in_string = true,
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::types::FunctionDef;
+
+    fn tools() -> Vec<ToolDef> {
+        let tool = |name: &str, parameters: Value| ToolDef {
+            tool_type: "function".to_string(),
+            function: FunctionDef {
+                name: name.to_string(),
+                description: String::new(),
+                parameters,
+            },
+        };
+
+        vec![
+            tool(
+                "click",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "x": { "type": "integer" },
+                        "y": { "type": "integer" },
+                        "button": { "type": "string", "default": "left" }
+                    },
+                    "required": ["x", "y"]
+                }),
+            ),
+            tool(
+                "type_text",
+                json!({
+                    "type": "object",
+                    "properties": { "text": { "type": "string" } },
+                    "required": ["text"]
+                }),
+            ),
+        ]
+    }
+
+    fn arguments(call: &ToolCall) -> Value {
+        serde_json::from_str(&call.function.arguments).unwrap()
+    }
+
+    #[test]
+    fn fenced_json_is_a_call() {
+        let content = "I'll click the button.\n```json\n\
+                       {\"thought\": \"the OK button\", \"action\": \"click\", \"params\": {\"x\": 10, \"y\": 20}}\n\
+                       ```";
+        let turn = parse_response(content, &tools()).unwrap();
+
+        assert_eq!(turn.thought, "the OK button");
+        assert_eq!(turn.tool_calls.len(), 1);
+        assert_eq!(turn.tool_calls[0].function.name, "click");
+        assert_eq!(
+            arguments(&turn.tool_calls[0]),
+            json!({"x": 10, "y": 20, "button": "left"})
+        );
+    }
+
+    #[test]
+    fn unfenced_json_is_a_call() {
+        let content = r#"{"action": "type_text", "params": {"text": "a {brace} and \"quote\""}}"#;
+        let turn = parse_response(content, &tools()).unwrap();
+
+        assert_eq!(turn.tool_calls.len(), 1);
+        assert_eq!(
+            arguments(&turn.tool_calls[0]),
+            json!({"text": "a {brace} and \"quote\""})
+        );
+
+        // Hermes-style blocks with string-encoded arguments
+        let content =
+            r#"<tool_call>{"name": "click", "arguments": "{\"x\": 1, \"y\": 2}"}</tool_call>"#;
+        let turn = parse_response(content, &tools()).unwrap();
+        assert_eq!(
+            arguments(&turn.tool_calls[0]),
+            json!({"x": 1, "y": 2, "button": "left"})
+        );
+    }
+
+    #[test]
+    fn malformed_calls_are_errors() {
+        let error = parse_response(r#"{"action": "scroll", "params": {}}"#, &tools()).unwrap_err();
+        assert!(error.contains("Unknown tool 'scroll'"), "{}", error);
+        assert!(error.contains("click, type_text"), "{}", error);
+
+        let error =
+            parse_response(r#"{"action": "click", "params": {"x": "ten"}}"#, &tools()).unwrap_err();
+        assert!(error.starts_with("Invalid params for 'click'"), "{}", error);
+
+        // Broken JSON names no tool, so it reads as plain text
+        let content = r#"{"action": "click", "params": {"x": 1,"#;
+        let turn = parse_response(content, &tools()).unwrap();
+        assert!(turn.tool_calls.is_empty());
+        assert_eq!(turn.thought, content);
+    }
+
+    #[test]
+    fn multiple_calls_keep_their_order() {
+        let content = r#"{"thought": "focus the field", "action": "click", "params": {"x": 5, "y": 5}}
+{"thought": "enter the name", "action": "type_text", "params": {"text": "hello"}}"#;
+        let turn = parse_response(content, &tools()).unwrap();
+
+        let names: Vec<_> = turn
+            .tool_calls
+            .iter()
+            .map(|c| c.function.name.as_str())
+            .collect();
+        assert_eq!(names, ["click", "type_text"]);
+        let ids: Vec<_> = turn.tool_calls.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, ["text_call_0", "text_call_1"]);
+        assert_eq!(turn.thought, "focus the field\nenter the name");
+    }
+
+    #[test]
+    fn plain_text_is_a_final_answer() {
+        let turn = parse_response("  The file has been saved.\n", &tools()).unwrap();
+        assert!(turn.tool_calls.is_empty());
+        assert_eq!(turn.thought, "The file has been saved.");
+
+        // A finish action reports its response
+        let content =
+            r#"{"thought": "all done", "action": "done", "params": {"response": "Saved."}}"#;
+        let turn = parse_response(content, &tools()).unwrap();
+        assert!(turn.tool_calls.is_empty());
+        assert_eq!(turn.thought, "Saved.");
+    }
+}
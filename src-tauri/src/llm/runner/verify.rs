@@ -0,0 +1,442 @@
+// Action verification - screen diff around input actions
+//
+// When verification is enabled the runner captures the screen and UI tree
+// before and after each input tool, and appends a short summary of what
+// changed (pixel regions, new or removed elements, focus) to the tool result.
+// A click that hit nothing then shows up as "nothing changed".
+
+use crate::commands::config::VerificationConfig;
+use crate::commands::screen::UIElement;
+use crate::llm::runner::environment::Desktop;
+use crate::llm::runner::ToolExecutor;
+use crate::llm::types::{ToolContext, ToolResult};
+use anyhow::Result;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::RgbaImage;
+use serde_json::Value;
+use std::collections::HashMap;
+use tokio::time::Duration;
+
+/// Side of the square cells changed pixels are grouped into
+const CELL_SIZE: u32 = 16;
+
+/// Elements listed per kind of UI change
+const MAX_LISTED_ELEMENTS: usize = 5;
+
+/// Run an input tool and append what changed on screen to its output
+pub async fn execute_verified(
+    desktop: &dyn Desktop,
+    executor: &ToolExecutor,
+    tool_name: &str,
+    params: Value,
+    ctx: &ToolContext,
+    config: &VerificationConfig,
+) -> Result<ToolResult> {
+    let before = capture_snapshot(desktop);
+    let mut result = desktop.execute(executor, tool_name, params, ctx).await?;
+    if !result.success {
+        return Ok(result);
+    }
+
+    let Some(before) = before else {
+        return Ok(result);
+    };
+    tokio::time::sleep(Duration::from_millis(config.settle_ms)).await;
+    if let Some(after) = capture_snapshot(desktop) {
+        let diff = ScreenDiff::compare(&before, &after, config.pixel_threshold);
+        result.output.push_str("\n\n");
+        result.output.push_str(&diff.summary(config.max_regions));
+    }
+
+    Ok(result)
+}
+
+fn capture_snapshot(desktop: &dyn Desktop) -> Option<Snapshot> {
+    let (screenshot, ui_tree) = desktop.capture().ok().flatten()?;
+    Some(Snapshot::new(&screenshot, &ui_tree))
+}
+
+/// Decoded screen state (parts that fail to decode are None)
+pub struct Snapshot {
+    image: Option<RgbaImage>,
+    tree: Option<UIElement>,
+}
+
+impl Snapshot {
+    /// From a screenshot data URL and the UI tree JSON
+    pub fn new(screenshot: &str, ui_tree: &str) -> Self {
+        Self {
+            image: decode_data_url(screenshot),
+            tree: serde_json::from_str(ui_tree).ok(),
+        }
+    }
+}
+
+fn decode_data_url(data_url: &str) -> Option<RgbaImage> {
+    let (_, data) = data_url.split_once(',')?;
+    let bytes = STANDARD.decode(data).ok()?;
+    Some(image::load_from_memory(&bytes).ok()?.to_rgba8())
+}
+
+/// Changed area of the screen in screen pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// What changed between two snapshots
+#[derive(Debug, Default)]
+pub struct ScreenDiff {
+    /// Both screenshots could be compared
+    pixels_compared: bool,
+    /// Both UI trees could be compared
+    tree_compared: bool,
+    /// The screen resolution changed (no pixel comparison)
+    size_changed: bool,
+    /// Share of pixels that changed (0-100)
+    changed_percent: f32,
+    /// Changed regions, largest first
+    regions: Vec<Region>,
+    added: Vec<String>,
+    removed: Vec<String>,
+    /// Focused element before and after (when it changed)
+    focus: Option<(String, String)>,
+}
+
+impl ScreenDiff {
+    pub fn compare(before: &Snapshot, after: &Snapshot, pixel_threshold: u8) -> Self {
+        let mut diff = Self::default();
+
+        if let (Some(a), Some(b)) = (&before.image, &after.image) {
+            diff.pixels_compared = true;
+            if a.dimensions() != b.dimensions() {
+                diff.size_changed = true;
+            } else {
+                diff.compare_pixels(a, b, pixel_threshold);
+            }
+        }
+
+        if let (Some(a), Some(b)) = (&before.tree, &after.tree) {
+            diff.tree_compared = true;
+            diff.compare_trees(a, b);
+        }
+
+        diff
+    }
+
+    fn compare_pixels(&mut self, before: &RgbaImage, after: &RgbaImage, threshold: u8) {
+        let (width, height) = before.dimensions();
+        let cols = width.div_ceil(CELL_SIZE) as usize;
+        let rows = height.div_ceil(CELL_SIZE) as usize;
+        let mut cells = vec![false; cols * rows];
+        let mut changed = 0u64;
+
+        for (x, y, a) in before.enumerate_pixels() {
+            let b = after.get_pixel(x, y);
+            let delta = (0..3).map(|c| a[c].abs_diff(b[c])).max().unwrap_or(0);
+            if delta > threshold {
+                changed += 1;
+                cells[(y / CELL_SIZE) as usize * cols + (x / CELL_SIZE) as usize] = true;
+            }
+        }
+
+        self.changed_percent =
+            changed as f32 * 100.0 / (width as u64 * height as u64).max(1) as f32;
+        self.regions = group_cells(&mut cells, cols, rows)
+            .into_iter()
+            .map(|(x0, y0, x1, y1)| {
+                let x = x0 as u32 * CELL_SIZE;
+                let y = y0 as u32 * CELL_SIZE;
+                Region {
+                    x,
+                    y,
+                    width: ((x1 as u32 + 1) * CELL_SIZE).min(width) - x,
+                    height: ((y1 as u32 + 1) * CELL_SIZE).min(height) - y,
+                }
+            })
+            .collect();
+        self.regions
+            .sort_by_key(|r| std::cmp::Reverse(r.width as u64 * r.height as u64));
+    }
+
+    fn compare_trees(&mut self, before: &UIElement, after: &UIElement) {
+        let before_labels = element_labels(before);
+        let after_labels = element_labels(after);
+
+        // Multiset difference in order of appearance
+        let mut counts: HashMap<&str, i32> = HashMap::new();
+        for label in &before_labels {
+            *counts.entry(label).or_default() += 1;
+        }
+        for label in &after_labels {
+            let count = counts.entry(label).or_default();
+            if *count > 0 {
+                *count -= 1;
+            } else {
+                self.added.push(label.clone());
+            }
+        }
+        for label in before_labels.iter().rev() {
+            let count = counts.entry(label).or_default();
+            if *count > 0 {
+                *count -= 1;
+                self.removed.push(label.clone());
+            }
+        }
+        self.removed.reverse();
+
+        let focus_before = focused_label(before).unwrap_or_else(|| "nothing".to_string());
+        let focus_after = focused_label(after).unwrap_or_else(|| "nothing".to_string());
+        if focus_before != focus_after {
+            self.focus = Some((focus_before, focus_after));
+        }
+    }
+
+    /// Nothing visible or structural changed
+    pub fn is_unchanged(&self) -> bool {
+        !self.size_changed
+            && self.regions.is_empty()
+            && self.added.is_empty()
+            && self.removed.is_empty()
+            && self.focus.is_none()
+    }
+
+    /// Compact summary for the model
+    pub fn summary(&self, max_regions: usize) -> String {
+        if !self.pixels_compared && !self.tree_compared {
+            return "Screen check: the screen could not be compared.".to_string();
+        }
+        if self.is_unchanged() {
+            return "Screen check: nothing changed on screen - the action may have had no effect."
+                .to_string();
+        }
+
+        let mut lines = vec!["Screen check:".to_string()];
+        if self.size_changed {
+            lines.push("- The screen resolution changed".to_string());
+        } else if !self.regions.is_empty() {
+            let listed = self
+                .regions
+                .iter()
+                .take(max_regions)
+                .map(|r| format!("{}x{} at ({}, {})", r.width, r.height, r.x, r.y))
+                .collect::<Vec<_>>()
+                .join(", ");
+            lines.push(format!(
+                "- {:.1}% of the screen changed: {}{}",
+                self.changed_percent,
+                listed,
+                more(self.regions.len(), max_regions, "regions")
+            ));
+        }
+        if !self.added.is_empty() {
+            lines.push(format!("- New elements: {}", list_labels(&self.added)));
+        }
+        if !self.removed.is_empty() {
+            lines.push(format!(
+                "- Removed elements: {}",
+                list_labels(&self.removed)
+            ));
+        }
+        if let Some((before, after)) = &self.focus {
+            lines.push(format!("- Focus moved: {} -> {}", before, after));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Group changed cells into connected regions (cell bounds: x0, y0, x1, y1)
+/// Clears the cells it visits
+fn group_cells(cells: &mut [bool], cols: usize, rows: usize) -> Vec<(usize, usize, usize, usize)> {
+    let mut regions = Vec::new();
+
+    for start in 0..cells.len() {
+        if !cells[start] {
+            continue;
+        }
+        cells[start] = false;
+        let (mut x0, mut y0) = (start % cols, start / cols);
+        let (mut x1, mut y1) = (x0, y0);
+        let mut stack = vec![start];
+
+        while let Some(cell) = stack.pop() {
+            let (x, y) = (cell % cols, cell / cols);
+            x0 = x0.min(x);
+            y0 = y0.min(y);
+            x1 = x1.max(x);
+            y1 = y1.max(y);
+
+            let mut visit = |next: usize| {
+                if cells[next] {
+                    cells[next] = false;
+                    stack.push(next);
+                }
+            };
+            if x > 0 {
+                visit(cell - 1);
+            }
+            if x + 1 < cols {
+                visit(cell + 1);
+            }
+            if y > 0 {
+                visit(cell - cols);
+            }
+            if y + 1 < rows {
+                visit(cell + cols);
+            }
+        }
+
+        regions.push((x0, y0, x1, y1));
+    }
+
+    regions
+}
+
+/// Label of an element as shown to the model (`Button "OK"`)
+fn element_label(element: &UIElement) -> String {
+    if element.name.is_empty() {
+        element.control_type.clone()
+    } else {
+        format!("{} \"{}\"", element.control_type, element.name)
+    }
+}
+
+/// Labels of the named elements in the tree (unnamed ones are mostly layout)
+fn element_labels(root: &UIElement) -> Vec<String> {
+    let mut labels = Vec::new();
+    let mut stack = vec![root];
+    while let Some(element) = stack.pop() {
+        if !element.name.is_empty() {
+            labels.push(element_label(element));
+        }
+        stack.extend(element.children.iter().rev());
+    }
+    labels
+}
+
+fn focused_label(element: &UIElement) -> Option<String> {
+    if element.is_focused {
+        return Some(element_label(element));
+    }
+    element.children.iter().find_map(focused_label)
+}
+
+fn list_labels(labels: &[String]) -> String {
+    let listed = labels
+        .iter()
+        .take(MAX_LISTED_ELEMENTS)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "{}{}",
+        listed,
+        more(labels.len(), MAX_LISTED_ELEMENTS, "more")
+    )
+}
+
+/// ` (+n <what>)` when not everything was listed
+fn more(total: usize, listed: usize, what: &str) -> String {
+    if total > listed {
+        format!(" (+{} {})", total - listed, what)
+    } else {
+        String::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::fake::{element, window};
+    use image::Rgba;
+
+    fn snapshot(image: RgbaImage, tree: UIElement) -> Snapshot {
+        Snapshot {
+            image: Some(image),
+            tree: Some(tree),
+        }
+    }
+
+    #[test]
+    fn reports_nothing_changed() {
+        let image = RgbaImage::from_pixel(64, 64, Rgba([255, 255, 255, 255]));
+        let tree = window(
+            "Notepad",
+            vec![element("Edit", "Text Editor", true, vec![])],
+        );
+        let diff = ScreenDiff::compare(
+            &snapshot(image.clone(), tree.clone()),
+            &snapshot(image, tree),
+            24,
+        );
+
+        assert!(diff.is_unchanged());
+        assert!(diff.summary(3).contains("nothing changed"));
+    }
+
+    #[test]
+    fn finds_changed_regions() {
+        let before = RgbaImage::from_pixel(64, 64, Rgba([255, 255, 255, 255]));
+        let mut after = before.clone();
+        // A 20x10 block at (4, 4) and a single faint pixel below the threshold
+        for x in 4..24 {
+            for y in 4..14 {
+                after.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+            }
+        }
+        after.put_pixel(60, 60, Rgba([250, 250, 250, 255]));
+
+        let tree = window("Notepad", vec![]);
+        let diff = ScreenDiff::compare(&snapshot(before, tree.clone()), &snapshot(after, tree), 24);
+
+        assert_eq!(
+            diff.regions,
+            [Region {
+                x: 0,
+                y: 0,
+                width: 32,
+                height: 16
+            }]
+        );
+        assert!(diff.summary(3).contains("32x16 at (0, 0)"));
+    }
+
+    #[test]
+    fn reports_element_and_focus_changes() {
+        let image = RgbaImage::from_pixel(16, 16, Rgba([0, 0, 0, 255]));
+        let before = window(
+            "Notepad",
+            vec![
+                element("Edit", "Text Editor", true, vec![]),
+                element("Button", "Close", false, vec![]),
+            ],
+        );
+        let after = window(
+            "Notepad",
+            vec![
+                element("Edit", "Text Editor", false, vec![]),
+                element(
+                    "Window",
+                    "Save As",
+                    false,
+                    vec![element("Button", "Save", true, vec![])],
+                ),
+            ],
+        );
+        let diff = ScreenDiff::compare(
+            &snapshot(image.clone(), before),
+            &snapshot(image, after),
+            24,
+        );
+
+        assert_eq!(diff.added, ["Window \"Save As\"", "Button \"Save\""]);
+        assert_eq!(diff.removed, ["Button \"Close\""]);
+        assert_eq!(
+            diff.summary(3),
+            "Screen check:\n- New elements: Window \"Save As\", Button \"Save\"\n- Removed elements: Button \"Close\"\n- Focus moved: Edit \"Text Editor\" -> Button \"Save\""
+        );
+    }
+}
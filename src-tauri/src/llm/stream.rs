@@ -0,0 +1,273 @@
+// SSE streaming support for chat completions
+//
+// Assembles `data:` chunks from an OpenAI-compatible stream into the same
+// ChatResponse the non-streaming client returns, reporting deltas as they arrive.
+
+use crate::llm::client::{ChatResponse, Choice, ResponseMessage, Usage};
+use crate::llm::types::{FunctionCall, ToolCall};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Tauri event name for streamed tokens
+pub const STREAM_EVENT: &str = "llm-stream";
+
+/// How far past the last known tool call a new index may start
+const MAX_TOOL_CALL_GAP: usize = 8;
+
+/// Incremental piece of a streamed response
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StreamDelta {
+    /// Assistant text (the agent's thought)
+    Content { text: String },
+    /// Fragment of a tool call; name arrives once, arguments arrive in pieces
+    ToolCall {
+        index: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        arguments: String,
+    },
+}
+
+/// Stream event payload sent to the frontend
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamEvent {
+    /// Agent run the delta belongs to
+    pub run_id: String,
+    /// Agent loop iteration the delta belongs to
+    pub iteration: usize,
+    #[serde(flatten)]
+    pub delta: StreamDelta,
+}
+
+/// Single SSE chunk
+#[derive(Debug, Deserialize)]
+struct ChatChunk {
+    #[serde(default)]
+    choices: Vec<ChunkChoice>,
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+/// Chunk choice
+#[derive(Debug, Deserialize)]
+struct ChunkChoice {
+    #[serde(default)]
+    delta: ChunkDelta,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+/// Chunk delta
+#[derive(Debug, Default, Deserialize)]
+struct ChunkDelta {
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Option<Vec<ToolCallDelta>>,
+}
+
+/// Partial tool call inside a chunk
+#[derive(Debug, Deserialize)]
+struct ToolCallDelta {
+    #[serde(default)]
+    index: usize,
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(rename = "type", default)]
+    call_type: Option<String>,
+    #[serde(default)]
+    function: Option<FunctionCallDelta>,
+}
+
+/// Partial function call inside a chunk
+#[derive(Debug, Deserialize)]
+struct FunctionCallDelta {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    arguments: Option<String>,
+}
+
+/// Tool call being assembled from fragments
+#[derive(Debug, Default)]
+struct PartialToolCall {
+    id: String,
+    call_type: String,
+    name: String,
+    arguments: String,
+}
+
+/// Accumulates SSE bytes into a complete ChatResponse
+#[derive(Debug, Default)]
+pub struct StreamAccumulator {
+    line_buffer: Vec<u8>,
+    content: String,
+    tool_calls: Vec<PartialToolCall>,
+    finish_reason: Option<String>,
+    usage: Option<Usage>,
+    done: bool,
+}
+
+impl StreamAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed raw bytes from the response body, returning the deltas they completed
+    pub fn push(&mut self, bytes: &[u8]) -> Result<Vec<StreamDelta>> {
+        self.line_buffer.extend_from_slice(bytes);
+
+        let mut deltas = Vec::new();
+        // Only complete lines are parsed so multi-byte characters split across
+        // network chunks are never decoded half-way
+        while let Some(pos) = self.line_buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.line_buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            deltas.extend(self.handle_line(line.trim_end_matches(['\r', '\n']))?);
+        }
+
+        Ok(deltas)
+    }
+
+    /// Whether the `[DONE]` marker has been received
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Handle a single SSE line
+    fn handle_line(&mut self, line: &str) -> Result<Vec<StreamDelta>> {
+        // Blank lines separate events; other fields (event:, id:, comments) are ignored
+        let Some(data) = line.strip_prefix("data:") else {
+            return Ok(Vec::new());
+        };
+        let data = data.trim();
+
+        if data == "[DONE]" {
+            self.done = true;
+            return Ok(Vec::new());
+        }
+        if data.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let chunk: ChatChunk = serde_json::from_str(data)
+            .map_err(|e| anyhow::anyhow!("Invalid stream chunk: {} ({})", e, data))?;
+
+        if chunk.usage.is_some() {
+            self.usage = chunk.usage;
+        }
+
+        let mut deltas = Vec::new();
+        for choice in chunk.choices {
+            if let Some(reason) = choice.finish_reason {
+                self.finish_reason = Some(reason);
+            }
+
+            if let Some(text) = choice.delta.content.filter(|t| !t.is_empty()) {
+                self.content.push_str(&text);
+                deltas.push(StreamDelta::Content { text });
+            }
+
+            for call in choice.delta.tool_calls.unwrap_or_default() {
+                deltas.push(self.apply_tool_call_delta(call)?);
+            }
+        }
+
+        Ok(deltas)
+    }
+
+    /// Merge a tool call fragment into the call at its index
+    fn apply_tool_call_delta(&mut self, delta: ToolCallDelta) -> Result<StreamDelta> {
+        if delta.index > self.tool_calls.len() + MAX_TOOL_CALL_GAP {
+            anyhow::bail!(
+                "Tool call index {} is out of range ({} calls so far)",
+                delta.index,
+                self.tool_calls.len()
+            );
+        }
+        while self.tool_calls.len() <= delta.index {
+            self.tool_calls.push(PartialToolCall::default());
+        }
+        let call = &mut self.tool_calls[delta.index];
+
+        if let Some(id) = delta.id {
+            call.id = id;
+        }
+        if let Some(call_type) = delta.call_type {
+            call.call_type = call_type;
+        }
+
+        let mut name = None;
+        let mut arguments = String::new();
+        if let Some(function) = delta.function {
+            if let Some(n) = function.name {
+                call.name.push_str(&n);
+                name = Some(n);
+            }
+            if let Some(args) = function.arguments {
+                call.arguments.push_str(&args);
+                arguments = args;
+            }
+        }
+
+        Ok(StreamDelta::ToolCall {
+            index: delta.index,
+            name,
+            arguments,
+        })
+    }
+
+    /// Build the final response from everything received
+    pub fn finish(mut self) -> Result<ChatResponse> {
+        // A final line without trailing newline still counts
+        if !self.line_buffer.is_empty() {
+            let rest = std::mem::take(&mut self.line_buffer);
+            let rest = String::from_utf8_lossy(&rest).to_string();
+            self.handle_line(rest.trim_end_matches('\r'))?;
+        }
+
+        let tool_calls: Vec<ToolCall> = self
+            .tool_calls
+            .into_iter()
+            .filter(|c| !c.name.is_empty())
+            .enumerate()
+            .map(|(i, c)| ToolCall {
+                id: if c.id.is_empty() {
+                    format!("call_{}", i)
+                } else {
+                    c.id
+                },
+                call_type: if c.call_type.is_empty() {
+                    "function".to_string()
+                } else {
+                    c.call_type
+                },
+                function: FunctionCall {
+                    name: c.name,
+                    arguments: c.arguments,
+                },
+            })
+            .collect();
+
+        Ok(ChatResponse {
+            choices: vec![Choice {
+                message: ResponseMessage {
+                    role: "assistant".to_string(),
+                    content: if self.content.is_empty() {
+                        None
+                    } else {
+                        Some(self.content)
+                    },
+                    tool_calls: if tool_calls.is_empty() {
+                        None
+                    } else {
+                        Some(tool_calls)
+                    },
+                },
+                finish_reason: self.finish_reason,
+            }],
+            usage: self.usage,
+        })
+    }
+}
@@ -1,6 +1,7 @@
 // Keyboard tools - text input and key presses
 
-use super::Tool;
+use super::{SideEffect, Tool, ToolCategory, ToolPlatform};
+use crate::input::keys::{parse_keys, Chord};
 use crate::llm::types::{ToolContext, ToolResult};
 use async_trait::async_trait;
 use serde_json::{json, Value};
@@ -28,16 +29,31 @@ impl Tool for KeyboardTypeTool {
         })
     }
 
-    async fn execute(&self, params: Value, _ctx: &ToolContext) -> anyhow::Result<ToolResult> {
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Keyboard
+    }
+
+    fn side_effect(&self) -> SideEffect {
+        SideEffect::Input
+    }
+
+    fn platform(&self) -> ToolPlatform {
+        ToolPlatform::Desktop
+    }
+
+    async fn execute(&self, params: Value, ctx: &ToolContext) -> anyhow::Result<ToolResult> {
+        ctx.cancel.check()?;
         let text = params["text"]
             .as_str()
             .ok_or_else(|| anyhow::anyhow!("Missing text"))?;
 
-        #[cfg(windows)]
-        {
-            crate::input::keyboard::type_text(text, 30)?;
+        if ctx.dry_run {
+            return Ok(ToolResult::planned(format!("type {:?}", text), Vec::new()));
         }
 
+        let input = ctx.input_backend()?;
+        crate::input::keyboard::type_text(input.as_ref(), text, 30, &ctx.cancel)?;
+
         Ok(ToolResult::success(format!("Typed: {}", text)))
     }
 }
@@ -52,7 +68,8 @@ impl Tool for KeyboardPressTool {
     }
 
     fn description(&self) -> &str {
-        "Press key or key combination"
+        "Press key combinations: keys joined with + are pressed together, \
+         space-separated chords one after another, *N repeats a chord, @500ms holds it"
     }
 
     fn parameters(&self) -> Value {
@@ -60,28 +77,252 @@ impl Tool for KeyboardPressTool {
             "type": "object",
             "properties": {
                 "keys": {
-                    "type": "array",
+                    "type": ["string", "array"],
                     "items": { "type": "string" },
-                    "description": "Keys to press (e.g., ['ctrl', 'c'])"
+                    "description": "Keys to press (e.g., 'ctrl+c', 'ctrl+k ctrl+s', 'down*3 enter', 'shift@1s', 'ctrl++'); an array of key names is pressed as one chord"
                 }
             },
             "required": ["keys"]
         })
     }
 
-    async fn execute(&self, params: Value, _ctx: &ToolContext) -> anyhow::Result<ToolResult> {
-        let keys: Vec<String> = params["keys"]
-            .as_array()
-            .ok_or_else(|| anyhow::anyhow!("Missing keys"))?
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Keyboard
+    }
+
+    fn side_effect(&self) -> SideEffect {
+        SideEffect::Input
+    }
+
+    fn platform(&self) -> ToolPlatform {
+        ToolPlatform::Desktop
+    }
+
+    async fn execute(&self, params: Value, ctx: &ToolContext) -> anyhow::Result<ToolResult> {
+        ctx.cancel.check()?;
+        // Unknown keys fail here, before anything is pressed
+        let chords = parse_keys(&params["keys"])?;
+        let sequence = chords
             .iter()
-            .filter_map(|v| v.as_str().map(String::from))
-            .collect();
+            .map(Chord::to_string)
+            .collect::<Vec<_>>()
+            .join(" ");
 
-        #[cfg(windows)]
-        {
-            crate::input::keyboard::press_keys(&keys)?;
+        if ctx.dry_run {
+            return Ok(ToolResult::planned(
+                format!("press {}", sequence),
+                Vec::new(),
+            ));
         }
 
-        Ok(ToolResult::success(format!("Pressed: {:?}", keys)))
+        let input = ctx.input_backend()?;
+        crate::input::keyboard::press_chords(input.as_ref(), &chords, &ctx.cancel)?;
+
+        Ok(ToolResult::success(format!("Pressed: {}", sequence)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::fake::{tool_context, FakeBackend, InputAction};
+
+    #[tokio::test]
+    async fn type_sends_every_character() {
+        let fake = FakeBackend::new();
+
+        let result = KeyboardTypeTool
+            .execute(json!({ "text": "hi 한글" }), &tool_context(&fake))
+            .await
+            .unwrap();
+
+        assert_eq!(result.output, "Typed: hi 한글");
+        assert_eq!(fake.typed(), "hi 한글");
+    }
+
+    #[tokio::test]
+    async fn press_holds_keys_and_releases_them_in_reverse() {
+        let fake = FakeBackend::new();
+
+        let result = KeyboardPressTool
+            .execute(json!({ "keys": "Ctrl+Shift+T" }), &tool_context(&fake))
+            .await
+            .unwrap();
+
+        assert_eq!(result.output, "Pressed: ctrl+shift+t");
+        let key = |name: &str| name.to_string();
+        assert_eq!(
+            fake.events(),
+            [
+                InputAction::KeyDown(key("ctrl")),
+                InputAction::KeyDown(key("shift")),
+                InputAction::KeyDown(key("t")),
+                InputAction::KeyUp(key("t")),
+                InputAction::KeyUp(key("shift")),
+                InputAction::KeyUp(key("ctrl")),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn key_name_arrays_are_pressed_as_one_chord() {
+        let fake = FakeBackend::new();
+
+        let result = KeyboardPressTool
+            .execute(json!({ "keys": ["Control", "c"] }), &tool_context(&fake))
+            .await
+            .unwrap();
+
+        assert_eq!(result.output, "Pressed: ctrl+c");
+        let key = |name: &str| name.to_string();
+        assert_eq!(
+            fake.events(),
+            [
+                InputAction::KeyDown(key("ctrl")),
+                InputAction::KeyDown(key("c")),
+                InputAction::KeyUp(key("c")),
+                InputAction::KeyUp(key("ctrl")),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_arguments_are_rejected() {
+        let fake = FakeBackend::new();
+        let ctx = tool_context(&fake);
+
+        let error = KeyboardTypeTool.execute(json!({}), &ctx).await.unwrap_err();
+        assert_eq!(error.to_string(), "Missing text");
+        let error = KeyboardPressTool
+            .execute(json!({}), &ctx)
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Missing keys");
+        assert!(fake.actions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn chords_are_pressed_in_sequence_and_repeated() {
+        let fake = FakeBackend::new();
+
+        let result = KeyboardPressTool
+            .execute(
+                json!({ "keys": "ctrl+k ctrl+s down*2" }),
+                &tool_context(&fake),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.output, "Pressed: ctrl+k ctrl+s down*2");
+        let key = |name: &str| name.to_string();
+        assert_eq!(
+            fake.events(),
+            [
+                InputAction::KeyDown(key("ctrl")),
+                InputAction::KeyDown(key("k")),
+                InputAction::KeyUp(key("k")),
+                InputAction::KeyUp(key("ctrl")),
+                InputAction::KeyDown(key("ctrl")),
+                InputAction::KeyDown(key("s")),
+                InputAction::KeyUp(key("s")),
+                InputAction::KeyUp(key("ctrl")),
+                InputAction::KeyDown(key("down")),
+                InputAction::KeyUp(key("down")),
+                InputAction::KeyDown(key("down")),
+                InputAction::KeyUp(key("down")),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_keys_are_errors_and_nothing_is_pressed() {
+        let fake = FakeBackend::new();
+
+        let error = KeyboardPressTool
+            .execute(json!({ "keys": "ctrl+k ctrl+sdf" }), &tool_context(&fake))
+            .await
+            .unwrap_err();
+
+        assert!(error.to_string().starts_with("Unknown key: \"sdf\""));
+        assert!(fake.actions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn cancelled_hold_releases_the_keys() {
+        let fake = FakeBackend::new();
+        let ctx = tool_context(&fake);
+        let cancel = ctx.cancel.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            cancel.cancel();
+        });
+
+        let error = KeyboardPressTool
+            .execute(json!({ "keys": "shift+a@5s" }), &ctx)
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "Cancelled");
+        let key = |name: &str| name.to_string();
+        assert_eq!(
+            fake.events(),
+            [
+                InputAction::KeyDown(key("shift")),
+                InputAction::KeyDown(key("a")),
+                InputAction::KeyUp(key("a")),
+                InputAction::KeyUp(key("shift")),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn cancelling_stops_typing() {
+        let fake = FakeBackend::new();
+        let ctx = tool_context(&fake);
+        let cancel = ctx.cancel.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            cancel.cancel();
+        });
+
+        // Typing blocks the thread, 30ms per character
+        let error = KeyboardTypeTool
+            .execute(json!({ "text": "a".repeat(100) }), &ctx)
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "Cancelled");
+        let typed = fake.typed().len();
+        assert!(typed > 0 && typed < 100, "typed {} characters", typed);
+    }
+
+    #[tokio::test]
+    async fn backend_failures_are_reported() {
+        let fake = FakeBackend::new();
+        fake.fail_with("no keyboard");
+
+        let error = KeyboardPressTool
+            .execute(json!({ "keys": "enter" }), &tool_context(&fake))
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "no keyboard");
+    }
+
+    #[tokio::test]
+    async fn dry_run_describes_the_keys() {
+        let fake = FakeBackend::new();
+        let ctx = ToolContext {
+            dry_run: true,
+            ..tool_context(&fake)
+        };
+
+        let result = KeyboardPressTool
+            .execute(json!({ "keys": "Control+C" }), &ctx)
+            .await
+            .unwrap();
+
+        assert_eq!(result.output, "Dry run, not performed: press ctrl+c");
+        assert!(fake.actions().is_empty());
     }
 }
@@ -4,17 +4,67 @@ mod mouse;
 mod keyboard;
 mod screen;
 mod scroll;
+mod plan;
 mod registry;
+mod schema;
 
 pub use mouse::*;
 pub use keyboard::*;
 pub use screen::*;
 pub use scroll::*;
+pub use plan::*;
+pub use registry::*;
+pub use schema::*;
 
 use crate::llm::types::{ToolContext, ToolDef, ToolResult};
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+/// What a tool is for (tool profiles select tools by category)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolCategory {
+    Mouse,
+    Keyboard,
+    Screen,
+    /// Plan mode tools (offered only in plan mode)
+    Plan,
+    /// Sub-agents
+    Agent,
+}
+
+/// What a tool changes outside the conversation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SideEffect {
+    /// Reads state or changes only the run's own state
+    None,
+    /// Sends mouse or keyboard input to the desktop
+    Input,
+    /// Runs a sub-agent that makes its own LLM calls
+    SubAgent,
+}
+
+/// Operating systems a tool works on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolPlatform {
+    Any,
+    /// Needs a native input backend (Windows, or Linux with X11)
+    Desktop,
+}
+
+impl ToolPlatform {
+    /// Whether the tool works on `os` (as in `std::env::consts::OS`)
+    pub fn supports(self, os: &str) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Desktop => matches!(os, "windows" | "linux"),
+        }
+    }
+}
+
 /// Trait for all tools (both primitive and agent-based)
 #[async_trait]
 pub trait Tool: Send + Sync {
@@ -27,6 +77,19 @@ pub trait Tool: Send + Sync {
     /// JSON schema for parameters
     fn parameters(&self) -> Value;
 
+    /// What the tool is for
+    fn category(&self) -> ToolCategory;
+
+    /// What the tool changes outside the conversation
+    fn side_effect(&self) -> SideEffect {
+        SideEffect::None
+    }
+
+    /// Where the tool works
+    fn platform(&self) -> ToolPlatform {
+        ToolPlatform::Any
+    }
+
     /// Execute the tool with given parameters
     async fn execute(&self, params: Value, ctx: &ToolContext) -> anyhow::Result<ToolResult>;
 
@@ -1,6 +1,9 @@
-// Mouse tools - mouse movement and clicking
+// Mouse tools - mouse movement, clicking and dragging
 
-use super::Tool;
+use super::{SideEffect, Tool, ToolCategory, ToolPlatform};
+use crate::commands::input::MouseButton;
+use crate::commands::overlay::OverlayEvent;
+use crate::input::mouse::Drag;
 use crate::llm::types::{ToolContext, ToolResult};
 use async_trait::async_trait;
 use serde_json::{json, Value};
@@ -29,16 +32,35 @@ impl Tool for MouseMoveTool {
         })
     }
 
-    async fn execute(&self, params: Value, _ctx: &ToolContext) -> anyhow::Result<ToolResult> {
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Mouse
+    }
+
+    fn side_effect(&self) -> SideEffect {
+        SideEffect::Input
+    }
+
+    fn platform(&self) -> ToolPlatform {
+        ToolPlatform::Desktop
+    }
+
+    async fn execute(&self, params: Value, ctx: &ToolContext) -> anyhow::Result<ToolResult> {
+        ctx.cancel.check()?;
         let x = params["x"].as_i64().ok_or_else(|| anyhow::anyhow!("Missing x"))? as i32;
         let y = params["y"].as_i64().ok_or_else(|| anyhow::anyhow!("Missing y"))? as i32;
 
-        #[cfg(windows)]
-        {
-            crate::input::mouse::smooth_move(x, y, 300)?;
+        let effects = vec![OverlayEvent::CursorMove { x, y }];
+        if ctx.dry_run {
+            return Ok(ToolResult::planned(
+                format!("move mouse to ({}, {})", x, y),
+                effects,
+            ));
         }
 
-        Ok(ToolResult::success(format!("Moved mouse to ({}, {})", x, y)))
+        let input = ctx.input_backend()?;
+        crate::input::mouse::smooth_move(input.as_ref(), x, y, 300, &ctx.cancel)?;
+
+        Ok(ToolResult::success(format!("Moved mouse to ({}, {})", x, y)).with_effects(effects))
     }
 }
 
@@ -71,23 +93,44 @@ impl Tool for MouseClickTool {
         })
     }
 
-    async fn execute(&self, params: Value, _ctx: &ToolContext) -> anyhow::Result<ToolResult> {
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Mouse
+    }
+
+    fn side_effect(&self) -> SideEffect {
+        SideEffect::Input
+    }
+
+    fn platform(&self) -> ToolPlatform {
+        ToolPlatform::Desktop
+    }
+
+    async fn execute(&self, params: Value, ctx: &ToolContext) -> anyhow::Result<ToolResult> {
+        ctx.cancel.check()?;
         let x = params["x"].as_i64().ok_or_else(|| anyhow::anyhow!("Missing x"))? as i32;
         let y = params["y"].as_i64().ok_or_else(|| anyhow::anyhow!("Missing y"))? as i32;
         let button_str = params["button"].as_str().unwrap_or("left");
 
-        #[cfg(windows)]
-        {
-            use crate::commands::input::MouseButton;
-            let button = match button_str {
-                "right" => MouseButton::Right,
-                "middle" => MouseButton::Middle,
-                _ => MouseButton::Left,
-            };
-            crate::input::mouse::click(x, y, button, false)?;
+        let effects = vec![
+            OverlayEvent::CursorMove { x, y },
+            OverlayEvent::Click {
+                x,
+                y,
+                button: button_str.to_string(),
+            },
+        ];
+        if ctx.dry_run {
+            return Ok(ToolResult::planned(
+                format!("click {} at ({}, {})", button_str, x, y),
+                effects,
+            ));
         }
 
-        Ok(ToolResult::success(format!("Clicked {} at ({}, {})", button_str, x, y)))
+        let input = ctx.input_backend()?;
+        crate::input::mouse::click(input.as_ref(), x, y, button(button_str), false, &ctx.cancel)?;
+
+        Ok(ToolResult::success(format!("Clicked {} at ({}, {})", button_str, x, y))
+            .with_effects(effects))
     }
 }
 
@@ -115,16 +158,648 @@ impl Tool for MouseDoubleClickTool {
         })
     }
 
-    async fn execute(&self, params: Value, _ctx: &ToolContext) -> anyhow::Result<ToolResult> {
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Mouse
+    }
+
+    fn side_effect(&self) -> SideEffect {
+        SideEffect::Input
+    }
+
+    fn platform(&self) -> ToolPlatform {
+        ToolPlatform::Desktop
+    }
+
+    async fn execute(&self, params: Value, ctx: &ToolContext) -> anyhow::Result<ToolResult> {
+        ctx.cancel.check()?;
         let x = params["x"].as_i64().ok_or_else(|| anyhow::anyhow!("Missing x"))? as i32;
         let y = params["y"].as_i64().ok_or_else(|| anyhow::anyhow!("Missing y"))? as i32;
 
-        #[cfg(windows)]
-        {
-            use crate::commands::input::MouseButton;
-            crate::input::mouse::click(x, y, MouseButton::Left, true)?;
+        let click = OverlayEvent::Click {
+            x,
+            y,
+            button: "left".to_string(),
+        };
+        let effects = vec![OverlayEvent::CursorMove { x, y }, click.clone(), click];
+        if ctx.dry_run {
+            return Ok(ToolResult::planned(
+                format!("double-click at ({}, {})", x, y),
+                effects,
+            ));
         }
 
-        Ok(ToolResult::success(format!("Double-clicked at ({}, {})", x, y)))
+        let input = ctx.input_backend()?;
+        crate::input::mouse::click(input.as_ref(), x, y, MouseButton::Left, true, &ctx.cancel)?;
+
+        Ok(ToolResult::success(format!("Double-clicked at ({}, {})", x, y)).with_effects(effects))
+    }
+}
+
+/// Mouse down tool
+pub struct MouseDownTool;
+
+#[async_trait]
+impl Tool for MouseDownTool {
+    fn name(&self) -> &str {
+        "mouse_down"
+    }
+
+    fn description(&self) -> &str {
+        "Press and hold a mouse button at specified coordinates (release with mouse_up)"
+    }
+
+    fn parameters(&self) -> Value {
+        button_at_point()
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Mouse
+    }
+
+    fn side_effect(&self) -> SideEffect {
+        SideEffect::Input
+    }
+
+    fn platform(&self) -> ToolPlatform {
+        ToolPlatform::Desktop
+    }
+
+    async fn execute(&self, params: Value, ctx: &ToolContext) -> anyhow::Result<ToolResult> {
+        ctx.cancel.check()?;
+        let x = params["x"].as_i64().ok_or_else(|| anyhow::anyhow!("Missing x"))? as i32;
+        let y = params["y"].as_i64().ok_or_else(|| anyhow::anyhow!("Missing y"))? as i32;
+        let button_str = params["button"].as_str().unwrap_or("left");
+
+        let effects = vec![
+            OverlayEvent::CursorMove { x, y },
+            OverlayEvent::MouseDown {
+                x,
+                y,
+                button: button_str.to_string(),
+            },
+        ];
+        if ctx.dry_run {
+            return Ok(ToolResult::planned(
+                format!("press {} at ({}, {})", button_str, x, y),
+                effects,
+            ));
+        }
+
+        let input = ctx.input_backend()?;
+        crate::input::mouse::press(input.as_ref(), x, y, button(button_str), &ctx.cancel)?;
+        ctx.held.press(button(button_str));
+
+        Ok(ToolResult::success(format!("Pressed {} at ({}, {})", button_str, x, y))
+            .with_effects(effects))
+    }
+}
+
+/// Mouse up tool
+pub struct MouseUpTool;
+
+#[async_trait]
+impl Tool for MouseUpTool {
+    fn name(&self) -> &str {
+        "mouse_up"
+    }
+
+    fn description(&self) -> &str {
+        "Move to specified coordinates and release a held mouse button"
+    }
+
+    fn parameters(&self) -> Value {
+        button_at_point()
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Mouse
+    }
+
+    fn side_effect(&self) -> SideEffect {
+        SideEffect::Input
+    }
+
+    fn platform(&self) -> ToolPlatform {
+        ToolPlatform::Desktop
+    }
+
+    async fn execute(&self, params: Value, ctx: &ToolContext) -> anyhow::Result<ToolResult> {
+        ctx.cancel.check()?;
+        let x = params["x"].as_i64().ok_or_else(|| anyhow::anyhow!("Missing x"))? as i32;
+        let y = params["y"].as_i64().ok_or_else(|| anyhow::anyhow!("Missing y"))? as i32;
+        let button_str = params["button"].as_str().unwrap_or("left");
+
+        let effects = vec![
+            OverlayEvent::CursorMove { x, y },
+            OverlayEvent::MouseUp {
+                x,
+                y,
+                button: button_str.to_string(),
+            },
+        ];
+        if ctx.dry_run {
+            return Ok(ToolResult::planned(
+                format!("release {} at ({}, {})", button_str, x, y),
+                effects,
+            ));
+        }
+
+        let input = ctx.input_backend()?;
+        crate::input::mouse::release(input.as_ref(), x, y, button(button_str), &ctx.cancel)?;
+        ctx.held.release(button(button_str));
+
+        Ok(ToolResult::success(format!("Released {} at ({}, {})", button_str, x, y))
+            .with_effects(effects))
+    }
+}
+
+/// Mouse drag tool
+pub struct MouseDragTool;
+
+#[async_trait]
+impl Tool for MouseDragTool {
+    fn name(&self) -> &str {
+        "mouse_drag"
+    }
+
+    fn description(&self) -> &str {
+        "Drag from one point to another with a mouse button held"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "from_x": { "type": "integer", "description": "X coordinate to drag from" },
+                "from_y": { "type": "integer", "description": "Y coordinate to drag from" },
+                "to_x": { "type": "integer", "description": "X coordinate to drop at" },
+                "to_y": { "type": "integer", "description": "Y coordinate to drop at" },
+                "button": {
+                    "type": "string",
+                    "enum": ["left", "right", "middle"],
+                    "default": "left"
+                },
+                "hold_ms": {
+                    "type": "integer",
+                    "description": "How long to hold the button before moving",
+                    "minimum": 0,
+                    "maximum": 5000,
+                    "default": 100
+                },
+                "duration_ms": {
+                    "type": "integer",
+                    "description": "How long the move takes",
+                    "minimum": 50,
+                    "maximum": 5000,
+                    "default": 500
+                },
+                "modifiers": {
+                    "type": "array",
+                    "items": { "type": "string", "enum": ["ctrl", "alt", "shift", "win"] },
+                    "description": "Keys held during the drag (e.g., ['ctrl'] to copy)"
+                }
+            },
+            "required": ["from_x", "from_y", "to_x", "to_y"]
+        })
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Mouse
+    }
+
+    fn side_effect(&self) -> SideEffect {
+        SideEffect::Input
+    }
+
+    fn platform(&self) -> ToolPlatform {
+        ToolPlatform::Desktop
+    }
+
+    async fn execute(&self, params: Value, ctx: &ToolContext) -> anyhow::Result<ToolResult> {
+        ctx.cancel.check()?;
+        let coordinate = |name: &str| {
+            params[name]
+                .as_i64()
+                .map(|v| v as i32)
+                .ok_or_else(|| anyhow::anyhow!("Missing {}", name))
+        };
+        let from = (coordinate("from_x")?, coordinate("from_y")?);
+        let to = (coordinate("to_x")?, coordinate("to_y")?);
+        let button_str = params["button"].as_str().unwrap_or("left");
+        let modifiers: Vec<String> = params["modifiers"]
+            .as_array()
+            .map(|keys| {
+                keys.iter()
+                    .filter_map(|key| key.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut summary = format!(
+            "{} from ({}, {}) to ({}, {})",
+            button_str, from.0, from.1, to.0, to.1
+        );
+        if !modifiers.is_empty() {
+            summary.push_str(&format!(" holding {}", modifiers.join("+")));
+        }
+
+        let effects = vec![
+            OverlayEvent::CursorMove {
+                x: from.0,
+                y: from.1,
+            },
+            OverlayEvent::MouseDown {
+                x: from.0,
+                y: from.1,
+                button: button_str.to_string(),
+            },
+            OverlayEvent::Drag {
+                from_x: from.0,
+                from_y: from.1,
+                to_x: to.0,
+                to_y: to.1,
+                button: button_str.to_string(),
+            },
+            OverlayEvent::CursorMove { x: to.0, y: to.1 },
+            OverlayEvent::MouseUp {
+                x: to.0,
+                y: to.1,
+                button: button_str.to_string(),
+            },
+        ];
+        if ctx.dry_run {
+            return Ok(ToolResult::planned(format!("drag {}", summary), effects));
+        }
+
+        let drag = Drag {
+            from,
+            to,
+            button: button(button_str),
+            hold_ms: params["hold_ms"].as_u64().unwrap_or(100),
+            duration_ms: params["duration_ms"].as_u64().unwrap_or(500),
+            modifiers: &modifiers,
+        };
+        let input = ctx.input_backend()?;
+        crate::input::mouse::drag(input.as_ref(), &drag, &ctx.cancel)?;
+
+        Ok(ToolResult::success(format!("Dragged {}", summary)).with_effects(effects))
+    }
+}
+
+/// Parameters of a button pressed or released at a point
+fn button_at_point() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "x": { "type": "integer", "description": "X coordinate" },
+            "y": { "type": "integer", "description": "Y coordinate" },
+            "button": {
+                "type": "string",
+                "enum": ["left", "right", "middle"],
+                "default": "left"
+            }
+        },
+        "required": ["x", "y"]
+    })
+}
+
+/// Mouse button by name (left when unknown)
+fn button(name: &str) -> MouseButton {
+    match name {
+        "right" => MouseButton::Right,
+        "middle" => MouseButton::Middle,
+        _ => MouseButton::Left,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::fake::{tool_context, FakeBackend, InputAction};
+
+    #[tokio::test]
+    async fn move_follows_a_path_to_the_target() {
+        let fake = FakeBackend::new();
+        fake.set_cursor(0, 0);
+
+        let result = MouseMoveTool
+            .execute(json!({ "x": 300, "y": 200 }), &tool_context(&fake))
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.output, "Moved mouse to (300, 200)");
+        let path = fake.cursor_path();
+        assert!(path.len() > 10);
+        assert_eq!(path.last(), Some(&(300, 200)));
+        assert!(fake.events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn click_presses_and_releases_the_button_at_the_target() {
+        let fake = FakeBackend::new();
+
+        let result = MouseClickTool
+            .execute(
+                json!({ "x": 40, "y": 60, "button": "right" }),
+                &tool_context(&fake),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.output, "Clicked right at (40, 60)");
+        assert_eq!(fake.cursor_path().last(), Some(&(40, 60)));
+        assert_eq!(
+            fake.events(),
+            [
+                InputAction::ButtonDown(MouseButton::Right),
+                InputAction::ButtonUp(MouseButton::Right)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn click_defaults_to_the_left_button() {
+        let fake = FakeBackend::new();
+
+        MouseClickTool
+            .execute(json!({ "x": 1, "y": 2 }), &tool_context(&fake))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            fake.events(),
+            [
+                InputAction::ButtonDown(MouseButton::Left),
+                InputAction::ButtonUp(MouseButton::Left)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn double_click_clicks_twice() {
+        let fake = FakeBackend::new();
+
+        let result = MouseDoubleClickTool
+            .execute(json!({ "x": 5, "y": 6 }), &tool_context(&fake))
+            .await
+            .unwrap();
+
+        assert_eq!(result.output, "Double-clicked at (5, 6)");
+        let click = [
+            InputAction::ButtonDown(MouseButton::Left),
+            InputAction::ButtonUp(MouseButton::Left),
+        ];
+        assert_eq!(fake.events(), [click.clone(), click].concat());
+    }
+
+    #[tokio::test]
+    async fn press_and_release_hold_the_button_in_between() {
+        let fake = FakeBackend::new();
+        let ctx = tool_context(&fake);
+
+        let result = MouseDownTool
+            .execute(json!({ "x": 10, "y": 20 }), &ctx)
+            .await
+            .unwrap();
+        assert_eq!(result.output, "Pressed left at (10, 20)");
+        assert_eq!(fake.events(), [InputAction::ButtonDown(MouseButton::Left)]);
+
+        let result = MouseUpTool
+            .execute(json!({ "x": 90, "y": 80 }), &ctx)
+            .await
+            .unwrap();
+        assert_eq!(result.output, "Released left at (90, 80)");
+        assert_eq!(fake.cursor_path().last(), Some(&(90, 80)));
+        assert_eq!(
+            fake.events(),
+            [
+                InputAction::ButtonDown(MouseButton::Left),
+                InputAction::ButtonUp(MouseButton::Left)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn drag_holds_modifiers_around_the_button() {
+        let fake = FakeBackend::new();
+
+        let result = MouseDragTool
+            .execute(
+                json!({
+                    "from_x": 100, "from_y": 100, "to_x": 400, "to_y": 250,
+                    "modifiers": ["ctrl"], "hold_ms": 0, "duration_ms": 100
+                }),
+                &tool_context(&fake),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result.output,
+            "Dragged left from (100, 100) to (400, 250) holding ctrl"
+        );
+        assert_eq!(
+            fake.events(),
+            [
+                InputAction::KeyDown("ctrl".to_string()),
+                InputAction::ButtonDown(MouseButton::Left),
+                InputAction::ButtonUp(MouseButton::Left),
+                InputAction::KeyUp("ctrl".to_string()),
+            ]
+        );
+
+        // The button goes down at the start and comes up at the end of the path
+        let actions = fake.actions();
+        let down = actions
+            .iter()
+            .position(|a| *a == InputAction::ButtonDown(MouseButton::Left))
+            .unwrap();
+        assert_eq!(
+            actions[..down]
+                .iter()
+                .rev()
+                .find(|a| matches!(a, InputAction::Move { .. })),
+            Some(&InputAction::Move { x: 100, y: 100 })
+        );
+        assert_eq!(fake.cursor_path().last(), Some(&(400, 250)));
+        assert!(fake.cursor_path().len() > 20);
+    }
+
+    #[tokio::test]
+    async fn cancelled_drag_releases_the_button_and_modifiers() {
+        let fake = FakeBackend::new();
+        let ctx = tool_context(&fake);
+        let cancel = ctx.cancel.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(400));
+            cancel.cancel();
+        });
+
+        // Cancelled while the button is held
+        let error = MouseDragTool
+            .execute(
+                json!({
+                    "from_x": 0, "from_y": 0, "to_x": 50, "to_y": 50,
+                    "modifiers": ["shift"], "hold_ms": 3000
+                }),
+                &ctx,
+            )
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "Cancelled");
+        assert_eq!(
+            fake.events(),
+            [
+                InputAction::KeyDown("shift".to_string()),
+                InputAction::ButtonDown(MouseButton::Left),
+                InputAction::ButtonUp(MouseButton::Left),
+                InputAction::KeyUp("shift".to_string()),
+            ]
+        );
+        assert_ne!(fake.cursor_path().last(), Some(&(50, 50)));
+    }
+
+    #[tokio::test]
+    async fn missing_coordinates_are_rejected() {
+        let fake = FakeBackend::new();
+        let ctx = tool_context(&fake);
+
+        let error = MouseMoveTool
+            .execute(json!({ "y": 2 }), &ctx)
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Missing x");
+        let error = MouseClickTool
+            .execute(json!({ "x": 1 }), &ctx)
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Missing y");
+        assert!(MouseDoubleClickTool.execute(json!({}), &ctx).await.is_err());
+        let error = MouseDragTool
+            .execute(json!({ "from_x": 1, "from_y": 2, "to_x": 3 }), &ctx)
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Missing to_y");
+        assert!(fake.actions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn backend_failures_are_reported() {
+        let fake = FakeBackend::new();
+        fake.fail_with("display gone");
+
+        let error = MouseClickTool
+            .execute(json!({ "x": 1, "y": 2 }), &tool_context(&fake))
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "display gone");
+    }
+
+    #[tokio::test]
+    async fn cancelled_run_sends_no_input() {
+        let fake = FakeBackend::new();
+        let ctx = tool_context(&fake);
+        ctx.cancel.cancel();
+
+        let error = MouseMoveTool
+            .execute(json!({ "x": 1, "y": 2 }), &ctx)
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "Cancelled");
+        assert!(fake.actions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn dry_run_previews_without_input() {
+        let fake = FakeBackend::new();
+        let ctx = ToolContext {
+            dry_run: true,
+            ..tool_context(&fake)
+        };
+
+        let result = MouseDoubleClickTool
+            .execute(json!({ "x": 5, "y": 6 }), &ctx)
+            .await
+            .unwrap();
+
+        assert!(result.output.starts_with("Dry run, not performed"));
+        assert_eq!(result.effects.len(), 3);
+
+        let result = MouseDragTool
+            .execute(
+                json!({ "from_x": 1, "from_y": 2, "to_x": 3, "to_y": 4 }),
+                &ctx,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result.output,
+            "Dry run, not performed: drag left from (1, 2) to (3, 4)"
+        );
+        assert!(result.effects.iter().any(|event| matches!(
+            event,
+            OverlayEvent::Drag {
+                to_x: 3,
+                to_y: 4,
+                ..
+            }
+        )));
+        assert!(fake.actions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn performed_drags_show_their_path() {
+        let fake = FakeBackend::new();
+        let ctx = tool_context(&fake);
+
+        let result = MouseDragTool
+            .execute(
+                json!({ "from_x": 1, "from_y": 2, "to_x": 30, "to_y": 40, "hold_ms": 0, "duration_ms": 50 }),
+                &ctx,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.output, "Dragged left from (1, 2) to (30, 40)");
+        assert!(matches!(
+            result.effects.as_slice(),
+            [
+                OverlayEvent::CursorMove { x: 1, y: 2 },
+                OverlayEvent::MouseDown { x: 1, y: 2, .. },
+                OverlayEvent::Drag {
+                    from_x: 1,
+                    from_y: 2,
+                    to_x: 30,
+                    to_y: 40,
+                    ..
+                },
+                OverlayEvent::CursorMove { x: 30, y: 40 },
+                OverlayEvent::MouseUp { x: 30, y: 40, .. },
+            ]
+        ));
+        assert!(!fake.actions().is_empty());
+
+        // Holding and releasing on their own show the button state
+        let result = MouseDownTool
+            .execute(json!({ "x": 5, "y": 6 }), &ctx)
+            .await
+            .unwrap();
+        assert!(matches!(
+            result.effects.last(),
+            Some(OverlayEvent::MouseDown { x: 5, y: 6, .. })
+        ));
+        let result = MouseUpTool
+            .execute(json!({ "x": 7, "y": 8 }), &ctx)
+            .await
+            .unwrap();
+        assert!(matches!(
+            result.effects.last(),
+            Some(OverlayEvent::MouseUp { x: 7, y: 8, .. })
+        ));
     }
 }
@@ -0,0 +1,169 @@
+// Plan tool - reports progress on the plan (plan mode only)
+
+use super::{Tool, ToolCategory};
+use crate::llm::runner::plan::PlanStepStatus;
+use crate::llm::types::{ToolContext, ToolResult};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+/// Update plan tool - marks a plan step done, failed or revised
+pub struct UpdatePlanTool;
+
+#[async_trait]
+impl Tool for UpdatePlanTool {
+    fn name(&self) -> &str {
+        "update_plan"
+    }
+
+    fn description(&self) -> &str {
+        "Mark a step of the plan as done, failed or revised"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "step": { "type": "integer", "description": "Step number" },
+                "status": {
+                    "type": "string",
+                    "enum": ["done", "failed", "revised"]
+                },
+                "note": { "type": "string", "description": "What happened" },
+                "description": {
+                    "type": "string",
+                    "description": "New wording of the step (required for revised)"
+                }
+            },
+            "required": ["step", "status"]
+        })
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Plan
+    }
+
+    async fn execute(&self, params: Value, ctx: &ToolContext) -> anyhow::Result<ToolResult> {
+        let Some(plan) = &ctx.plan else {
+            return Ok(ToolResult::error("This run has no plan"));
+        };
+        let step = params["step"]
+            .as_u64()
+            .ok_or_else(|| anyhow::anyhow!("Missing step"))? as usize;
+        let status = match params["status"].as_str() {
+            Some("done") => PlanStepStatus::Done,
+            Some("failed") => PlanStepStatus::Failed,
+            Some("revised") => PlanStepStatus::Revised,
+            _ => return Err(anyhow::anyhow!("status must be done, failed or revised")),
+        };
+        let note = params["note"].as_str().map(String::from);
+        let description = params["description"].as_str().map(String::from);
+
+        let mut plan = plan.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = plan.update(step, status, note, description) {
+            return Ok(ToolResult::error(e.to_string()));
+        }
+
+        let hint = if status == PlanStepStatus::Failed {
+            "\nRevise the step or call replan."
+        } else if plan.is_complete() {
+            "\nAll steps are done."
+        } else {
+            ""
+        };
+        Ok(ToolResult::success(format!(
+            "Plan:\n{}{}",
+            plan.render(),
+            hint
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::fake::{tool_context, FakeBackend};
+    use crate::llm::runner::plan::{Plan, SharedPlan};
+    use std::sync::{Arc, Mutex};
+
+    fn plan_context() -> (ToolContext, SharedPlan) {
+        let plan = Arc::new(Mutex::new(Plan::new(
+            "Save the file",
+            vec!["Press ctrl+s".to_string(), "Click Save".to_string()],
+        )));
+        let ctx = ToolContext {
+            plan: Some(plan.clone()),
+            ..tool_context(&FakeBackend::new())
+        };
+        (ctx, plan)
+    }
+
+    #[tokio::test]
+    async fn marks_steps_and_reports_completion() {
+        let (ctx, plan) = plan_context();
+
+        let result = UpdatePlanTool
+            .execute(json!({ "step": 1, "status": "done" }), &ctx)
+            .await
+            .unwrap();
+        assert!(result.output.contains("1. [done] Press ctrl+s"));
+        assert!(!result.output.contains("All steps are done"));
+
+        let result = UpdatePlanTool
+            .execute(
+                json!({ "step": 2, "status": "done", "note": "saved" }),
+                &ctx,
+            )
+            .await
+            .unwrap();
+        assert!(result.output.contains("2. [done] Click Save - saved"));
+        assert!(result.output.ends_with("All steps are done."));
+        assert!(plan.lock().unwrap().is_complete());
+    }
+
+    #[tokio::test]
+    async fn failed_step_asks_for_a_revision() {
+        let (ctx, _) = plan_context();
+
+        let result = UpdatePlanTool
+            .execute(json!({ "step": 2, "status": "failed" }), &ctx)
+            .await
+            .unwrap();
+
+        assert!(result.output.ends_with("Revise the step or call replan."));
+    }
+
+    #[tokio::test]
+    async fn invalid_updates_are_reported() {
+        let (ctx, _) = plan_context();
+
+        let result = UpdatePlanTool
+            .execute(json!({ "step": 9, "status": "done" }), &ctx)
+            .await
+            .unwrap();
+        assert_eq!(result.error.as_deref(), Some("The plan has no step 9"));
+
+        let result = UpdatePlanTool
+            .execute(json!({ "step": 1, "status": "revised" }), &ctx)
+            .await
+            .unwrap();
+        assert!(!result.success);
+
+        let error = UpdatePlanTool
+            .execute(json!({ "step": 1, "status": "skipped" }), &ctx)
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "status must be done, failed or revised");
+    }
+
+    #[tokio::test]
+    async fn run_without_plan_is_an_error() {
+        let ctx = tool_context(&FakeBackend::new());
+
+        let result = UpdatePlanTool
+            .execute(json!({ "step": 1, "status": "done" }), &ctx)
+            .await
+            .unwrap();
+
+        assert_eq!(result.error.as_deref(), Some("This run has no plan"));
+    }
+}
@@ -1,71 +1,203 @@
-// Tool registry - manages all available tools
+// Tool registry - the one list of tools the agent can use
+//
+// Execution, the tool definitions sent to the API and the tool list in the
+// system prompt all come from here. Each run gets a ToolSet filtered by its
+// tool profile, its mode and the platform of the desktop it runs on.
 
 use super::{
-    Tool, MouseMoveTool, MouseClickTool, MouseDoubleClickTool,
-    KeyboardTypeTool, KeyboardPressTool, ScreenUpdateTool, WaitTool, ScrollTool,
+    KeyboardPressTool, KeyboardTypeTool, MouseClickTool, MouseDoubleClickTool, MouseDownTool,
+    MouseDragTool, MouseMoveTool, MouseUpTool, ScreenUpdateTool, ScrollTool, SideEffect, Tool,
+    ToolCategory, UpdatePlanTool, WaitTool,
 };
+use crate::commands::config::ToolProfile;
+use crate::llm::agents::{GuideSearchAgentTool, PlannerAgentTool};
 use crate::llm::types::ToolDef;
-use std::collections::HashMap;
+use serde_json::Value;
 use std::sync::Arc;
 
-/// Registry of all available tools
-#[allow(dead_code)]
+/// Registry of all tools, in the order they are offered
 pub struct ToolRegistry {
-    tools: HashMap<String, Arc<dyn Tool>>,
+    tools: Vec<Arc<dyn Tool>>,
 }
 
 impl ToolRegistry {
-    /// Create a new registry with all primitive tools
     pub fn new() -> Self {
-        let mut tools: HashMap<String, Arc<dyn Tool>> = HashMap::new();
+        Self {
+            tools: vec![
+                Arc::new(MouseMoveTool),
+                Arc::new(MouseClickTool),
+                Arc::new(MouseDoubleClickTool),
+                Arc::new(MouseDragTool),
+                Arc::new(MouseDownTool),
+                Arc::new(MouseUpTool),
+                Arc::new(KeyboardTypeTool),
+                Arc::new(KeyboardPressTool),
+                Arc::new(ScrollTool),
+                Arc::new(WaitTool),
+                Arc::new(ScreenUpdateTool),
+                Arc::new(GuideSearchAgentTool),
+                Arc::new(UpdatePlanTool),
+                Arc::new(PlannerAgentTool),
+            ],
+        }
+    }
+
+    /// Get a tool by name
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn Tool>> {
+        self.tools.iter().find(|tool| tool.name() == name)
+    }
+
+    /// Tools for one run
+    /// `plan` offers the plan mode tools, `os` is the desktop's operating system
+    pub fn tool_set(&self, profile: &ToolProfile, plan: bool, os: &str) -> ToolSet {
+        let tools = self
+            .tools
+            .iter()
+            .filter(|tool| tool.platform().supports(os))
+            .filter(|tool| plan || tool.category() != ToolCategory::Plan)
+            .filter(|tool| profile_allows(profile, tool.as_ref()))
+            .cloned()
+            .collect();
+        ToolSet { tools }
+    }
+}
+
+impl Default for ToolRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        // Mouse tools
-        tools.insert("mouse_move".to_string(), Arc::new(MouseMoveTool));
-        tools.insert("mouse_click".to_string(), Arc::new(MouseClickTool));
-        tools.insert("mouse_double_click".to_string(), Arc::new(MouseDoubleClickTool));
+fn profile_allows(profile: &ToolProfile, tool: &dyn Tool) -> bool {
+    (profile.categories.is_empty() || profile.categories.contains(&tool.category()))
+        && !profile.disabled.iter().any(|name| name == tool.name())
+        && (profile.allow_input || tool.side_effect() != SideEffect::Input)
+}
 
-        // Keyboard tools
-        tools.insert("keyboard_type".to_string(), Arc::new(KeyboardTypeTool));
-        tools.insert("keyboard_press".to_string(), Arc::new(KeyboardPressTool));
+// Global tool registry instance
+lazy_static::lazy_static! {
+    pub static ref TOOL_REGISTRY: ToolRegistry = ToolRegistry::new();
+}
 
-        // Screen tools
-        tools.insert("get_screen_update".to_string(), Arc::new(ScreenUpdateTool));
-        tools.insert("wait".to_string(), Arc::new(WaitTool));
+/// Whether a tool sends mouse or keyboard input
+pub fn is_input_tool(name: &str) -> bool {
+    TOOL_REGISTRY
+        .get(name)
+        .is_some_and(|tool| tool.side_effect() == SideEffect::Input)
+}
 
-        // Scroll tool
-        tools.insert("scroll".to_string(), Arc::new(ScrollTool));
+/// Tools offered in one run
+#[derive(Clone, Default)]
+pub struct ToolSet {
+    tools: Vec<Arc<dyn Tool>>,
+}
 
-        Self { tools }
+impl ToolSet {
+    /// Get a tool by name
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn Tool>> {
+        self.tools.iter().find(|tool| tool.name() == name)
     }
 
-    /// Register a new tool
-    pub fn register(&mut self, tool: Arc<dyn Tool>) {
-        self.tools.insert(tool.name().to_string(), tool);
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
     }
 
-    /// Get a tool by name
-    pub fn get(&self, name: &str) -> Option<Arc<dyn Tool>> {
-        self.tools.get(name).cloned()
+    /// Tool names in order
+    pub fn names(&self) -> Vec<&str> {
+        self.tools.iter().map(|tool| tool.name()).collect()
     }
 
-    /// Get all tool definitions for API
-    pub fn get_tool_defs(&self) -> Vec<ToolDef> {
-        self.tools.values().map(|t| t.to_tool_def()).collect()
+    /// Tool definitions for the API
+    pub fn tool_defs(&self) -> Vec<ToolDef> {
+        self.tools.iter().map(|tool| tool.to_tool_def()).collect()
     }
 
-    /// List all tool names
-    pub fn list_names(&self) -> Vec<&str> {
-        self.tools.keys().map(|s| s.as_str()).collect()
+    /// One line per tool for the system prompt (`- name(params): description`)
+    pub fn prompt_list(&self) -> String {
+        self.tools
+            .iter()
+            .map(|tool| {
+                format!(
+                    "- {}({}): {}\n",
+                    tool.name(),
+                    signature(&tool.parameters()),
+                    tool.description()
+                )
+            })
+            .collect()
     }
 }
 
-impl Default for ToolRegistry {
-    fn default() -> Self {
-        Self::new()
-    }
+/// Parameter list of a schema (`x, y, button: "left"|"right"`)
+fn signature(schema: &Value) -> String {
+    let Some(properties) = schema["properties"].as_object() else {
+        return String::new();
+    };
+    properties
+        .iter()
+        .map(|(name, property)| match property["enum"].as_array() {
+            Some(values) => {
+                let values = values
+                    .iter()
+                    .map(|value| value.to_string())
+                    .collect::<Vec<_>>()
+                    .join("|");
+                format!("{}: {}", name, values)
+            }
+            None => name.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
-// Global tool registry instance
-lazy_static::lazy_static! {
-    pub static ref TOOL_REGISTRY: ToolRegistry = ToolRegistry::new();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tool_set_follows_profile_mode_and_platform() {
+        let full = ToolProfile::default();
+        let set = TOOL_REGISTRY.tool_set(&full, false, "windows");
+        assert!(set.contains("mouse_click"));
+        assert!(set.contains("guide_search"));
+        assert!(!set.contains("update_plan"));
+        assert!(TOOL_REGISTRY
+            .tool_set(&full, true, "windows")
+            .contains("replan"));
+
+        // Input tools need a native input backend
+        assert!(TOOL_REGISTRY
+            .tool_set(&full, false, "linux")
+            .contains("mouse_click"));
+        let set = TOOL_REGISTRY.tool_set(&full, false, "macos");
+        assert!(!set.contains("mouse_click"));
+        assert!(set.contains("wait"));
+
+        let observe = ToolProfile {
+            allow_input: false,
+            disabled: vec!["guide_search".to_string()],
+            ..ToolProfile::default()
+        };
+        assert_eq!(
+            TOOL_REGISTRY.tool_set(&observe, false, "windows").names(),
+            ["wait", "get_screen_update"]
+        );
+
+        let keyboard = ToolProfile {
+            categories: vec![ToolCategory::Keyboard],
+            ..ToolProfile::default()
+        };
+        assert_eq!(
+            TOOL_REGISTRY.tool_set(&keyboard, false, "windows").names(),
+            ["keyboard_type", "keyboard_press"]
+        );
+    }
+
+    #[test]
+    fn prompt_list_shows_parameters() {
+        let set = TOOL_REGISTRY.tool_set(&ToolProfile::default(), false, "windows");
+        let list = set.prompt_list();
+        assert!(list.contains("- get_screen_update(): "));
+        assert!(list.contains("direction: \"up\"|\"down\"|\"left\"|\"right\""));
+    }
 }
@@ -0,0 +1,347 @@
+// Tool argument validation - checks call arguments against the tool's schema
+//
+// Arguments arrive as a JSON string from the model. They are parsed, checked
+// against the JSON schema from `Tool::parameters()` (required fields, types,
+// enums, bounds, nested objects and arrays) and completed with the schema's
+// defaults. Problems go back to the model as a structured tool error instead
+// of running the tool with wrong or empty parameters.
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::fmt;
+
+/// One argument that doesn't match the schema
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ArgumentProblem {
+    /// Path of the argument (`button`, `keys[1]`; empty for the whole object)
+    pub field: String,
+    pub message: String,
+}
+
+/// Why tool-call arguments were rejected
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgumentError {
+    /// The arguments are not valid JSON
+    Malformed(String),
+    /// The arguments don't match the schema
+    Invalid(Vec<ArgumentProblem>),
+}
+
+impl fmt::Display for ArgumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed(message) => write!(f, "arguments are not valid JSON ({})", message),
+            Self::Invalid(problems) => {
+                let problems = problems
+                    .iter()
+                    .map(|p| match p.field.as_str() {
+                        "" => p.message.clone(),
+                        field => format!("'{}' {}", field, p.message),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("; ");
+                write!(f, "{}", problems)
+            }
+        }
+    }
+}
+
+impl ArgumentError {
+    /// Tool error for the model: what is wrong and the schema to follow
+    pub fn to_tool_error(&self, tool: &str, schema: &Value) -> String {
+        let (kind, problems) = match self {
+            Self::Malformed(message) => (
+                "malformed_arguments",
+                vec![ArgumentProblem {
+                    field: String::new(),
+                    message: format!("not valid JSON: {}", message),
+                }],
+            ),
+            Self::Invalid(problems) => ("invalid_arguments", problems.clone()),
+        };
+        json!({
+            "error": kind,
+            "tool": tool,
+            "problems": problems,
+            "schema": schema,
+            "hint": format!(
+                "The tool was not run. Call {} again with a JSON object that matches the schema.",
+                tool
+            ),
+        })
+        .to_string()
+    }
+}
+
+/// Parse a tool call's argument string and validate it
+/// An empty string counts as no arguments
+pub fn parse_arguments(raw: &str, schema: &Value) -> Result<Value, ArgumentError> {
+    let params = if raw.trim().is_empty() {
+        json!({})
+    } else {
+        serde_json::from_str(raw).map_err(|e| ArgumentError::Malformed(e.to_string()))?
+    };
+    validate_arguments(params, schema)
+}
+
+/// Validate arguments against a schema and fill in its defaults
+pub fn validate_arguments(mut params: Value, schema: &Value) -> Result<Value, ArgumentError> {
+    if !params.is_object() {
+        return Err(ArgumentError::Invalid(vec![ArgumentProblem {
+            field: String::new(),
+            message: format!(
+                "arguments must be a JSON object, got {}",
+                type_name(&params)
+            ),
+        }]));
+    }
+
+    let mut problems = Vec::new();
+    check_value(&mut params, schema, "", &mut problems);
+    if problems.is_empty() {
+        Ok(params)
+    } else {
+        Err(ArgumentError::Invalid(problems))
+    }
+}
+
+fn check_value(value: &mut Value, schema: &Value, path: &str, problems: &mut Vec<ArgumentProblem>) {
+    let mut problem = |message: String| {
+        problems.push(ArgumentProblem {
+            field: path.to_string(),
+            message,
+        })
+    };
+
+    // "type" is one type name or a list of them
+    let expected: Vec<&str> = match &schema["type"] {
+        Value::String(name) => vec![name.as_str()],
+        Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    if !expected.is_empty() && !expected.iter().any(|name| has_type(value, name)) {
+        let expected = expected
+            .iter()
+            .map(|name| article(name))
+            .collect::<Vec<_>>()
+            .join(" or ");
+        problem(format!("must be {}, got {}", expected, type_name(value)));
+        return;
+    }
+
+    if let Some(allowed) = schema["enum"].as_array() {
+        if !allowed.contains(value) {
+            let allowed = allowed
+                .iter()
+                .map(Value::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            problem(format!("must be one of {}, got {}", allowed, value));
+            return;
+        }
+    }
+
+    if let Some(number) = value.as_f64() {
+        if let Some(min) = schema["minimum"].as_f64().filter(|min| number < *min) {
+            problem(format!("must be at least {}", min));
+        }
+        if let Some(max) = schema["maximum"].as_f64().filter(|max| number > *max) {
+            problem(format!("must be at most {}", max));
+        }
+    }
+
+    match value {
+        Value::Object(object) => check_object(object, schema, path, problems),
+        Value::Array(items) => {
+            if let Some(min) = schema["minItems"]
+                .as_u64()
+                .filter(|min| (items.len() as u64) < *min)
+            {
+                problem(format!("must have at least {} items", min));
+            }
+            if schema["items"].is_object() {
+                for (index, item) in items.iter_mut().enumerate() {
+                    check_value(
+                        item,
+                        &schema["items"],
+                        &format!("{}[{}]", path, index),
+                        problems,
+                    );
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn check_object(
+    object: &mut Map<String, Value>,
+    schema: &Value,
+    path: &str,
+    problems: &mut Vec<ArgumentProblem>,
+) {
+    let field_path = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", path, key)
+        }
+    };
+    let empty = Map::new();
+    let properties = schema["properties"].as_object().unwrap_or(&empty);
+
+    for field in schema["required"].as_array().into_iter().flatten() {
+        if let Some(field) = field.as_str().filter(|field| !object.contains_key(*field)) {
+            problems.push(ArgumentProblem {
+                field: field_path(field),
+                message: "is required".to_string(),
+            });
+        }
+    }
+
+    if schema["additionalProperties"] == Value::Bool(false) {
+        for key in object.keys().filter(|key| !properties.contains_key(*key)) {
+            problems.push(ArgumentProblem {
+                field: field_path(key),
+                message: "is not a known argument".to_string(),
+            });
+        }
+    }
+
+    for (key, property) in properties {
+        match object.get_mut(key) {
+            Some(value) => check_value(value, property, &field_path(key), problems),
+            None => {
+                if let Some(default) = property.get("default") {
+                    object.insert(key.clone(), default.clone());
+                }
+            }
+        }
+    }
+}
+
+fn has_type(value: &Value, schema_type: &str) -> bool {
+    match schema_type {
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(n) if n.is_f64() => "a decimal number",
+        Value::Number(_) => "an integer",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+fn article(schema_type: &str) -> String {
+    match schema_type {
+        "integer" | "array" | "object" => format!("an {}", schema_type),
+        other => format!("a {}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn click_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "x": { "type": "integer" },
+                "y": { "type": "integer" },
+                "button": { "type": "string", "enum": ["left", "right"], "default": "left" },
+                "keys": { "type": "array", "items": { "type": "string" } }
+            },
+            "required": ["x", "y"]
+        })
+    }
+
+    #[test]
+    fn applies_defaults() {
+        let params = parse_arguments(r#"{"x": 10, "y": 20}"#, &click_schema()).unwrap();
+        assert_eq!(params, json!({"x": 10, "y": 20, "button": "left"}));
+
+        // No arguments at all is an empty object
+        let schema = json!({"type": "object", "properties": {}});
+        assert_eq!(parse_arguments("", &schema).unwrap(), json!({}));
+    }
+
+    #[test]
+    fn collects_every_problem() {
+        let error = parse_arguments(
+            r#"{"x": "10", "button": "middle", "keys": ["ctrl", 3]}"#,
+            &click_schema(),
+        )
+        .unwrap_err();
+
+        let ArgumentError::Invalid(problems) = &error else {
+            panic!("expected invalid arguments, got {:?}", error);
+        };
+        let mut fields: Vec<&str> = problems.iter().map(|p| p.field.as_str()).collect();
+        fields.sort();
+        assert_eq!(fields, ["button", "keys[1]", "x", "y"]);
+
+        let message = error.to_string();
+        for part in [
+            "'y' is required",
+            "'x' must be an integer, got a string",
+            "'button' must be one of \"left\", \"right\", got \"middle\"",
+            "'keys[1]' must be a string, got an integer",
+        ] {
+            assert!(message.contains(part), "{} missing in {}", part, message);
+        }
+    }
+
+    #[test]
+    fn accepts_any_of_several_types() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "keys": { "type": ["string", "array"], "items": { "type": "string" } }
+            }
+        });
+        assert!(validate_arguments(json!({"keys": "ctrl+c"}), &schema).is_ok());
+        assert!(validate_arguments(json!({"keys": ["ctrl", "c"]}), &schema).is_ok());
+
+        let error = validate_arguments(json!({"keys": 3}), &schema).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "'keys' must be a string or an array, got an integer"
+        );
+        let error = validate_arguments(json!({"keys": ["ctrl", 3]}), &schema).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "'keys[1]' must be a string, got an integer"
+        );
+    }
+
+    #[test]
+    fn reports_malformed_json_as_tool_error() {
+        let error = parse_arguments(r#"{"x": 10, "y": "#, &click_schema()).unwrap_err();
+        assert!(matches!(error, ArgumentError::Malformed(_)));
+
+        let report: Value =
+            serde_json::from_str(&error.to_tool_error("mouse_click", &click_schema())).unwrap();
+        assert_eq!(report["error"], "malformed_arguments");
+        assert_eq!(report["tool"], "mouse_click");
+        assert_eq!(report["schema"], click_schema());
+
+        let error = parse_arguments("[1, 2]", &click_schema()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "arguments must be a JSON object, got an array"
+        );
+    }
+}
@@ -1,6 +1,6 @@
 // Screen tools - screen updates and waiting
 
-use super::Tool;
+use super::{Tool, ToolCategory};
 use crate::llm::types::{ToolContext, ToolResult};
 use async_trait::async_trait;
 use serde_json::{json, Value};
@@ -25,6 +25,10 @@ impl Tool for ScreenUpdateTool {
         })
     }
 
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Screen
+    }
+
     async fn execute(&self, _params: Value, _ctx: &ToolContext) -> anyhow::Result<ToolResult> {
         // This is a signal to the runner to capture new screen
         Ok(ToolResult::success("Screen update requested"))
@@ -54,9 +58,69 @@ impl Tool for WaitTool {
         })
     }
 
-    async fn execute(&self, params: Value, _ctx: &ToolContext) -> anyhow::Result<ToolResult> {
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Screen
+    }
+
+    async fn execute(&self, params: Value, ctx: &ToolContext) -> anyhow::Result<ToolResult> {
         let ms = params["ms"].as_u64().unwrap_or(1000);
-        tokio::time::sleep(tokio::time::Duration::from_millis(ms)).await;
+        ctx.cancel
+            .sleep(tokio::time::Duration::from_millis(ms))
+            .await?;
         Ok(ToolResult::success(format!("Waited {}ms", ms)))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::fake::{tool_context, FakeBackend};
+    use std::time::{Duration, Instant};
+
+    #[tokio::test]
+    async fn screen_update_only_signals_the_runner() {
+        let fake = FakeBackend::new();
+
+        let result = ScreenUpdateTool
+            .execute(json!({}), &tool_context(&fake))
+            .await
+            .unwrap();
+
+        assert_eq!(result.output, "Screen update requested");
+        assert!(fake.actions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn wait_sleeps_for_the_given_time() {
+        let fake = FakeBackend::new();
+        let started = Instant::now();
+
+        let result = WaitTool
+            .execute(json!({ "ms": 60 }), &tool_context(&fake))
+            .await
+            .unwrap();
+
+        assert_eq!(result.output, "Waited 60ms");
+        assert!(started.elapsed() >= Duration::from_millis(60));
+    }
+
+    #[tokio::test]
+    async fn wait_stops_when_cancelled() {
+        let fake = FakeBackend::new();
+        let ctx = tool_context(&fake);
+        let cancel = ctx.cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            cancel.cancel();
+        });
+        let started = Instant::now();
+
+        let error = WaitTool
+            .execute(json!({ "ms": 10_000 }), &ctx)
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "Cancelled");
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
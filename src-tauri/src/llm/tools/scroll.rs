@@ -1,8 +1,9 @@
-// Scroll tool
-// Note: Scroll is not yet implemented in input/mouse.rs
-// This is a placeholder that will be connected when scroll is implemented
+// Scroll tool - wheel scrolling, optionally over a point
 
-use super::Tool;
+use super::{SideEffect, Tool, ToolCategory, ToolPlatform};
+use crate::commands::overlay::OverlayEvent;
+use crate::input::backend::WHEEL_NOTCH;
+use crate::input::mouse::ScrollUnit;
 use crate::llm::types::{ToolContext, ToolResult};
 use async_trait::async_trait;
 use serde_json::{json, Value};
@@ -17,7 +18,7 @@ impl Tool for ScrollTool {
     }
 
     fn description(&self) -> &str {
-        "Scroll in specified direction"
+        "Scroll in specified direction, over (x, y) if given"
     }
 
     fn parameters(&self) -> Value {
@@ -28,23 +29,252 @@ impl Tool for ScrollTool {
                     "type": "string",
                     "enum": ["up", "down", "left", "right"]
                 },
-                "amount": { "type": "integer", "default": 3 }
+                "amount": {
+                    "type": "integer",
+                    "description": format!(
+                        "Notches (at most {}) or pixels (at most {})",
+                        ScrollUnit::Notches.max_amount(),
+                        ScrollUnit::Pixels.max_amount()
+                    ),
+                    "minimum": 1,
+                    "maximum": ScrollUnit::Pixels.max_amount(),
+                    "default": 3
+                },
+                "unit": {
+                    "type": "string",
+                    "enum": ["notches", "pixels"],
+                    "default": "notches"
+                },
+                "x": { "type": "integer", "description": "X coordinate to scroll over" },
+                "y": { "type": "integer", "description": "Y coordinate to scroll over" },
+                "smooth": {
+                    "type": "boolean",
+                    "description": "Scroll in small steps",
+                    "default": false
+                }
             },
             "required": ["direction"]
         })
     }
 
-    async fn execute(&self, params: Value, _ctx: &ToolContext) -> anyhow::Result<ToolResult> {
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Mouse
+    }
+
+    fn side_effect(&self) -> SideEffect {
+        SideEffect::Input
+    }
+
+    fn platform(&self) -> ToolPlatform {
+        ToolPlatform::Desktop
+    }
+
+    async fn execute(&self, params: Value, ctx: &ToolContext) -> anyhow::Result<ToolResult> {
+        ctx.cancel.check()?;
         let direction = params["direction"]
             .as_str()
             .ok_or_else(|| anyhow::anyhow!("Missing direction"))?;
-        let amount = params["amount"].as_i64().unwrap_or(3) as i32;
-
-        // TODO: Implement scroll in input/mouse.rs
-        // For now, return success with a note
-        Ok(ToolResult::success(format!(
-            "Scroll {} by {} (not yet implemented)",
-            direction, amount
-        )))
+        let amount = params["amount"].as_i64().unwrap_or(3);
+        let unit_str = params["unit"].as_str().unwrap_or("notches");
+        let smooth = params["smooth"].as_bool().unwrap_or(false);
+
+        let (sign_x, sign_y) = match direction {
+            "up" => (0, -1),
+            "down" => (0, 1),
+            "left" => (-1, 0),
+            "right" => (1, 0),
+            _ => return Err(anyhow::anyhow!("Unknown direction: {}", direction)),
+        };
+        let unit = match unit_str {
+            "notches" => ScrollUnit::Notches,
+            "pixels" => ScrollUnit::Pixels,
+            _ => return Err(anyhow::anyhow!("Unknown unit: {}", unit_str)),
+        };
+        let mut delta = unit.wheel_delta(amount)?;
+        let target = match (params["x"].as_i64(), params["y"].as_i64()) {
+            (Some(x), Some(y)) => Some((x as i32, y as i32)),
+            (None, None) => None,
+            (Some(_), None) => return Err(anyhow::anyhow!("Missing y")),
+            (None, Some(_)) => return Err(anyhow::anyhow!("Missing x")),
+        };
+
+        let mut summary = format!("{} by {} {}", direction, amount, unit_str);
+        if let Some((x, y)) = target {
+            summary.push_str(&format!(" at ({}, {})", x, y));
+        }
+        // Less than a notch is dropped by some backends (X11 only has whole notches)
+        if delta < WHEEL_NOTCH {
+            delta = WHEEL_NOTCH;
+            summary.push_str(" (rounded up to one notch)");
+        }
+
+        let effects: Vec<_> = target
+            .map(|(x, y)| OverlayEvent::CursorMove { x, y })
+            .into_iter()
+            .collect();
+        if ctx.dry_run {
+            return Ok(ToolResult::planned(format!("scroll {}", summary), effects));
+        }
+
+        let input = ctx.input_backend()?;
+        crate::input::mouse::scroll(
+            input.as_ref(),
+            target,
+            sign_x * delta,
+            sign_y * delta,
+            smooth,
+            &ctx.cancel,
+        )?;
+
+        Ok(ToolResult::success(format!("Scrolled {}", summary)).with_effects(effects))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::fake::{tool_context, FakeBackend, InputAction};
+
+    #[tokio::test]
+    async fn scrolls_by_notches_where_the_cursor_is() {
+        let fake = FakeBackend::new();
+
+        let result = ScrollTool
+            .execute(json!({ "direction": "down" }), &tool_context(&fake))
+            .await
+            .unwrap();
+
+        assert_eq!(result.output, "Scrolled down by 3 notches");
+        assert_eq!(fake.actions(), [InputAction::Scroll { dx: 0, dy: 360 }]);
+    }
+
+    #[tokio::test]
+    async fn smooth_pixel_scroll_hovers_the_target_first() {
+        let fake = FakeBackend::new();
+
+        let result = ScrollTool
+            .execute(
+                json!({ "direction": "left", "amount": 100, "unit": "pixels", "x": 300, "y": 200, "smooth": true }),
+                &tool_context(&fake),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.output, "Scrolled left by 100 pixels at (300, 200)");
+        assert_eq!(fake.cursor_path().last(), Some(&(300, 200)));
+        let steps: Vec<_> = fake
+            .events()
+            .into_iter()
+            .map(|event| match event {
+                InputAction::Scroll { dx, dy } => (dx, dy),
+                other => panic!("unexpected {:?}", other),
+            })
+            .collect();
+        // 100 pixels = 2 notches, sent in quarter notches
+        assert_eq!(steps, [(-30, 0); 8]);
+    }
+
+    #[tokio::test]
+    async fn less_than_a_notch_is_rounded_up() {
+        let fake = FakeBackend::new();
+
+        let result = ScrollTool
+            .execute(
+                json!({ "direction": "up", "amount": 20, "unit": "pixels" }),
+                &tool_context(&fake),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result.output,
+            "Scrolled up by 20 pixels (rounded up to one notch)"
+        );
+        assert_eq!(fake.actions(), [InputAction::Scroll { dx: 0, dy: -120 }]);
+    }
+
+    #[tokio::test]
+    async fn invalid_arguments_are_errors() {
+        let fake = FakeBackend::new();
+        let ctx = tool_context(&fake);
+
+        for (params, message) in [
+            (
+                json!({ "direction": "sideways" }),
+                "Unknown direction: sideways",
+            ),
+            (
+                json!({ "direction": "up", "unit": "lines" }),
+                "Unknown unit: lines",
+            ),
+            (
+                json!({ "direction": "up", "amount": 0 }),
+                "Amount must be between 1 and 100",
+            ),
+            (
+                json!({ "direction": "up", "amount": 101 }),
+                "Amount must be between 1 and 100",
+            ),
+            (
+                json!({ "direction": "up", "amount": 3_000_000_000u64 }),
+                "Amount must be between 1 and 100",
+            ),
+            (
+                json!({ "direction": "up", "amount": 5001, "unit": "pixels" }),
+                "Amount must be between 1 and 5000",
+            ),
+            (json!({ "direction": "up", "x": 10 }), "Missing y"),
+        ] {
+            let error = ScrollTool.execute(params, &ctx).await.unwrap_err();
+            assert_eq!(error.to_string(), message);
+        }
+        assert!(fake.actions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn backend_failure_is_an_error() {
+        let fake = FakeBackend::new();
+        fake.fail_with("display gone");
+
+        let error = ScrollTool
+            .execute(json!({ "direction": "up" }), &tool_context(&fake))
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "display gone");
+    }
+
+    #[tokio::test]
+    async fn missing_direction_is_rejected() {
+        let fake = FakeBackend::new();
+
+        let error = ScrollTool
+            .execute(json!({ "amount": 3 }), &tool_context(&fake))
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "Missing direction");
+        assert!(fake.actions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn dry_run_describes_the_scroll() {
+        let fake = FakeBackend::new();
+        let ctx = ToolContext {
+            dry_run: true,
+            ..tool_context(&fake)
+        };
+
+        let result = ScrollTool
+            .execute(json!({ "direction": "down", "x": 5, "y": 6 }), &ctx)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result.output,
+            "Dry run, not performed: scroll down by 3 notches at (5, 6)"
+        );
+        assert_eq!(result.effects.len(), 1);
+        assert!(fake.actions().is_empty());
     }
 }
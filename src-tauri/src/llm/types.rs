@@ -7,9 +7,90 @@ use serde_json::Value;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
     pub role: String,
-    pub content: MessageContent,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// `null` for assistant turns that only call tools
+    #[serde(default)]
+    pub content: Option<MessageContent>,
+    /// Name of the tool a `tool` message answers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub tool_call_id: Option<String>,
+    /// Tool calls requested by an assistant turn
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCall>>,
+    /// Set on `tool` messages reporting a failed call
+    /// (Anthropic's `is_error`; cleared before OpenAI-style requests)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_error: bool,
+}
+
+impl Message {
+    fn new(role: &str, content: Option<MessageContent>) -> Self {
+        Self {
+            role: role.to_string(),
+            content,
+            name: None,
+            tool_call_id: None,
+            tool_calls: None,
+            is_error: false,
+        }
+    }
+
+    pub fn system(text: impl Into<String>) -> Self {
+        Self::new("system", Some(MessageContent::Text(text.into())))
+    }
+
+    pub fn user(text: impl Into<String>) -> Self {
+        Self::new("user", Some(MessageContent::Text(text.into())))
+    }
+
+    /// User turn with text and images
+    pub fn user_parts(parts: Vec<ContentPart>) -> Self {
+        Self::new("user", Some(MessageContent::Parts(parts)))
+    }
+
+    pub fn assistant(text: impl Into<String>) -> Self {
+        Self::new("assistant", Some(MessageContent::Text(text.into())))
+    }
+
+    /// Assistant turn that calls tools (empty content is sent as `null`)
+    pub fn assistant_tool_calls(content: Option<String>, tool_calls: Vec<ToolCall>) -> Self {
+        let content = content.filter(|c| !c.is_empty()).map(MessageContent::Text);
+        Self {
+            tool_calls: Some(tool_calls),
+            ..Self::new("assistant", content)
+        }
+    }
+
+    /// Result of a tool call, linked by id and tool name
+    pub fn tool(call: &ToolCall, result: impl Into<String>) -> Self {
+        Self {
+            name: Some(call.function.name.clone()),
+            tool_call_id: Some(call.id.clone()),
+            ..Self::new("tool", Some(MessageContent::Text(result.into())))
+        }
+    }
+
+    /// Mark a tool result as the failure of its call
+    pub fn with_error(self, is_error: bool) -> Self {
+        Self { is_error, ..self }
+    }
+
+    /// Text content of the message (images are dropped)
+    pub fn text(&self) -> String {
+        match &self.content {
+            Some(MessageContent::Text(text)) => text.clone(),
+            Some(MessageContent::Parts(parts)) => parts
+                .iter()
+                .filter_map(|p| match p {
+                    ContentPart::Text { text } => Some(text.as_str()),
+                    ContentPart::ImageUrl { .. } => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            None => String::new(),
+        }
+    }
 }
 
 /// Message content (text or multimodal)
@@ -54,18 +135,17 @@ pub struct FunctionDef {
     pub parameters: Value,
 }
 
-/// Tool call in response
-#[derive(Debug, Clone, Deserialize)]
+/// Tool call in response (echoed back in assistant history)
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolCall {
     pub id: String,
     #[serde(rename = "type")]
-    #[allow(dead_code)]
     pub call_type: String,
     pub function: FunctionCall,
 }
 
 /// Function call details
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FunctionCall {
     pub name: String,
     pub arguments: String,
@@ -86,6 +166,9 @@ pub struct ToolResult {
     pub output: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// Overlay effects showing the action (performed, or planned in a dry run)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub effects: Vec<crate::commands::overlay::OverlayEvent>,
 }
 
 impl ToolResult {
@@ -94,20 +177,101 @@ impl ToolResult {
             success: true,
             output: output.into(),
             error: None,
+            effects: Vec::new(),
         }
     }
 
+    /// Action a dry run describes instead of performing
+    pub fn planned(
+        description: impl Into<String>,
+        effects: Vec<crate::commands::overlay::OverlayEvent>,
+    ) -> Self {
+        Self::success(format!("Dry run, not performed: {}", description.into()))
+            .with_effects(effects)
+    }
+
+    /// Show the action on the overlay
+    pub fn with_effects(mut self, effects: Vec<crate::commands::overlay::OverlayEvent>) -> Self {
+        self.effects = effects;
+        self
+    }
+
     pub fn error(error: impl Into<String>) -> Self {
         Self {
             success: false,
             output: String::new(),
             error: Some(error.into()),
+            effects: Vec::new(),
         }
     }
 }
 
 /// Context passed to tools during execution
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct ToolContext {
-    pub api_config: crate::commands::config::ApiConfig,
+    pub provider: std::sync::Arc<dyn crate::llm::provider::LlmProvider>,
+    pub tool_mode: crate::commands::config::ToolCallMode,
+    /// Cancelled when the run is stopped (checked during long input actions)
+    pub cancel: crate::input::cancel::CancelToken,
+    /// Limits of the run the tool belongs to
+    pub budget: crate::commands::config::RunBudget,
+    /// Sub-agent nesting level (0 for the main agent)
+    pub depth: usize,
+    /// When the run's wall-clock budget runs out
+    pub deadline: Option<tokio::time::Instant>,
+    /// Input tools describe their effect instead of performing it
+    pub dry_run: bool,
+    /// Plan of the run (plan mode only)
+    pub plan: Option<crate::llm::runner::plan::SharedPlan>,
+    /// Input backend tools act through (None = the platform's native backend)
+    pub input: Option<std::sync::Arc<dyn crate::input::backend::InputBackend>>,
+    /// Mouse buttons left pressed by mouse_down (released when the run ends)
+    pub held: crate::input::mouse::HeldButtons,
+}
+
+impl ToolContext {
+    /// Context for a sub-agent one level deeper
+    /// Fails when the budget's sub-agent depth would be exceeded
+    pub fn sub_agent(&self) -> anyhow::Result<ToolContext> {
+        if self.depth >= self.budget.max_sub_agent_depth {
+            return Err(anyhow::anyhow!(
+                "Sub-agent depth limit ({}) reached",
+                self.budget.max_sub_agent_depth
+            ));
+        }
+        Ok(ToolContext {
+            depth: self.depth + 1,
+            ..self.clone()
+        })
+    }
+
+    /// Input backend tools act through
+    pub fn input_backend(
+        &self,
+    ) -> anyhow::Result<std::sync::Arc<dyn crate::input::backend::InputBackend>> {
+        match &self.input {
+            Some(input) => Ok(input.clone()),
+            None => crate::input::backend::native(),
+        }
+    }
+
+    /// Release the mouse buttons the run still holds
+    pub fn release_held(&self) -> anyhow::Result<()> {
+        if self.held.is_empty() {
+            return Ok(());
+        }
+        self.held.release_all(self.input_backend()?.as_ref())
+    }
+
+    /// Error if the run was cancelled or ran out of time
+    pub fn check_limits(&self) -> anyhow::Result<()> {
+        self.cancel.check()?;
+        if self
+            .deadline
+            .is_some_and(|deadline| tokio::time::Instant::now() >= deadline)
+        {
+            return Err(anyhow::anyhow!("Run time limit reached"));
+        }
+        Ok(())
+    }
 }
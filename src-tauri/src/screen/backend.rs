@@ -0,0 +1,73 @@
+// Screen backends - screenshots, UI trees and window titles of a platform
+//
+// Windows uses GDI and UI Automation. Linux reads the X server in $DISPLAY:
+// screenshots of the root window and a tree of the active window's X windows
+// (X11 has no accessibility tree without AT-SPI).
+
+use crate::commands::screen::UIElement;
+use anyhow::Result;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::ImageEncoder;
+use std::sync::Arc;
+
+/// What is on screen
+pub trait ScreenBackend: Send + Sync {
+    /// Capture the entire screen as a PNG data URL
+    fn capture(&self) -> Result<String>;
+
+    /// UI element tree of the active window
+    fn window_tree(&self, max_depth: usize) -> Result<UIElement>;
+
+    /// Title of the foreground window
+    fn foreground_window_title(&self) -> Option<String>;
+
+    /// Title of the top-level window at screen coordinates
+    fn window_title_at(&self, x: i32, y: i32) -> Option<String>;
+}
+
+/// Screen backend of the platform the app runs on
+#[cfg(windows)]
+pub fn native() -> Result<Arc<dyn ScreenBackend>> {
+    Ok(Arc::new(crate::screen::win32::WindowsScreen))
+}
+
+/// Screen backend of the platform the app runs on
+/// (the X11 connection is opened on first use and shared afterwards)
+#[cfg(target_os = "linux")]
+pub fn native() -> Result<Arc<dyn ScreenBackend>> {
+    use crate::screen::x11::X11Screen;
+    use std::sync::Mutex;
+
+    lazy_static::lazy_static! {
+        static ref X11: Mutex<Option<Arc<X11Screen>>> = Mutex::new(None);
+    }
+
+    let mut x11 = X11.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(screen) = x11.as_ref() {
+        return Ok(screen.clone());
+    }
+    let screen = Arc::new(X11Screen::connect()?);
+    *x11 = Some(screen.clone());
+    Ok(screen)
+}
+
+/// Screen backend of the platform the app runs on
+#[cfg(not(any(windows, target_os = "linux")))]
+pub fn native() -> Result<Arc<dyn ScreenBackend>> {
+    Err(anyhow::anyhow!(
+        "Screen capture is not supported on {}",
+        std::env::consts::OS
+    ))
+}
+
+/// Encode RGBA pixels as a PNG data URL
+pub fn png_data_url(width: u32, height: u32, rgba: &[u8]) -> Result<String> {
+    let mut png_data = Vec::new();
+    let encoder = image::codecs::png::PngEncoder::new(&mut png_data);
+    encoder.write_image(rgba, width, height, image::ExtendedColorType::Rgba8)?;
+
+    Ok(format!(
+        "data:image/png;base64,{}",
+        STANDARD.encode(&png_data)
+    ))
+}
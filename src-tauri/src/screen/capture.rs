@@ -1,8 +1,7 @@
 // Screen capture implementation (Windows)
 
+use crate::screen::backend::png_data_url;
 use anyhow::Result;
-use base64::{engine::general_purpose::STANDARD, Engine};
-use image::{ImageBuffer, ImageEncoder, Rgba};
 use windows::Win32::Graphics::Gdi::*;
 use windows::Win32::UI::WindowsAndMessaging::*;
 
@@ -76,24 +75,6 @@ pub fn capture_screen_base64() -> Result<String> {
             chunk.swap(0, 2); // Swap B and R
         }
 
-        // Create image buffer
-        let img: ImageBuffer<Rgba<u8>, Vec<u8>> =
-            ImageBuffer::from_raw(width as u32, height as u32, pixels)
-                .ok_or_else(|| anyhow::anyhow!("Failed to create image buffer"))?;
-
-        // Encode to PNG
-        let mut png_data = Vec::new();
-        let encoder = image::codecs::png::PngEncoder::new(&mut png_data);
-        encoder.write_image(
-            img.as_raw(),
-            width as u32,
-            height as u32,
-            image::ExtendedColorType::Rgba8,
-        )?;
-
-        // Base64 encode
-        let base64_data = STANDARD.encode(&png_data);
-
-        Ok(format!("data:image/png;base64,{}", base64_data))
+        png_data_url(width as u32, height as u32, &pixels)
     }
 }
@@ -1,6 +1,13 @@
 // Screen recognition module
 
+pub mod backend;
 #[cfg(windows)]
 pub mod capture;
 #[cfg(windows)]
 pub mod ui_automation;
+#[cfg(windows)]
+mod win32;
+#[cfg(windows)]
+pub mod window;
+#[cfg(target_os = "linux")]
+mod x11;
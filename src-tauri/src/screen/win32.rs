@@ -0,0 +1,27 @@
+// Screen backend (Windows) - GDI capture, UI Automation and window titles
+
+use crate::commands::screen::UIElement;
+use crate::screen::backend::ScreenBackend;
+use crate::screen::{capture, ui_automation, window};
+use anyhow::Result;
+
+/// GDI / UI Automation backend
+pub struct WindowsScreen;
+
+impl ScreenBackend for WindowsScreen {
+    fn capture(&self) -> Result<String> {
+        capture::capture_screen_base64()
+    }
+
+    fn window_tree(&self, max_depth: usize) -> Result<UIElement> {
+        ui_automation::get_active_window_tree(max_depth)
+    }
+
+    fn foreground_window_title(&self) -> Option<String> {
+        window::foreground_window_title()
+    }
+
+    fn window_title_at(&self, x: i32, y: i32) -> Option<String> {
+        window::window_title_at(x, y)
+    }
+}
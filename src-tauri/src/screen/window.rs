@@ -0,0 +1,32 @@
+// Top-level window lookup (Windows)
+
+use windows::Win32::Foundation::{HWND, POINT};
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetAncestor, GetForegroundWindow, GetWindowTextW, WindowFromPoint, GA_ROOT,
+};
+
+/// Title of the foreground window
+pub fn foreground_window_title() -> Option<String> {
+    unsafe { window_title(GetForegroundWindow()) }
+}
+
+/// Title of the top-level window at screen coordinates
+pub fn window_title_at(x: i32, y: i32) -> Option<String> {
+    unsafe {
+        let hwnd = WindowFromPoint(POINT { x, y });
+        if hwnd.is_invalid() {
+            return None;
+        }
+        window_title(GetAncestor(hwnd, GA_ROOT))
+    }
+}
+
+/// Read a window's title (None for invalid handles)
+unsafe fn window_title(hwnd: HWND) -> Option<String> {
+    if hwnd.is_invalid() {
+        return None;
+    }
+    let mut buffer = [0u16; 512];
+    let len = GetWindowTextW(hwnd, &mut buffer);
+    Some(String::from_utf16_lossy(&buffer[..len.max(0) as usize]))
+}
@@ -0,0 +1,221 @@
+// Screen backend (Linux) - root window capture and X window tree
+
+use crate::commands::screen::{BoundingRect, UIElement};
+use crate::screen::backend::{png_data_url, ScreenBackend};
+use anyhow::Result;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{Atom, AtomEnum, ConnectionExt as _, ImageFormat, MapState, Window};
+use x11rb::rust_connection::RustConnection;
+
+/// Child windows searched for a title below a window manager frame
+const TITLE_SEARCH_DEPTH: usize = 3;
+
+/// X11 backend
+pub struct X11Screen {
+    conn: RustConnection,
+    root: Window,
+    net_active_window: Atom,
+    net_wm_name: Atom,
+    utf8_string: Atom,
+}
+
+impl X11Screen {
+    /// Connect to the X server in $DISPLAY
+    pub fn connect() -> Result<Self> {
+        let (conn, screen) = x11rb::connect(None)
+            .map_err(|e| anyhow::anyhow!("Cannot connect to X server: {}", e))?;
+        let root = conn.setup().roots[screen].root;
+
+        let net_active_window = conn.intern_atom(false, b"_NET_ACTIVE_WINDOW")?;
+        let net_wm_name = conn.intern_atom(false, b"_NET_WM_NAME")?;
+        let utf8_string = conn.intern_atom(false, b"UTF8_STRING")?;
+        let net_active_window = net_active_window.reply()?.atom;
+        let net_wm_name = net_wm_name.reply()?.atom;
+        let utf8_string = utf8_string.reply()?.atom;
+
+        Ok(Self {
+            conn,
+            root,
+            net_active_window,
+            net_wm_name,
+            utf8_string,
+        })
+    }
+
+    /// Window the window manager reports as active (input focus without one)
+    fn active_window(&self) -> Result<Window> {
+        let reply = self
+            .conn
+            .get_property(
+                false,
+                self.root,
+                self.net_active_window,
+                AtomEnum::WINDOW,
+                0,
+                1,
+            )?
+            .reply()?;
+        if let Some(window) = reply.value32().and_then(|mut v| v.next()) {
+            if window != 0 {
+                return Ok(window);
+            }
+        }
+
+        let focus = self.conn.get_input_focus()?.reply()?.focus;
+        // PointerRoot (1) and None (0) are not windows
+        Ok(if focus > 1 { focus } else { self.root })
+    }
+
+    /// Window title (_NET_WM_NAME, falling back to WM_NAME)
+    fn title(&self, window: Window) -> Option<String> {
+        for (property, kind) in [
+            (self.net_wm_name, self.utf8_string),
+            (AtomEnum::WM_NAME.into(), AtomEnum::STRING.into()),
+        ] {
+            let reply = self
+                .conn
+                .get_property(false, window, property, kind, 0, 1024)
+                .ok()?
+                .reply()
+                .ok()?;
+            if !reply.value.is_empty() {
+                return Some(String::from_utf8_lossy(&reply.value).into_owned());
+            }
+        }
+        None
+    }
+
+    /// Title of a window or, for window manager frames, of the client inside
+    fn title_below(&self, window: Window, depth: usize) -> Option<String> {
+        if let Some(title) = self.title(window) {
+            return Some(title);
+        }
+        if depth == 0 {
+            return None;
+        }
+        let tree = self.conn.query_tree(window).ok()?.reply().ok()?;
+        tree.children
+            .iter()
+            .rev()
+            .find_map(|child| self.title_below(*child, depth - 1))
+    }
+
+    /// Class of a window (second string of WM_CLASS)
+    fn class_name(&self, window: Window) -> String {
+        let value = self
+            .conn
+            .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 256)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|reply| reply.value)
+            .unwrap_or_default();
+        value
+            .split(|b| *b == 0)
+            .rfind(|part| !part.is_empty())
+            .map(|part| String::from_utf8_lossy(part).into_owned())
+            .unwrap_or_default()
+    }
+
+    /// Window position in root coordinates
+    fn bounds(&self, window: Window) -> Result<BoundingRect> {
+        let geometry = self.conn.get_geometry(window)?.reply()?;
+        let origin = self
+            .conn
+            .translate_coordinates(window, self.root, 0, 0)?
+            .reply()?;
+        Ok(BoundingRect {
+            x: origin.dst_x as i32,
+            y: origin.dst_y as i32,
+            width: geometry.width as i32,
+            height: geometry.height as i32,
+        })
+    }
+
+    fn is_viewable(&self, window: Window) -> bool {
+        self.conn
+            .get_window_attributes(window)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .is_some_and(|attributes| attributes.map_state == MapState::VIEWABLE)
+    }
+
+    /// Build UI element tree recursively (mapped windows only)
+    fn build_element_tree(
+        &self,
+        window: Window,
+        focus: Window,
+        depth: usize,
+        max_depth: usize,
+    ) -> Result<UIElement> {
+        let mut children = Vec::new();
+        if depth < max_depth {
+            let tree = self.conn.query_tree(window)?.reply()?;
+            for child in tree.children {
+                if self.is_viewable(child) {
+                    children.push(self.build_element_tree(child, focus, depth + 1, max_depth)?);
+                }
+            }
+        }
+
+        Ok(UIElement {
+            name: self.title(window).unwrap_or_default(),
+            class_name: self.class_name(window),
+            control_type: "Window".to_string(),
+            bounding_rect: self.bounds(window)?,
+            is_enabled: true,
+            is_focused: window == focus,
+            children,
+        })
+    }
+}
+
+impl ScreenBackend for X11Screen {
+    fn capture(&self) -> Result<String> {
+        let geometry = self.conn.get_geometry(self.root)?.reply()?;
+        let (width, height) = (geometry.width, geometry.height);
+        let image = self
+            .conn
+            .get_image(ImageFormat::Z_PIXMAP, self.root, 0, 0, width, height, !0)?
+            .reply()?;
+
+        // 24/32-bit visuals store pixels as BGRX
+        let pixel_count = width as usize * height as usize;
+        if image.depth < 24 || image.data.len() < pixel_count * 4 {
+            return Err(anyhow::anyhow!(
+                "Unsupported X11 screen depth: {}",
+                image.depth
+            ));
+        }
+        let mut pixels = image.data;
+        pixels.truncate(pixel_count * 4);
+        for chunk in pixels.chunks_exact_mut(4) {
+            chunk.swap(0, 2); // Swap B and R
+            chunk[3] = 255;
+        }
+
+        png_data_url(width as u32, height as u32, &pixels)
+    }
+
+    fn window_tree(&self, max_depth: usize) -> Result<UIElement> {
+        let active = self.active_window()?;
+        let focus = self.conn.get_input_focus()?.reply()?.focus;
+        self.build_element_tree(active, focus, 0, max_depth)
+    }
+
+    fn foreground_window_title(&self) -> Option<String> {
+        let active = self.active_window().ok()?;
+        self.title_below(active, TITLE_SEARCH_DEPTH)
+    }
+
+    fn window_title_at(&self, x: i32, y: i32) -> Option<String> {
+        // Children are listed bottom to top, so the last match is the visible one
+        let tree = self.conn.query_tree(self.root).ok()?.reply().ok()?;
+        let top = tree.children.iter().rev().find(|child| {
+            self.is_viewable(**child)
+                && self
+                    .bounds(**child)
+                    .is_ok_and(|r| x >= r.x && y >= r.y && x < r.x + r.width && y < r.y + r.height)
+        })?;
+        self.title_below(*top, TITLE_SEARCH_DEPTH)
+    }
+}
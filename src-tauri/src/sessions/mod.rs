@@ -0,0 +1,5 @@
+// Sessions module - persistent multi-turn conversations
+
+pub mod storage;
+
+pub use storage::{Session, SessionRun, SessionSummary};
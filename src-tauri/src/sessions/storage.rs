@@ -0,0 +1,202 @@
+// Session storage - conversations saved as JSON under the app data dir
+//
+// A session keeps the agent's message history (without the system prompt,
+// which is rebuilt for every run) and the steps of each run, so a new
+// message can continue where the previous run stopped.
+
+use crate::llm::runner::{AgentStep, StopReason};
+use crate::llm::types::{ContentPart, Message, MessageContent};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// Stored in place of screenshots (images are not persisted)
+const SCREENSHOT_PLACEHOLDER: &str = "[Screenshot not stored]";
+
+/// Session titles are cut to this many characters
+const MAX_TITLE_CHARS: usize = 60;
+
+/// One agent run within a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRun {
+    pub run_id: String,
+    pub user_message: String,
+    pub started_at: String,
+    pub finished_at: String,
+    pub steps: Vec<AgentStep>,
+    pub final_response: String,
+    /// None when the run failed with an error
+    pub stop_reason: Option<StopReason>,
+    pub error: Option<String>,
+}
+
+/// A conversation: message history plus the runs that produced it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub id: String,
+    pub title: String,
+    pub created_at: String,
+    pub updated_at: String,
+    /// History sent to the model (system prompt excluded)
+    pub messages: Vec<Message>,
+    pub runs: Vec<SessionRun>,
+}
+
+/// Session list entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub id: String,
+    pub title: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub run_count: usize,
+}
+
+impl Session {
+    /// New empty session titled after its first message
+    pub fn new(first_message: &str) -> Self {
+        let now = chrono::Local::now();
+        Self {
+            id: format!(
+                "session-{}-{:04x}",
+                now.format("%Y%m%d%H%M%S"),
+                now.timestamp_subsec_micros() & 0xffff
+            ),
+            title: make_title(first_message),
+            created_at: now.to_rfc3339(),
+            updated_at: now.to_rfc3339(),
+            messages: Vec::new(),
+            runs: Vec::new(),
+        }
+    }
+
+    /// Replace the history, dropping screenshots to keep the file small
+    pub fn set_messages(&mut self, mut messages: Vec<Message>) {
+        for message in &mut messages {
+            if let Some(MessageContent::Parts(parts)) = &mut message.content {
+                for part in parts.iter_mut() {
+                    if matches!(part, ContentPart::ImageUrl { .. }) {
+                        *part = ContentPart::Text {
+                            text: SCREENSHOT_PLACEHOLDER.to_string(),
+                        };
+                    }
+                }
+            }
+        }
+        self.messages = messages;
+    }
+
+    fn summary(&self) -> SessionSummary {
+        SessionSummary {
+            id: self.id.clone(),
+            title: self.title.clone(),
+            created_at: self.created_at.clone(),
+            updated_at: self.updated_at.clone(),
+            run_count: self.runs.len(),
+        }
+    }
+}
+
+/// First line of a message, shortened for use as a title
+fn make_title(message: &str) -> String {
+    let line = message.lines().next().unwrap_or_default().trim();
+    match line.char_indices().nth(MAX_TITLE_CHARS) {
+        Some((index, _)) => format!("{}...", &line[..index]),
+        None => line.to_string(),
+    }
+}
+
+/// Get the sessions directory path
+fn get_sessions_dir() -> Result<PathBuf> {
+    let data_dir = dirs::data_local_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not find data directory"))?
+        .join("automate")
+        .join("sessions");
+
+    if !data_dir.exists() {
+        fs::create_dir_all(&data_dir)?;
+    }
+
+    Ok(data_dir)
+}
+
+/// Path of a session file (rejects IDs that could escape the directory)
+fn get_session_path(id: &str) -> Result<PathBuf> {
+    let valid = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(anyhow::anyhow!("Invalid session ID: {}", id));
+    }
+
+    Ok(get_sessions_dir()?.join(format!("{}.json", id)))
+}
+
+/// Load a session
+pub fn load_session(id: &str) -> Result<Session> {
+    let path = get_session_path(id)?;
+    if !path.exists() {
+        return Err(anyhow::anyhow!("Session not found: {}", id));
+    }
+
+    let content = fs::read_to_string(&path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Save a session, updating its modification time
+pub fn save_session(session: &mut Session) -> Result<()> {
+    session.updated_at = chrono::Local::now().to_rfc3339();
+    let content = serde_json::to_string_pretty(session)?;
+    fs::write(get_session_path(&session.id)?, content)?;
+    Ok(())
+}
+
+/// List all sessions, most recently updated first
+pub fn list_sessions() -> Result<Vec<SessionSummary>> {
+    let mut sessions = Vec::new();
+
+    for entry in fs::read_dir(get_sessions_dir()?)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+
+        // Skip unreadable files instead of failing the whole list
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        if let Ok(session) = serde_json::from_str::<Session>(&content) {
+            sessions.push(session.summary());
+        }
+    }
+
+    // RFC 3339 timestamps from the same clock sort chronologically as strings
+    sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    Ok(sessions)
+}
+
+/// Change a session's title
+pub fn rename_session(id: &str, title: &str) -> Result<SessionSummary> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err(anyhow::anyhow!("Title must not be empty"));
+    }
+
+    let mut session = load_session(id)?;
+    session.title = title.to_string();
+    save_session(&mut session)?;
+    Ok(session.summary())
+}
+
+/// Delete a session
+pub fn delete_session(id: &str) -> Result<()> {
+    let path = get_session_path(id)?;
+    if !path.exists() {
+        return Err(anyhow::anyhow!("Session not found: {}", id));
+    }
+
+    fs::remove_file(path)?;
+    Ok(())
+}
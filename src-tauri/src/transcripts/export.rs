@@ -0,0 +1,425 @@
+// Transcript export - renders a recorded run as an HTML or Markdown report
+//
+// The report is a timeline of the run: the request, each model response with
+// its timing, tool calls and results, approvals and the screenshots the model
+// saw. Screenshots are embedded as data URLs so the file is self-contained.
+
+use crate::llm::runner::events::AgentEvent;
+use crate::llm::types::{ContentPart, Message, MessageContent};
+use crate::transcripts::recorder::{load_screenshot, read_transcript};
+use crate::transcripts::{TranscriptEntry, TranscriptRecord};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Report format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Html,
+    Markdown,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Html => "html",
+            Self::Markdown => "md",
+        }
+    }
+}
+
+/// One element of the report timeline
+enum Block {
+    /// Section heading (one per iteration)
+    Heading(String),
+    /// Short line of text, prefixed with the time since the run started
+    Line { elapsed_ms: u64, text: String },
+    /// Preformatted text (prompts, tool parameters and output)
+    Code { label: String, body: String },
+    /// Screenshot as a data URL
+    Image { caption: String, data_url: String },
+}
+
+/// Render a run's transcript in `dir` and write it to `dir/exports/<run_id>.<ext>`
+/// Returns the path of the written report
+pub fn export_run(dir: &Path, run_id: &str, format: ExportFormat) -> Result<PathBuf> {
+    let entries = read_transcript(dir, run_id)?;
+    let blocks = build_timeline(dir, &entries);
+    let report = match format {
+        ExportFormat::Html => render_html(run_id, &blocks),
+        ExportFormat::Markdown => render_markdown(run_id, &blocks),
+    };
+
+    let exports = dir.join("exports");
+    fs::create_dir_all(&exports)?;
+    let path = exports.join(format!("{}.{}", run_id, format.extension()));
+    fs::write(&path, report)?;
+
+    Ok(path)
+}
+
+/// Turn transcript entries into report blocks (screenshots are read from `dir`)
+fn build_timeline(dir: &Path, entries: &[TranscriptEntry]) -> Vec<Block> {
+    let mut blocks = Vec::new();
+
+    for entry in entries {
+        let elapsed_ms = entry.elapsed_ms;
+        let line = |text: String| Block::Line { elapsed_ms, text };
+
+        match &entry.record {
+            TranscriptRecord::RunStarted {
+                user_message,
+                model,
+                ..
+            } => {
+                blocks.push(line(format!(
+                    "Run started ({}) with model {}",
+                    entry.timestamp, model
+                )));
+                blocks.push(Block::Code {
+                    label: "User request".to_string(),
+                    body: user_message.clone(),
+                });
+            }
+            TranscriptRecord::Request {
+                iteration,
+                from_index,
+                messages,
+            } => {
+                let prompt = messages
+                    .iter()
+                    .map(message_text)
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
+                blocks.push(Block::Code {
+                    label: format!(
+                        "Prompt for step {} (messages from #{})",
+                        iteration + 1,
+                        from_index
+                    ),
+                    body: prompt,
+                });
+            }
+            TranscriptRecord::Response {
+                duration_ms,
+                message,
+                usage,
+                ..
+            } => {
+                let tokens = usage
+                    .map(|u| {
+                        format!(
+                            ", {} prompt + {} completion tokens",
+                            u.prompt_tokens, u.completion_tokens
+                        )
+                    })
+                    .unwrap_or_default();
+                blocks.push(line(format!(
+                    "Model answered in {} ms{}",
+                    duration_ms, tokens
+                )));
+                blocks.push(Block::Code {
+                    label: "Response".to_string(),
+                    body: message_text(message),
+                });
+            }
+            TranscriptRecord::Screen { ui_tree, .. } => {
+                if !ui_tree.is_empty() {
+                    blocks.push(Block::Code {
+                        label: "UI tree".to_string(),
+                        body: ui_tree.clone(),
+                    });
+                }
+            }
+            TranscriptRecord::Event { event } => {
+                event_blocks(dir, event, elapsed_ms, &mut blocks);
+            }
+        }
+    }
+
+    blocks
+}
+
+/// Report blocks for an agent event
+fn event_blocks(dir: &Path, event: &AgentEvent, elapsed_ms: u64, blocks: &mut Vec<Block>) {
+    let line = |text: String| Block::Line { elapsed_ms, text };
+
+    match event {
+        AgentEvent::StepStarted { iteration } => {
+            blocks.push(Block::Heading(format!("Step {}", iteration + 1)));
+        }
+        AgentEvent::Thought { text, .. } => {
+            blocks.push(line(format!("Thought: {}", text)));
+        }
+        AgentEvent::ToolCall { name, params, .. } => {
+            blocks.push(Block::Code {
+                label: format!("Tool call: {}", name),
+                body: serde_json::to_string_pretty(params).unwrap_or_default(),
+            });
+        }
+        AgentEvent::ToolResult {
+            name,
+            success,
+            output,
+            duration_ms,
+            ..
+        } => {
+            let status = if *success { "succeeded" } else { "failed" };
+            blocks.push(Block::Code {
+                label: format!("{} {} in {} ms", name, status, duration_ms),
+                body: output.clone(),
+            });
+        }
+        AgentEvent::ApprovalRequested { name, reason, .. } => {
+            let reason = reason
+                .as_deref()
+                .map(|r| format!(" ({})", r))
+                .unwrap_or_default();
+            blocks.push(line(format!("Approval requested for {}{}", name, reason)));
+        }
+        AgentEvent::ApprovalResolved { decision, .. } => {
+            blocks.push(line(format!("Approval decision: {:?}", decision)));
+        }
+        AgentEvent::Paused { .. } => blocks.push(line("Paused".to_string())),
+        AgentEvent::Resumed { .. } => blocks.push(line("Resumed".to_string())),
+        AgentEvent::ScreenshotCaptured { screenshot, .. } => match screenshot {
+            Some(file) => match load_screenshot(dir, file) {
+                Some(data_url) => blocks.push(Block::Image {
+                    caption: format!("Screenshot {}", file),
+                    data_url,
+                }),
+                None => blocks.push(line(format!("Screenshot {} (file missing)", file))),
+            },
+            None => blocks.push(line("Screenshot captured (not stored)".to_string())),
+        },
+        AgentEvent::PlanUpdated { plan, .. } => {
+            blocks.push(Block::Code {
+                label: format!("Plan (revision {})", plan.revision),
+                body: plan.render(),
+            });
+        }
+        AgentEvent::StallDetected {
+            reason,
+            intervention,
+            ..
+        } => {
+            blocks.push(line(format!("Stall detected ({:?}): {}", intervention, reason)));
+        }
+        AgentEvent::Finished { result } => {
+            blocks.push(Block::Heading("Result".to_string()));
+            blocks.push(line(format!(
+                "Finished: {:?} after {} steps, ${:.4}",
+                result.stop_reason,
+                result.steps.len(),
+                result.usage.cost_usd
+            )));
+            blocks.push(Block::Code {
+                label: "Final response".to_string(),
+                body: result.final_response.clone(),
+            });
+        }
+        AgentEvent::Error { message } => {
+            blocks.push(Block::Heading("Result".to_string()));
+            blocks.push(line(format!("Failed: {}", message)));
+        }
+    }
+}
+
+/// Readable text of a recorded message (role, content and tool calls)
+fn message_text(message: &Message) -> String {
+    let mut text = format!("[{}]", message.role);
+    if let Some(name) = &message.name {
+        text.push_str(&format!(" {}", name));
+    }
+
+    match &message.content {
+        Some(MessageContent::Text(content)) => text.push_str(&format!("\n{}", content)),
+        Some(MessageContent::Parts(parts)) => {
+            for part in parts {
+                match part {
+                    ContentPart::Text { text: content } => text.push_str(&format!("\n{}", content)),
+                    ContentPart::ImageUrl { image_url } => {
+                        text.push_str(&format!("\n[image {}]", image_url.url))
+                    }
+                }
+            }
+        }
+        None => {}
+    }
+
+    for call in message.tool_calls.iter().flatten() {
+        text.push_str(&format!(
+            "\n-> {}({})",
+            call.function.name, call.function.arguments
+        ));
+    }
+
+    text
+}
+
+/// Format milliseconds since the run started as `m:ss.mmm`
+fn format_elapsed(ms: u64) -> String {
+    format!("{}:{:02}.{:03}", ms / 60_000, ms / 1000 % 60, ms % 1000)
+}
+
+/// Escape text for HTML element content and attributes
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Escape text so Markdown shows it literally (inline HTML included)
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' | '!'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn render_html(run_id: &str, blocks: &[Block]) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Run {id}</title>\n<style>\n\
+         body {{ font-family: sans-serif; max-width: 960px; margin: 2em auto; color: #222; }}\n\
+         .time {{ color: #888; font-family: monospace; margin-right: 0.5em; }}\n\
+         pre {{ background: #f4f4f4; padding: 0.75em; white-space: pre-wrap; word-break: break-word; }}\n\
+         img {{ max-width: 100%; border: 1px solid #ccc; }}\n\
+         </style>\n</head>\n<body>\n<h1>Run {id}</h1>\n",
+        id = escape_html(run_id)
+    );
+
+    for block in blocks {
+        match block {
+            Block::Heading(text) => html.push_str(&format!("<h2>{}</h2>\n", escape_html(text))),
+            Block::Line { elapsed_ms, text } => html.push_str(&format!(
+                "<p><span class=\"time\">{}</span>{}</p>\n",
+                format_elapsed(*elapsed_ms),
+                escape_html(text)
+            )),
+            Block::Code { label, body } => html.push_str(&format!(
+                "<details open>\n<summary>{}</summary>\n<pre>{}</pre>\n</details>\n",
+                escape_html(label),
+                escape_html(body)
+            )),
+            Block::Image { caption, data_url } => html.push_str(&format!(
+                "<figure>\n<img src=\"{}\" alt=\"{}\">\n<figcaption>{}</figcaption>\n</figure>\n",
+                escape_html(data_url),
+                escape_html(caption),
+                escape_html(caption)
+            )),
+        }
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn render_markdown(run_id: &str, blocks: &[Block]) -> String {
+    let mut md = format!("# Run {}\n\n", escape_markdown(run_id));
+
+    for block in blocks {
+        match block {
+            Block::Heading(text) => md.push_str(&format!("## {}\n\n", escape_markdown(text))),
+            Block::Line { elapsed_ms, text } => md.push_str(&format!(
+                "`{}` {}\n\n",
+                format_elapsed(*elapsed_ms),
+                escape_markdown(text)
+            )),
+            Block::Code { label, body } => {
+                // A fence longer than any backtick run in the body keeps it intact
+                let longest = body.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+                let fence = "`".repeat(longest.max(2) + 1);
+                md.push_str(&format!(
+                    "**{}**\n\n{}\n{}\n{}\n\n",
+                    escape_markdown(label),
+                    fence,
+                    body,
+                    fence
+                ));
+            }
+            Block::Image { caption, data_url } => md.push_str(&format!(
+                "![{}]({})\n\n",
+                escape_markdown(caption),
+                data_url
+            )),
+        }
+    }
+
+    md
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::config::ToolCallMode;
+    use crate::transcripts::recorder::Transcript;
+
+    /// Transcript of a run whose request and thought contain markup
+    fn record_run(dir: &Path) {
+        let mut transcript = Transcript::create(dir, "run-1").unwrap();
+        transcript
+            .record(TranscriptRecord::RunStarted {
+                user_message: "Type <script>alert(1)</script> and ```".to_string(),
+                model: "gpt-4o".to_string(),
+                tool_mode: ToolCallMode::Native,
+            })
+            .unwrap();
+        transcript
+            .record(TranscriptRecord::Event {
+                event: AgentEvent::Thought {
+                    iteration: 0,
+                    text: "<img src=x onerror=alert(1)> **now** [link](x)".to_string(),
+                },
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn html_report_escapes_recorded_text() {
+        let dir = tempfile::tempdir().unwrap();
+        record_run(dir.path());
+
+        let path = export_run(dir.path(), "run-1", ExportFormat::Html).unwrap();
+        let html = fs::read_to_string(&path).unwrap();
+
+        assert_eq!(path, dir.path().join("exports").join("run-1.html"));
+        assert!(!html.contains("<script>"));
+        assert!(!html.contains("<img src=x"));
+        assert!(html.contains("Type &lt;script&gt;alert(1)&lt;/script&gt; and ```"));
+        assert!(html.contains("Thought: &lt;img src=x onerror=alert(1)&gt;"));
+    }
+
+    #[test]
+    fn markdown_report_escapes_recorded_text() {
+        let dir = tempfile::tempdir().unwrap();
+        record_run(dir.path());
+
+        let path = export_run(dir.path(), "run-1", ExportFormat::Markdown).unwrap();
+        let md = fs::read_to_string(&path).unwrap();
+
+        assert_eq!(path, dir.path().join("exports").join("run-1.md"));
+        assert!(md.contains(r"Thought: \<img src=x onerror=alert(1)\> \*\*now\*\* \[link\](x)"));
+        // The request is fenced with more backticks than it contains
+        assert!(md.contains("````\nType <script>alert(1)</script> and ```\n````"));
+    }
+
+    #[test]
+    fn unknown_run_is_not_exported() {
+        let dir = tempfile::tempdir().unwrap();
+
+        assert!(export_run(dir.path(), "run-2", ExportFormat::Html).is_err());
+        assert!(export_run(dir.path(), "../run-1", ExportFormat::Html).is_err());
+        assert!(!dir.path().join("exports").exists());
+    }
+}
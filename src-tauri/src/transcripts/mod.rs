@@ -0,0 +1,8 @@
+// Transcripts module - per-run JSONL records, screenshot files, reports and replay
+
+pub mod export;
+pub mod recorder;
+#[cfg(test)]
+pub mod replay;
+
+pub use recorder::{TranscriptEntry, TranscriptRecord};
@@ -0,0 +1,449 @@
+// Transcript recorder - writes everything a run saw and did to a JSONL file
+//
+// One file per run (`transcripts/<run_id>.jsonl`), one JSON object per line.
+// Screenshots are written once to `transcripts/screenshots/<sha256>.<ext>`
+// and referenced as `screenshot:<file>` instead of being inlined. Old
+// transcripts expire after the configured retention, and screenshots go
+// with the last transcript that uses them.
+
+use crate::commands::config::ToolCallMode;
+use crate::llm::client::Usage;
+use crate::llm::runner::events::AgentEvent;
+use crate::llm::types::{ContentPart, Message, MessageContent};
+use anyhow::Result;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// URL prefix of screenshot references in recorded messages
+pub const SCREENSHOT_REF_PREFIX: &str = "screenshot:";
+
+lazy_static::lazy_static! {
+    /// Name of a stored screenshot file
+    static ref SCREENSHOT_NAME: Regex = Regex::new(r"[0-9a-f]{64}\.(?:png|jpg|webp)").unwrap();
+}
+
+/// What happened at one point of a run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TranscriptRecord {
+    /// The run began
+    RunStarted {
+        user_message: String,
+        model: String,
+        #[serde(default)]
+        tool_mode: ToolCallMode,
+    },
+    /// The screen was captured: screenshot file (when stored) and UI tree
+    Screen {
+        iteration: usize,
+        screenshot: Option<String>,
+        ui_tree: String,
+    },
+    /// Prompt sent to the model: the messages from `from_index` on
+    /// (earlier ones were recorded with a previous request)
+    Request {
+        iteration: usize,
+        from_index: usize,
+        messages: Vec<Message>,
+    },
+    /// The model's answer and how long it took
+    Response {
+        iteration: usize,
+        duration_ms: u64,
+        message: Message,
+        usage: Option<Usage>,
+    },
+    /// An agent event (thoughts, tool calls and results, screenshots, the outcome)
+    Event { event: AgentEvent },
+}
+
+/// One line of a transcript
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    /// Local time (RFC 3339)
+    pub timestamp: String,
+    /// Milliseconds since the transcript was opened
+    pub elapsed_ms: u64,
+    #[serde(flatten)]
+    pub record: TranscriptRecord,
+}
+
+/// Open transcript of a run
+pub struct Transcript {
+    file: File,
+    started: Instant,
+    /// Transcripts directory (screenshots go to its `screenshots/`)
+    dir: PathBuf,
+}
+
+impl Transcript {
+    /// Open (or continue) the transcript of a run in `dir`
+    pub fn create(dir: &Path, run_id: &str) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(get_transcript_path(dir, run_id)?)?;
+
+        Ok(Self {
+            file,
+            started: Instant::now(),
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    /// Append a record, moving screenshots out into their own files
+    pub fn record(&mut self, record: TranscriptRecord) -> Result<()> {
+        let record = match record {
+            TranscriptRecord::Request {
+                iteration,
+                from_index,
+                messages,
+            } => TranscriptRecord::Request {
+                iteration,
+                from_index,
+                messages: messages
+                    .into_iter()
+                    .map(|message| externalize_images(&self.dir, message))
+                    .collect(),
+            },
+            other => other,
+        };
+
+        let entry = TranscriptEntry {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            elapsed_ms: self.started.elapsed().as_millis() as u64,
+            record,
+        };
+
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+        self.file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// Store a screenshot next to the transcript and return its file name
+    pub fn store_screenshot(&self, data_url: &str) -> Result<String> {
+        store_screenshot(&self.dir, data_url)
+    }
+}
+
+/// Get the transcripts directory path
+pub fn get_transcripts_dir() -> Result<PathBuf> {
+    let data_dir = dirs::data_local_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not find data directory"))?
+        .join("automate")
+        .join("transcripts");
+
+    if !data_dir.exists() {
+        fs::create_dir_all(&data_dir)?;
+    }
+
+    Ok(data_dir)
+}
+
+/// Screenshots directory of a transcripts directory
+fn get_screenshots_dir(dir: &Path) -> Result<PathBuf> {
+    let dir = dir.join("screenshots");
+
+    if !dir.exists() {
+        fs::create_dir_all(&dir)?;
+    }
+
+    Ok(dir)
+}
+
+/// Path of a run's transcript (rejects IDs that could escape the directory)
+fn get_transcript_path(dir: &Path, run_id: &str) -> Result<PathBuf> {
+    let valid = !run_id.is_empty()
+        && run_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(anyhow::anyhow!("Invalid run ID: {}", run_id));
+    }
+
+    Ok(dir.join(format!("{}.jsonl", run_id)))
+}
+
+/// Store a `data:image/...;base64,` screenshot and return its file name
+/// Identical images share one file (named by SHA-256 of the image bytes)
+pub fn store_screenshot(dir: &Path, data_url: &str) -> Result<String> {
+    let (header, data) = data_url
+        .split_once(',')
+        .ok_or_else(|| anyhow::anyhow!("Screenshot is not a data URL"))?;
+    let extension = match header {
+        h if h.starts_with("data:image/jpeg") => "jpg",
+        h if h.starts_with("data:image/webp") => "webp",
+        _ => "png",
+    };
+
+    let bytes = STANDARD.decode(data)?;
+    let hash = Sha256::digest(&bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    let file_name = format!("{}.{}", hash, extension);
+
+    let path = get_screenshots_dir(dir)?.join(&file_name);
+    if !path.exists() {
+        fs::write(path, bytes)?;
+    }
+
+    Ok(file_name)
+}
+
+/// Load a stored screenshot (file name or `screenshot:` reference) as a data URL
+pub fn load_screenshot(dir: &Path, file: &str) -> Option<String> {
+    let file = file.strip_prefix(SCREENSHOT_REF_PREFIX).unwrap_or(file);
+    // Stored names are `<sha256>.<ext>` - anything else is not ours to read
+    if file.contains(['/', '\\']) || file.starts_with('.') {
+        return None;
+    }
+
+    let bytes = fs::read(dir.join("screenshots").join(file)).ok()?;
+    let mime = match file.rsplit('.').next() {
+        Some("jpg") => "image/jpeg",
+        Some("webp") => "image/webp",
+        _ => "image/png",
+    };
+    Some(format!("data:{};base64,{}", mime, STANDARD.encode(bytes)))
+}
+
+/// Replace inline images with `screenshot:<file>` references
+fn externalize_images(dir: &Path, mut message: Message) -> Message {
+    if let Some(MessageContent::Parts(parts)) = &mut message.content {
+        for part in parts.iter_mut() {
+            if let ContentPart::ImageUrl { image_url } = part {
+                if image_url.url.starts_with("data:") {
+                    image_url.url = match store_screenshot(dir, &image_url.url) {
+                        Ok(file) => format!("{}{}", SCREENSHOT_REF_PREFIX, file),
+                        Err(_) => "[screenshot not stored]".to_string(),
+                    };
+                }
+            }
+        }
+    }
+    message
+}
+
+/// Read all entries of a run's transcript (unparseable lines are skipped)
+pub fn read_transcript(dir: &Path, run_id: &str) -> Result<Vec<TranscriptEntry>> {
+    let path = get_transcript_path(dir, run_id)?;
+    if !path.exists() {
+        return Err(anyhow::anyhow!("No transcript for run: {}", run_id));
+    }
+
+    let reader = BufReader::new(File::open(path)?);
+    let mut entries = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if let Ok(entry) = serde_json::from_str(&line) {
+            entries.push(entry);
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Delete transcripts last written more than `max_age` ago, then every
+/// screenshot the remaining transcripts don't reference
+/// Returns how many transcripts and screenshots were removed
+pub fn remove_expired(dir: &Path, max_age: Duration) -> Result<(usize, usize)> {
+    let now = SystemTime::now();
+    let mut removed_transcripts = 0;
+    let mut referenced = HashSet::new();
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+            continue;
+        }
+
+        let modified = fs::metadata(&path)?.modified()?;
+        if now.duration_since(modified).unwrap_or_default() > max_age {
+            fs::remove_file(&path)?;
+            removed_transcripts += 1;
+            continue;
+        }
+
+        // Screenshot files are named `<sha256>.<ext>` - any mention is a reference
+        let content = fs::read_to_string(&path)?;
+        referenced.extend(
+            SCREENSHOT_NAME
+                .find_iter(&content)
+                .map(|m| m.as_str().to_string()),
+        );
+    }
+
+    let mut removed_screenshots = 0;
+    let screenshots = dir.join("screenshots");
+    if screenshots.exists() {
+        for entry in fs::read_dir(screenshots)? {
+            let entry = entry?;
+            if !referenced.contains(entry.file_name().to_string_lossy().as_ref()) {
+                fs::remove_file(entry.path())?;
+                removed_screenshots += 1;
+            }
+        }
+    }
+
+    Ok((removed_transcripts, removed_screenshots))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::types::ImageUrl;
+
+    /// 1x1 PNGs of different colours
+    const RED: &str = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mP8z8DwHwAFBQIAX8jx0gAAAABJRU5ErkJggg==";
+    const BLUE: &str = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPj/HwADBwIAMCbHYQAAAABJRU5ErkJggg==";
+
+    fn screen_message(data_url: &str) -> Message {
+        Message::user_parts(vec![
+            ContentPart::Text {
+                text: "Current screen".to_string(),
+            },
+            ContentPart::ImageUrl {
+                image_url: ImageUrl {
+                    url: data_url.to_string(),
+                    detail: None,
+                },
+            },
+        ])
+    }
+
+    fn screenshot_files(dir: &Path) -> Vec<String> {
+        let mut files: Vec<String> = fs::read_dir(dir.join("screenshots"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn records_round_trip_through_jsonl() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut transcript = Transcript::create(dir.path(), "run-1").unwrap();
+        transcript
+            .record(TranscriptRecord::RunStarted {
+                user_message: "Open notepad".to_string(),
+                model: "gpt-4o".to_string(),
+                tool_mode: ToolCallMode::Text,
+            })
+            .unwrap();
+        transcript
+            .record(TranscriptRecord::Request {
+                iteration: 0,
+                from_index: 0,
+                messages: vec![screen_message(RED)],
+            })
+            .unwrap();
+        transcript
+            .record(TranscriptRecord::Event {
+                event: AgentEvent::Thought {
+                    iteration: 0,
+                    text: "Looking for the icon".to_string(),
+                },
+            })
+            .unwrap();
+
+        let entries = read_transcript(dir.path(), "run-1").unwrap();
+
+        assert_eq!(entries.len(), 3);
+        assert!(matches!(
+            &entries[0].record,
+            TranscriptRecord::RunStarted { user_message, tool_mode: ToolCallMode::Text, .. }
+                if user_message == "Open notepad"
+        ));
+        assert!(matches!(
+            &entries[2].record,
+            TranscriptRecord::Event { event: AgentEvent::Thought { text, .. } }
+                if text == "Looking for the icon"
+        ));
+
+        // The image was moved out to a screenshot file and is loaded back unchanged
+        let TranscriptRecord::Request { messages, .. } = &entries[1].record else {
+            panic!("expected a request, got {:?}", entries[1].record);
+        };
+        let Some(MessageContent::Parts(parts)) = &messages[0].content else {
+            panic!("expected content parts");
+        };
+        let ContentPart::ImageUrl { image_url } = &parts[1] else {
+            panic!("expected an image");
+        };
+        assert!(image_url.url.starts_with(SCREENSHOT_REF_PREFIX));
+        assert_eq!(
+            load_screenshot(dir.path(), &image_url.url).as_deref(),
+            Some(RED)
+        );
+    }
+
+    #[test]
+    fn identical_screenshots_share_a_file() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let first = store_screenshot(dir.path(), RED).unwrap();
+        let again = store_screenshot(dir.path(), RED).unwrap();
+        let other = store_screenshot(dir.path(), BLUE).unwrap();
+
+        assert_eq!(first, again);
+        assert_ne!(first, other);
+        assert!(SCREENSHOT_NAME.is_match(&first));
+        assert_eq!(screenshot_files(dir.path()).len(), 2);
+    }
+
+    #[test]
+    fn paths_outside_the_directory_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+
+        assert!(Transcript::create(dir.path(), "../run-1").is_err());
+        assert!(read_transcript(dir.path(), "run/1").is_err());
+        assert_eq!(load_screenshot(dir.path(), "../config.json"), None);
+        assert_eq!(load_screenshot(dir.path(), ".hidden"), None);
+    }
+
+    #[test]
+    fn expired_transcripts_take_their_screenshots_along() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut old = Transcript::create(dir.path(), "run-old").unwrap();
+        old.record(TranscriptRecord::Request {
+            iteration: 0,
+            from_index: 0,
+            messages: vec![screen_message(RED), screen_message(BLUE)],
+        })
+        .unwrap();
+        let blue = old.store_screenshot(BLUE).unwrap();
+        let mut recent = Transcript::create(dir.path(), "run-recent").unwrap();
+        recent
+            .record(TranscriptRecord::Screen {
+                iteration: 0,
+                screenshot: Some(blue.clone()),
+                ui_tree: String::new(),
+            })
+            .unwrap();
+        let last_week = SystemTime::now() - Duration::from_secs(7 * 24 * 60 * 60);
+        File::options()
+            .write(true)
+            .open(dir.path().join("run-old.jsonl"))
+            .unwrap()
+            .set_modified(last_week)
+            .unwrap();
+
+        let removed = remove_expired(dir.path(), Duration::from_secs(24 * 60 * 60)).unwrap();
+
+        assert_eq!(removed, (1, 1));
+        assert!(read_transcript(dir.path(), "run-old").is_err());
+        assert!(read_transcript(dir.path(), "run-recent").is_ok());
+        // Still used by the recent transcript
+        assert_eq!(screenshot_files(dir.path()), [blue]);
+    }
+}
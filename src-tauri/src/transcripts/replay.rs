@@ -0,0 +1,570 @@
+// Replay - re-runs a recorded transcript without a live model or desktop
+//
+// `ReplayProvider` answers with the recorded responses in order, and
+// `ReplayDesktop` serves the recorded screenshots and UI trees and checks that
+// the run issues the recorded tool calls (answering with the recorded results
+// instead of touching the real input). Whatever the run does differently is
+// collected as a divergence, so prompt and tool changes can be regression
+// tested with `cargo test` on any platform.
+
+use crate::commands::config::{AppConfig, ToolCallMode};
+use crate::llm::client::{ChatResponse, Choice, ResponseMessage, Usage};
+use crate::llm::provider::LlmProvider;
+use crate::llm::runner::control::RunControl;
+use crate::llm::runner::environment::{Desktop, RunEnvironment};
+use crate::llm::runner::events::{new_run_id, AgentEvent, EventSink};
+use crate::llm::runner::{run_agent_loop_in, AgentResult, RunOptions, ToolExecutor};
+use crate::llm::types::{Message, ToolContext, ToolDef, ToolResult};
+use crate::transcripts::recorder::load_screenshot;
+use crate::transcripts::{TranscriptEntry, TranscriptRecord};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Served when a recorded screenshot file is not available (1x1 PNG)
+const PLACEHOLDER_SCREENSHOT: &str = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAQAAAC1HAwCAAAAC0lEQVR42mNkYAAAAAYAAjCB0C8AAAAASUVORK5CYII=";
+
+/// Tool call issued during the recorded run and its result
+#[derive(Debug, Clone)]
+struct RecordedCall {
+    call_id: String,
+    name: String,
+    params: Value,
+    success: bool,
+    output: String,
+}
+
+/// Screen captured during the recorded run
+#[derive(Debug, Clone)]
+struct RecordedScreen {
+    screenshot: Option<String>,
+    ui_tree: String,
+}
+
+/// What a recorded run saw and did, as needed to replay it
+#[derive(Debug, Clone)]
+pub struct Recording {
+    pub user_message: String,
+    pub model: String,
+    pub tool_mode: ToolCallMode,
+    responses: Vec<(Message, Option<Usage>)>,
+    screens: Vec<RecordedScreen>,
+    tool_calls: Vec<RecordedCall>,
+}
+
+impl Recording {
+    /// Parse a transcript in JSONL form (a `transcripts/<run_id>.jsonl` file)
+    pub fn parse(jsonl: &str) -> Result<Self> {
+        let entries = jsonl
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<serde_json::Result<Vec<TranscriptEntry>>>()?;
+        Self::from_entries(entries)
+    }
+
+    fn from_entries(entries: Vec<TranscriptEntry>) -> Result<Self> {
+        let mut started = None;
+        let mut responses = Vec::new();
+        let mut screens = Vec::new();
+        let mut tool_calls: Vec<RecordedCall> = Vec::new();
+
+        for entry in entries {
+            match entry.record {
+                TranscriptRecord::RunStarted {
+                    user_message,
+                    model,
+                    tool_mode,
+                } => started = Some((user_message, model, tool_mode)),
+                TranscriptRecord::Response { message, usage, .. } => {
+                    responses.push((message, usage))
+                }
+                TranscriptRecord::Screen {
+                    screenshot,
+                    ui_tree,
+                    ..
+                } => screens.push(RecordedScreen {
+                    screenshot,
+                    ui_tree,
+                }),
+                TranscriptRecord::Event {
+                    event:
+                        AgentEvent::ToolCall {
+                            call_id,
+                            name,
+                            params,
+                            ..
+                        },
+                } => tool_calls.push(RecordedCall {
+                    call_id,
+                    name,
+                    params,
+                    success: false,
+                    output: String::new(),
+                }),
+                TranscriptRecord::Event {
+                    event:
+                        AgentEvent::ToolResult {
+                            call_id,
+                            success,
+                            output,
+                            ..
+                        },
+                } => {
+                    let call = tool_calls.iter_mut().rev().find(|c| c.call_id == call_id);
+                    if let Some(call) = call {
+                        call.success = success;
+                        call.output = output;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let (user_message, model, tool_mode) =
+            started.ok_or_else(|| anyhow::anyhow!("Transcript has no run_started record"))?;
+
+        Ok(Self {
+            user_message,
+            model,
+            tool_mode,
+            responses,
+            screens,
+            tool_calls,
+        })
+    }
+
+    /// Config matching the recorded run (no streaming, approvals already decided)
+    pub fn config(&self) -> AppConfig {
+        let mut config = AppConfig::default();
+        config.api.model = self.model.clone();
+        config.api.tool_mode = self.tool_mode;
+        config.api.supports_vision = !self.screens.is_empty();
+        config.api.stream = false;
+        config.approval.enabled = false;
+        config
+    }
+}
+
+/// Differences between a replayed run and its recording
+type Divergences = Arc<Mutex<Vec<String>>>;
+
+fn diverge(divergences: &Divergences, message: String) {
+    divergences
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(message);
+}
+
+/// Provider answering with recorded responses in order
+pub struct ReplayProvider {
+    responses: Mutex<VecDeque<(Message, Option<Usage>)>>,
+    requests: Mutex<Vec<Vec<Message>>>,
+    divergences: Divergences,
+}
+
+#[async_trait]
+impl LlmProvider for ReplayProvider {
+    async fn chat(
+        &self,
+        messages: Vec<Message>,
+        _tools: Option<Vec<ToolDef>>,
+    ) -> Result<ChatResponse> {
+        let request = {
+            let mut requests = self.requests.lock().unwrap_or_else(|e| e.into_inner());
+            requests.push(messages);
+            requests.len()
+        };
+
+        let Some((message, usage)) = self
+            .responses
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .pop_front()
+        else {
+            diverge(
+                &self.divergences,
+                format!("request #{} has no recorded response", request),
+            );
+            return Err(anyhow::anyhow!("Replay: no recorded response left"));
+        };
+
+        Ok(ChatResponse {
+            choices: vec![Choice {
+                message: ResponseMessage {
+                    role: message.role.clone(),
+                    content: message.content.as_ref().map(|_| message.text()),
+                    tool_calls: message.tool_calls.clone(),
+                },
+                finish_reason: None,
+            }],
+            usage,
+        })
+    }
+}
+
+/// Desktop serving recorded screens and checking tool calls against the recording
+pub struct ReplayDesktop {
+    /// Transcripts directory the recorded screenshots are read from
+    transcripts_dir: PathBuf,
+    screens: Mutex<VecDeque<RecordedScreen>>,
+    tool_calls: Mutex<VecDeque<RecordedCall>>,
+    issued: Mutex<usize>,
+    divergences: Divergences,
+}
+
+#[async_trait]
+impl Desktop for ReplayDesktop {
+    /// Recordings are made on Windows, so replays offer the Windows tools
+    fn os(&self) -> &str {
+        "windows"
+    }
+
+    fn capture(&self) -> Result<Option<(String, String)>> {
+        let screen = self
+            .screens
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .pop_front();
+
+        match screen {
+            Some(screen) => {
+                let screenshot = screen
+                    .screenshot
+                    .as_deref()
+                    .and_then(|file| load_screenshot(&self.transcripts_dir, file))
+                    .unwrap_or_else(|| PLACEHOLDER_SCREENSHOT.to_string());
+                Ok(Some((screenshot, screen.ui_tree)))
+            }
+            None => {
+                diverge(
+                    &self.divergences,
+                    "screen captured more often than recorded".to_string(),
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    async fn execute(
+        &self,
+        _executor: &ToolExecutor,
+        tool_name: &str,
+        params: Value,
+        _ctx: &ToolContext,
+    ) -> Result<ToolResult> {
+        let index = {
+            let mut issued = self.issued.lock().unwrap_or_else(|e| e.into_inner());
+            *issued += 1;
+            *issued
+        };
+        let expected = self
+            .tool_calls
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .pop_front();
+
+        let Some(expected) = expected else {
+            diverge(
+                &self.divergences,
+                format!(
+                    "tool call #{} {}({}) was not recorded",
+                    index, tool_name, params
+                ),
+            );
+            return Ok(ToolResult::error("Replay: tool call not in the recording"));
+        };
+
+        if expected.name != tool_name || expected.params != params {
+            diverge(
+                &self.divergences,
+                format!(
+                    "tool call #{}: expected {}({}), got {}({})",
+                    index, expected.name, expected.params, tool_name, params
+                ),
+            );
+        }
+
+        // The runner adds the "Error: " prefix again
+        Ok(if expected.success {
+            ToolResult::success(expected.output)
+        } else {
+            let error = expected
+                .output
+                .strip_prefix("Error: ")
+                .unwrap_or(&expected.output);
+            ToolResult::error(error)
+        })
+    }
+}
+
+/// Result of a replayed run
+#[derive(Debug)]
+pub struct ReplayOutcome {
+    pub result: Result<AgentResult>,
+    /// Everything the run did differently from the recording
+    pub divergences: Vec<String>,
+    /// Messages of every request sent to the model
+    pub requests: Vec<Vec<Message>>,
+}
+
+impl ReplayOutcome {
+    /// Panic with the list of divergences unless the run matched its recording
+    pub fn assert_matches(&self) {
+        assert!(
+            self.divergences.is_empty(),
+            "replay diverged from the recording:\n{}",
+            self.divergences.join("\n")
+        );
+    }
+}
+
+/// A recorded run set up for replay
+pub struct Replay {
+    recording: Recording,
+    /// Transcripts directory holding the recording's screenshots
+    transcripts_dir: PathBuf,
+    /// Config of the replayed run (starts as `Recording::config`)
+    pub config: AppConfig,
+}
+
+impl Replay {
+    pub fn new(recording: Recording, transcripts_dir: impl Into<PathBuf>) -> Self {
+        let config = recording.config();
+        Self {
+            recording,
+            transcripts_dir: transcripts_dir.into(),
+            config,
+        }
+    }
+
+    /// Run the agent loop against the recording
+    pub async fn run(self) -> ReplayOutcome {
+        let divergences: Divergences = Arc::new(Mutex::new(Vec::new()));
+        let provider = Arc::new(ReplayProvider {
+            responses: Mutex::new(self.recording.responses.into()),
+            requests: Mutex::new(Vec::new()),
+            divergences: divergences.clone(),
+        });
+        let desktop = Arc::new(ReplayDesktop {
+            transcripts_dir: self.transcripts_dir,
+            screens: Mutex::new(self.recording.screens.into()),
+            tool_calls: Mutex::new(self.recording.tool_calls.into()),
+            issued: Mutex::new(0),
+            divergences: divergences.clone(),
+        });
+
+        let env = RunEnvironment {
+            config: self.config,
+            provider: Some(provider.clone()),
+            desktop: desktop.clone(),
+            persist: false,
+        };
+        let options = RunOptions {
+            include_screen: env.config.api.supports_vision,
+            budget: None,
+            session_id: None,
+            dry_run: false,
+            plan: false,
+            tool_profile: None,
+        };
+        let events = EventSink::new(new_run_id(), None);
+        let control = RunControl::default();
+
+        let result = run_agent_loop_in(
+            &env,
+            &self.recording.user_message,
+            &options,
+            &events,
+            &control,
+        )
+        .await;
+
+        // Whatever the run didn't consume is a divergence too
+        let unused_responses = provider
+            .responses
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .len();
+        if unused_responses > 0 {
+            diverge(
+                &divergences,
+                format!("{} recorded responses were not requested", unused_responses),
+            );
+        }
+        for call in desktop
+            .tool_calls
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+        {
+            diverge(
+                &divergences,
+                format!(
+                    "recorded tool call {}({}) was not issued",
+                    call.name, call.params
+                ),
+            );
+        }
+
+        let requests =
+            std::mem::take(&mut *provider.requests.lock().unwrap_or_else(|e| e.into_inner()));
+        let divergences =
+            std::mem::take(&mut *divergences.lock().unwrap_or_else(|e| e.into_inner()));
+
+        ReplayOutcome {
+            result,
+            divergences,
+            requests,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::runner::StopReason;
+    use crate::llm::types::{ContentPart, MessageContent};
+    use crate::transcripts::recorder::store_screenshot;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    const NOTEPAD: &str = include_str!("../../tests/fixtures/transcripts/notepad_hello.jsonl");
+
+    /// Replay reading screenshots from an empty temporary transcripts directory
+    fn replay(recording: Recording) -> (Replay, TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        (Replay::new(recording, dir.path()), dir)
+    }
+
+    fn user_text(messages: &[Message]) -> String {
+        messages
+            .iter()
+            .filter(|m| m.role == "user")
+            .map(Message::text)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[tokio::test]
+    async fn replays_recorded_run() {
+        let (replay, _dir) = replay(Recording::parse(NOTEPAD).unwrap());
+        let outcome = replay.run().await;
+        outcome.assert_matches();
+
+        let result = outcome.result.unwrap();
+        assert_eq!(result.stop_reason, StopReason::Completed);
+        assert!(result.success);
+        assert_eq!(result.final_response, "I typed hello into Notepad.");
+        let actions: Vec<&str> = result.steps.iter().map(|s| s.action.as_str()).collect();
+        assert_eq!(
+            actions,
+            ["mouse_double_click", "get_screen_update", "keyboard_type"]
+        );
+        assert_eq!(result.usage.requests, 4);
+    }
+
+    #[tokio::test]
+    async fn serves_recorded_screens_to_the_model() {
+        let (replay, _dir) = replay(Recording::parse(NOTEPAD).unwrap());
+        let outcome = replay.run().await;
+        outcome.assert_matches();
+
+        assert_eq!(outcome.requests.len(), 4);
+        assert_eq!(outcome.requests[0][0].role, "system");
+        let first = user_text(&outcome.requests[0]);
+        assert!(first.contains("Notepad shortcut"));
+        assert!(first.contains("User request: Open Notepad and type hello"));
+        // The screen captured after get_screen_update reaches the next request
+        assert!(!user_text(&outcome.requests[1]).contains("Untitled - Notepad"));
+        assert!(user_text(&outcome.requests[2]).contains("Untitled - Notepad"));
+    }
+
+    #[tokio::test]
+    async fn serves_stored_screenshots_from_its_directory() {
+        const PIXEL: &str = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mP8z8DwHwAFBQIAX8jx0gAAAABJRU5ErkJggg==";
+        let dir = tempfile::tempdir().unwrap();
+        let mut recording = Recording::parse(NOTEPAD).unwrap();
+        recording.screens[0].screenshot = Some(store_screenshot(dir.path(), PIXEL).unwrap());
+
+        let outcome = Replay::new(recording, dir.path()).run().await;
+        outcome.assert_matches();
+
+        let images: Vec<String> = outcome
+            .requests
+            .iter()
+            .flatten()
+            .filter_map(|m| match &m.content {
+                Some(MessageContent::Parts(parts)) => Some(parts),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|part| match part {
+                ContentPart::ImageUrl { image_url } => Some(image_url.url.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(images.first().map(String::as_str), Some(PIXEL));
+        // Screens recorded without a file get the placeholder
+        assert!(images.iter().any(|url| url == PLACEHOLDER_SCREENSHOT));
+    }
+
+    #[tokio::test]
+    async fn reports_tool_call_that_differs() {
+        let mut recording = Recording::parse(NOTEPAD).unwrap();
+        recording.tool_calls[0].params = json!({ "x": 41, "y": 60 });
+
+        let (replay, _dir) = replay(recording);
+        let outcome = replay.run().await;
+
+        assert_eq!(outcome.divergences.len(), 1);
+        assert!(outcome.divergences[0].contains("expected mouse_double_click"));
+    }
+
+    #[tokio::test]
+    async fn reports_recorded_steps_not_reached() {
+        let (mut replay, _dir) = replay(Recording::parse(NOTEPAD).unwrap());
+        replay.config.budget.max_iterations = 2;
+
+        let outcome = replay.run().await;
+
+        assert_eq!(
+            outcome.result.unwrap().stop_reason,
+            StopReason::MaxIterations
+        );
+        assert!(outcome
+            .divergences
+            .contains(&"2 recorded responses were not requested".to_string()));
+        assert!(outcome
+            .divergences
+            .iter()
+            .any(|d| d.contains("keyboard_type") && d.contains("was not issued")));
+    }
+
+    #[tokio::test]
+    async fn reports_requests_beyond_the_recording() {
+        let mut recording = Recording::parse(NOTEPAD).unwrap();
+        recording.responses.pop();
+
+        let (replay, _dir) = replay(recording);
+        let outcome = replay.run().await;
+
+        assert!(outcome.result.is_err());
+        assert!(outcome
+            .divergences
+            .contains(&"request #4 has no recorded response".to_string()));
+    }
+
+    #[test]
+    fn rejects_transcript_without_start() {
+        let jsonl = NOTEPAD
+            .lines()
+            .filter(|line| !line.contains("\"run_started\""))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(Recording::parse(&jsonl).is_err());
+    }
+}
@@ -0,0 +1,104 @@
+// Usage ledger - persisted daily token and cost totals per model
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+lazy_static::lazy_static! {
+    /// Serializes read-modify-write cycles on the ledger file
+    static ref LEDGER_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Totals for one model on one day
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub requests: u64,
+    pub cost_usd: f64,
+}
+
+/// Ledger file contents, keyed by local date (YYYY-MM-DD) then model
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Ledger {
+    pub days: BTreeMap<String, BTreeMap<String, ModelUsage>>,
+}
+
+/// Get the ledger file path
+pub fn get_ledger_path() -> Result<PathBuf> {
+    let data_dir = dirs::data_local_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not find data directory"))?
+        .join("automate")
+        .join("usage");
+
+    if !data_dir.exists() {
+        fs::create_dir_all(&data_dir)?;
+    }
+
+    Ok(data_dir.join("ledger.json"))
+}
+
+/// Today's ledger key
+pub fn today() -> String {
+    chrono::Local::now().format("%Y-%m-%d").to_string()
+}
+
+/// Load the ledger (empty if it doesn't exist yet)
+pub fn load_ledger() -> Result<Ledger> {
+    read_ledger(&get_ledger_path()?)
+}
+
+/// Load the ledger file at `path` (empty if it doesn't exist yet)
+fn read_ledger(path: &Path) -> Result<Ledger> {
+    if path.exists() {
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    } else {
+        Ok(Ledger::default())
+    }
+}
+
+/// Add one request's usage to today's entry for `model` in the ledger at `path`
+pub fn record(
+    path: &Path,
+    model: &str,
+    prompt_tokens: u64,
+    completion_tokens: u64,
+    cost_usd: f64,
+) -> Result<()> {
+    let _guard = LEDGER_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let mut ledger = read_ledger(path)?;
+    let entry = ledger
+        .days
+        .entry(today())
+        .or_default()
+        .entry(model.to_string())
+        .or_default();
+    entry.prompt_tokens += prompt_tokens;
+    entry.completion_tokens += completion_tokens;
+    entry.requests += 1;
+    entry.cost_usd += cost_usd;
+
+    let content = serde_json::to_string_pretty(&ledger)?;
+    fs::write(path, content)?;
+    Ok(())
+}
+
+/// Total spend recorded today in the ledger at `path` (all runs so far)
+pub fn spent_today(path: &Path) -> Result<f64> {
+    let _guard = LEDGER_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    Ok(spent_on(&read_ledger(path)?, &today()))
+}
+
+/// Total spend recorded for a day
+pub fn spent_on(ledger: &Ledger, date: &str) -> f64 {
+    ledger
+        .days
+        .get(date)
+        .map(|models| models.values().map(|m| m.cost_usd).sum())
+        .unwrap_or(0.0)
+}
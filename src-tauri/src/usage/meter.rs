@@ -0,0 +1,244 @@
+// Usage meter - per-task token/cost totals and budget checks
+
+use crate::commands::config::{AppConfig, ModelPrice};
+use crate::llm::client::Usage;
+use crate::usage::ledger;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Tokens and cost spent by one task (main agent and sub-agents)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    pub requests: u32,
+    pub cost_usd: f64,
+    /// Set when the model has no entry in the price table (cost is then 0)
+    pub unpriced: bool,
+}
+
+/// Collects usage for a task and enforces the configured budgets
+pub struct UsageMeter {
+    model: String,
+    price: Option<ModelPrice>,
+    task_budget_usd: Option<f64>,
+    daily_budget_usd: Option<f64>,
+    /// Daily ledger the usage is written to (None for detached meters)
+    ledger: Option<PathBuf>,
+    usage: Mutex<TaskUsage>,
+}
+
+impl UsageMeter {
+    /// Meter for a task, recording into the daily ledger
+    pub fn new(config: &AppConfig) -> Self {
+        match ledger::get_ledger_path() {
+            Ok(path) => Self::with_ledger(config, path),
+            Err(e) => {
+                log::warn!("Usage ledger unavailable, not recording usage: {}", e);
+                Self::detached(config)
+            }
+        }
+    }
+
+    /// Meter recording into the ledger file at `path`
+    fn with_ledger(config: &AppConfig, path: PathBuf) -> Self {
+        Self {
+            ledger: Some(path),
+            ..Self::detached(config)
+        }
+    }
+
+    /// Meter that only counts the task (no ledger reads or writes)
+    pub fn detached(config: &AppConfig) -> Self {
+        let price = config.usage.price_for(&config.api.model).cloned();
+
+        Self {
+            model: config.api.model.clone(),
+            usage: Mutex::new(TaskUsage {
+                unpriced: price.is_none(),
+                ..TaskUsage::default()
+            }),
+            price,
+            task_budget_usd: config.usage.task_budget_usd,
+            daily_budget_usd: config.usage.daily_budget_usd,
+            ledger: None,
+        }
+    }
+
+    /// Add the usage of one response (None when the server didn't report it)
+    pub fn record(&self, usage: Option<&Usage>) {
+        let (prompt, completion) = usage
+            .map(|u| (u.prompt_tokens as u64, u.completion_tokens as u64))
+            .unwrap_or((0, 0));
+        let cost = self.price.as_ref().map_or(0.0, |p| {
+            (prompt as f64 * p.input_per_mtok + completion as f64 * p.output_per_mtok) / 1_000_000.0
+        });
+
+        {
+            let mut total = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+            total.prompt_tokens += prompt;
+            total.completion_tokens += completion;
+            total.total_tokens += prompt + completion;
+            total.requests += 1;
+            total.cost_usd += cost;
+        }
+
+        // Accounting must never fail the task itself
+        if let Some(path) = &self.ledger {
+            if let Err(e) = ledger::record(path, &self.model, prompt, completion, cost) {
+                log::warn!("Failed to record usage in the ledger: {}", e);
+            }
+        }
+    }
+
+    /// Current totals
+    pub fn snapshot(&self) -> TaskUsage {
+        self.usage.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Reason the task must stop, if a budget has been reached
+    pub fn budget_exceeded(&self) -> Option<String> {
+        let spent = self.snapshot().cost_usd;
+
+        if let Some(limit) = self.task_budget_usd {
+            if spent >= limit {
+                return Some(format!(
+                    "Task budget of ${:.2} reached (spent ${:.4})",
+                    limit, spent
+                ));
+            }
+        }
+
+        if let Some(limit) = self.daily_budget_usd {
+            let today = self.spent_today(spent);
+            if today >= limit {
+                return Some(format!(
+                    "Daily budget of ${:.2} reached (spent ${:.4} today)",
+                    limit, today
+                ));
+            }
+        }
+
+        None
+    }
+
+    /// Today's total spend, re-read from the ledger so concurrent runs and
+    /// the change of date are accounted (`task_spent` without a ledger)
+    fn spent_today(&self, task_spent: f64) -> f64 {
+        let Some(path) = &self.ledger else {
+            return task_spent;
+        };
+
+        ledger::spent_today(path).unwrap_or_else(|e| {
+            log::warn!("Failed to read the usage ledger: {}", e);
+            task_spent
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usage::ledger::{Ledger, ModelUsage};
+
+    fn config(model: &str) -> AppConfig {
+        let mut config = AppConfig::default();
+        config.api.model = model.to_string();
+        config.usage.prices = vec![ModelPrice {
+            model: "test-model".to_string(),
+            input_per_mtok: 3.0,
+            output_per_mtok: 15.0,
+        }];
+        config
+    }
+
+    fn usage(prompt_tokens: u32, completion_tokens: u32) -> Usage {
+        Usage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+        }
+    }
+
+    #[test]
+    fn cost_follows_the_price_table() {
+        let meter = UsageMeter::detached(&config("test-model-large"));
+        meter.record(Some(&usage(1000, 500)));
+        meter.record(None);
+
+        let total = meter.snapshot();
+        assert_eq!(total.prompt_tokens, 1000);
+        assert_eq!(total.completion_tokens, 500);
+        assert_eq!(total.requests, 2);
+        assert!((total.cost_usd - 0.0105).abs() < 1e-9, "{}", total.cost_usd);
+        assert!(!total.unpriced);
+
+        // Models without a price are counted but cost nothing
+        let meter = UsageMeter::detached(&config("other-model"));
+        meter.record(Some(&usage(1000, 500)));
+        let total = meter.snapshot();
+        assert_eq!(total.total_tokens, 1500);
+        assert_eq!(total.cost_usd, 0.0);
+        assert!(total.unpriced);
+    }
+
+    #[test]
+    fn daily_budget_counts_every_run_of_today() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger.json");
+        let yesterday = (chrono::Local::now() - chrono::Duration::days(1))
+            .format("%Y-%m-%d")
+            .to_string();
+        let spent = |cost_usd| {
+            [(
+                "test-model".to_string(),
+                ModelUsage {
+                    cost_usd,
+                    ..ModelUsage::default()
+                },
+            )]
+            .into()
+        };
+        let recorded = Ledger {
+            days: [(yesterday, spent(4.0)), (ledger::today(), spent(1.5))].into(),
+        };
+        std::fs::write(&path, serde_json::to_string(&recorded).unwrap()).unwrap();
+
+        let mut config = config("test-model");
+        config.usage.daily_budget_usd = Some(2.0);
+
+        // Yesterday's $4 doesn't count, today's $1.5 does
+        let meter = UsageMeter::with_ledger(&config, path.clone());
+        assert_eq!(meter.budget_exceeded(), None);
+
+        // $0.45 for this request, $1.95 today
+        meter.record(Some(&usage(100_000, 10_000)));
+        assert_eq!(meter.budget_exceeded(), None);
+
+        // Another run spending at the same time uses up the rest
+        let other = UsageMeter::with_ledger(&config, path);
+        other.record(Some(&usage(100_000, 10_000)));
+        assert_eq!(
+            meter.budget_exceeded().as_deref(),
+            Some("Daily budget of $2.00 reached (spent $2.4000 today)")
+        );
+    }
+
+    #[test]
+    fn task_budget_refuses_further_requests() {
+        let mut config = config("test-model");
+        config.usage.task_budget_usd = Some(0.01);
+        let meter = UsageMeter::detached(&config);
+
+        meter.record(Some(&usage(1000, 0)));
+        assert_eq!(meter.budget_exceeded(), None);
+
+        meter.record(Some(&usage(1000, 1000)));
+        assert_eq!(
+            meter.budget_exceeded().as_deref(),
+            Some("Task budget of $0.01 reached (spent $0.0210)")
+        );
+    }
+}
@@ -0,0 +1,6 @@
+// Usage module - token usage, cost accounting and spending budgets
+
+pub mod ledger;
+pub mod meter;
+
+pub use meter::{TaskUsage, UsageMeter};
@@ -4,6 +4,7 @@ import { Bot, Settings as SettingsIcon, BookPlus } from 'lucide-react';
 import { Chat } from './components/Chat';
 import { Settings } from './components/Settings';
 import { AddGuide } from './components/AddGuide';
+import { ApprovalPrompt } from './components/ApprovalPrompt';
 import { ToastContainer } from './components/Toast';
 import { UpdateChecker } from './components/UpdateChecker';
 import { useConfigStore } from './stores/configStore';
@@ -67,6 +68,9 @@ function App() {
       {/* Add Guide Modal */}
       <AddGuide isOpen={isAddGuideOpen} onClose={() => setIsAddGuideOpen(false)} />
 
+      {/* Approval requests from the agent */}
+      <ApprovalPrompt />
+
       {/* Toast Notifications */}
       <ToastContainer />
 
@@ -0,0 +1,92 @@
+// Approval prompt - asks the user to allow or reject a risky agent action
+
+import { useEffect, useState } from 'react';
+import { useTranslation } from 'react-i18next';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { ShieldAlert } from 'lucide-react';
+
+interface ApprovalRequest {
+  request_id: string;
+  name: string;
+  params: Record<string, unknown>;
+  reason: string | null;
+  timeout_secs: number;
+}
+
+type AgentEvent =
+  | ({ type: 'approval_requested'; run_id: string } & ApprovalRequest)
+  | { type: 'approval_resolved'; run_id: string; request_id: string }
+  | { type: string; run_id: string };
+
+export function ApprovalPrompt() {
+  const { t } = useTranslation();
+  const [requests, setRequests] = useState<ApprovalRequest[]>([]);
+
+  useEffect(() => {
+    const unlisten = listen<AgentEvent>('agent-event', (event) => {
+      const payload = event.payload;
+
+      if (payload.type === 'approval_requested') {
+        const request = payload as ApprovalRequest;
+        setRequests((current) => [...current, request]);
+      } else if (payload.type === 'approval_resolved') {
+        const { request_id } = payload as { request_id: string };
+        setRequests((current) => current.filter((r) => r.request_id !== request_id));
+      }
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const request = requests[0];
+  if (!request) return null;
+
+  const respond = async (approved: boolean) => {
+    setRequests((current) => current.filter((r) => r.request_id !== request.request_id));
+    try {
+      await invoke('respond_approval', { requestId: request.request_id, approved });
+    } catch (e) {
+      console.error('Failed to answer approval request:', e);
+    }
+  };
+
+  return (
+    <div className="fixed inset-0 bg-black/50 flex items-center justify-center z-50">
+      <div className="bg-gray-900 border border-gray-700 rounded-xl w-full max-w-md mx-4">
+        <div className="flex items-center gap-2 px-6 py-4 border-b border-gray-700">
+          <ShieldAlert className="w-5 h-5 text-yellow-400" />
+          <h2 className="text-lg font-semibold">{t('approval.title')}</h2>
+        </div>
+
+        <div className="px-6 py-4 space-y-3">
+          {request.reason && <p className="text-sm text-yellow-300">{request.reason}</p>}
+          <p className="text-sm text-gray-400">{t('approval.description')}</p>
+          <pre className="bg-gray-800 rounded-lg p-3 text-xs text-gray-200 overflow-x-auto">
+            {request.name}({JSON.stringify(request.params, null, 2)})
+          </pre>
+          <p className="text-xs text-gray-500">
+            {t('approval.timeout', { seconds: request.timeout_secs })}
+          </p>
+        </div>
+
+        <div className="flex justify-end gap-3 px-6 py-4 border-t border-gray-700">
+          <button
+            onClick={() => respond(false)}
+            className="px-4 py-2 text-gray-400 hover:text-white transition-colors"
+          >
+            {t('approval.reject')}
+          </button>
+          <button
+            onClick={() => respond(true)}
+            className="px-4 py-2 bg-ai-glow hover:bg-ai-glow/80 rounded-lg transition-colors"
+          >
+            {t('approval.approve')}
+          </button>
+        </div>
+      </div>
+    </div>
+  );
+}
@@ -75,6 +75,35 @@ export function Settings({ isOpen, onClose }: SettingsProps) {
           <section>
             <h3 className="text-sm font-medium text-gray-400 mb-4">{t('settings.api.title')}</h3>
 
+            {/* Provider */}
+            <div className="mb-4">
+              <label className="block text-sm mb-2">{t('settings.api.provider')}</label>
+              <select
+                value={localConfig.api.provider ?? 'openai'}
+                onChange={(e) => updateApi('provider', e.target.value)}
+                className="w-full bg-gray-800 border border-gray-700 rounded-lg px-4 py-2
+                         text-white focus:outline-none focus:border-ai-glow"
+              >
+                <option value="openai">{t('settings.api.providerOpenai')}</option>
+                <option value="anthropic">{t('settings.api.providerAnthropic')}</option>
+                <option value="local">{t('settings.api.providerLocal')}</option>
+              </select>
+            </div>
+
+            {/* Tool calling mode */}
+            <div className="mb-4">
+              <label className="block text-sm mb-2">{t('settings.api.toolMode')}</label>
+              <select
+                value={localConfig.api.tool_mode ?? 'native'}
+                onChange={(e) => updateApi('tool_mode', e.target.value)}
+                className="w-full bg-gray-800 border border-gray-700 rounded-lg px-4 py-2
+                         text-white focus:outline-none focus:border-ai-glow"
+              >
+                <option value="native">{t('settings.api.toolModeNative')}</option>
+                <option value="text">{t('settings.api.toolModeText')}</option>
+              </select>
+            </div>
+
             {/* Endpoint */}
             <div className="mb-4">
               <label className="block text-sm mb-2">{t('settings.api.endpoint')}</label>
@@ -151,6 +180,47 @@ export function Settings({ isOpen, onClose }: SettingsProps) {
               />
             </div>
 
+            {/* Request timeout */}
+            <div className="mb-4">
+              <label className="block text-sm mb-2">{t('settings.api.timeout')}</label>
+              <input
+                type="number"
+                value={localConfig.api.timeout_secs ?? 120}
+                onChange={(e) => updateApi('timeout_secs', parseInt(e.target.value) || 120)}
+                min={1}
+                max={3600}
+                className="w-full bg-gray-800 border border-gray-700 rounded-lg px-4 py-2
+                         text-white placeholder-gray-500 focus:outline-none focus:border-ai-glow"
+              />
+            </div>
+
+            {/* Retries */}
+            <div className="mb-4">
+              <label className="block text-sm mb-2">{t('settings.api.maxRetries')}</label>
+              <input
+                type="number"
+                value={localConfig.api.retry?.max_retries ?? 3}
+                onChange={(e) =>
+                  setLocalConfig({
+                    ...localConfig,
+                    api: {
+                      ...localConfig.api,
+                      retry: {
+                        max_retries: parseInt(e.target.value) || 0,
+                        initial_backoff_ms: localConfig.api.retry?.initial_backoff_ms ?? 1000,
+                        max_backoff_ms: localConfig.api.retry?.max_backoff_ms ?? 30000,
+                        multiplier: localConfig.api.retry?.multiplier ?? 2.0,
+                      },
+                    },
+                  })
+                }
+                min={0}
+                max={10}
+                className="w-full bg-gray-800 border border-gray-700 rounded-lg px-4 py-2
+                         text-white placeholder-gray-500 focus:outline-none focus:border-ai-glow"
+              />
+            </div>
+
             {/* Temperature */}
             <div className="mb-4">
               <label className="block text-sm mb-2">
@@ -197,6 +267,75 @@ export function Settings({ isOpen, onClose }: SettingsProps) {
             )}
           </section>
 
+          {/* Usage budgets */}
+          <section>
+            <h3 className="text-sm font-medium text-gray-400 mb-4">{t('settings.usage.title')}</h3>
+
+            {(['task_budget_usd', 'daily_budget_usd'] as const).map((key) => (
+              <div key={key} className="mb-4">
+                <label className="block text-sm mb-2">
+                  {t(key === 'task_budget_usd' ? 'settings.usage.taskBudget' : 'settings.usage.dailyBudget')}
+                </label>
+                <input
+                  type="number"
+                  value={localConfig.usage?.[key] ?? ''}
+                  onChange={(e) =>
+                    setLocalConfig({
+                      ...localConfig,
+                      usage: {
+                        prices: localConfig.usage?.prices ?? [],
+                        task_budget_usd: localConfig.usage?.task_budget_usd ?? null,
+                        daily_budget_usd: localConfig.usage?.daily_budget_usd ?? null,
+                        [key]: e.target.value === '' ? null : parseFloat(e.target.value),
+                      },
+                    })
+                  }
+                  min={0}
+                  step={0.01}
+                  placeholder={t('settings.usage.noLimit')}
+                  className="w-full bg-gray-800 border border-gray-700 rounded-lg px-4 py-2
+                           text-white placeholder-gray-500 focus:outline-none focus:border-ai-glow"
+                />
+              </div>
+            ))}
+          </section>
+
+          {/* Run limits */}
+          <section>
+            <h3 className="text-sm font-medium text-gray-400 mb-4">{t('settings.budget.title')}</h3>
+
+            {(['max_iterations', 'max_duration_secs'] as const).map((key) => (
+              <div key={key} className="mb-4">
+                <label className="block text-sm mb-2">
+                  {t(key === 'max_iterations' ? 'settings.budget.maxIterations' : 'settings.budget.maxDuration')}
+                </label>
+                <input
+                  type="number"
+                  value={localConfig.budget?.[key] ?? ''}
+                  onChange={(e) =>
+                    setLocalConfig({
+                      ...localConfig,
+                      budget: {
+                        max_iterations: localConfig.budget?.max_iterations ?? 20,
+                        max_duration_secs: localConfig.budget?.max_duration_secs ?? null,
+                        max_tool_calls: localConfig.budget?.max_tool_calls ?? {},
+                        max_sub_agent_depth: localConfig.budget?.max_sub_agent_depth ?? 1,
+                        sub_agent_max_iterations: localConfig.budget?.sub_agent_max_iterations ?? 10,
+                        [key]: e.target.value === ''
+                          ? (key === 'max_iterations' ? 20 : null)
+                          : parseInt(e.target.value, 10),
+                      },
+                    })
+                  }
+                  min={1}
+                  placeholder={key === 'max_duration_secs' ? t('settings.usage.noLimit') : undefined}
+                  className="w-full bg-gray-800 border border-gray-700 rounded-lg px-4 py-2
+                           text-white placeholder-gray-500 focus:outline-none focus:border-ai-glow"
+                />
+              </div>
+            ))}
+          </section>
+
           {/* Language */}
           <section>
             <h3 className="text-sm font-medium text-gray-400 mb-4">{t('settings.language.title')}</h3>
@@ -222,6 +361,18 @@ export function Settings({ isOpen, onClose }: SettingsProps) {
               className="w-full bg-gray-800 border border-gray-700 rounded-lg px-4 py-2
                        text-white placeholder-gray-500 focus:outline-none focus:border-ai-glow"
             />
+            <label className="block text-sm mt-4 mb-2">
+              {t('settings.shortcut.emergencyStop')}
+            </label>
+            <input
+              type="text"
+              value={localConfig.emergency_stop_shortcut}
+              onChange={(e) => setLocalConfig({ ...localConfig, emergency_stop_shortcut: e.target.value })}
+              placeholder="Shift+Alt+X"
+              className="w-full bg-gray-800 border border-gray-700 rounded-lg px-4 py-2
+                       text-white placeholder-gray-500 focus:outline-none focus:border-ai-glow"
+            />
+            <p className="text-xs text-gray-500 mt-1">{t('settings.shortcut.emergencyStopHint')}</p>
           </section>
         </div>
 
@@ -5,15 +5,19 @@ import { useOverlayStore } from '../../stores/overlayStore';
 import { useShallow } from 'zustand/shallow';
 
 export function CursorGlow() {
-  const { cursorPosition, isAiControlling } = useOverlayStore(
+  const { cursorPosition, isAiControlling, pressedButton } = useOverlayStore(
     useShallow((state) => ({
       cursorPosition: state.cursorPosition,
       isAiControlling: state.isAiControlling,
+      pressedButton: state.pressedButton,
     }))
   );
 
   if (!isAiControlling) return null;
 
+  // A held button shows as a larger dot
+  const dotSize = pressedButton ? 14 : 8;
+
   return (
     <motion.div
       className="fixed pointer-events-none"
@@ -71,10 +75,10 @@ export function CursorGlow() {
       <motion.div
         className="absolute rounded-full bg-indigo-400"
         style={{
-          width: 8,
-          height: 8,
-          left: -4,
-          top: -4,
+          width: dotSize,
+          height: dotSize,
+          left: -dotSize / 2,
+          top: -dotSize / 2,
           boxShadow: '0 0 10px 3px rgba(99, 102, 241, 0.6)',
         }}
         animate={{
@@ -0,0 +1,73 @@
+// Drag path effect component
+
+import { motion, AnimatePresence } from 'motion/react';
+import { useOverlayStore, DragTrail } from '../../stores/overlayStore';
+import { useShallow } from 'zustand/shallow';
+
+export function DragPath() {
+  const dragTrails = useOverlayStore(useShallow((state) => state.dragTrails));
+
+  return (
+    <AnimatePresence>
+      {dragTrails.map((trail) => (
+        <TrailEffect key={trail.id} trail={trail} />
+      ))}
+    </AnimatePresence>
+  );
+}
+
+function TrailEffect({ trail }: { trail: DragTrail }) {
+  const getColor = () => {
+    switch (trail.button) {
+      case 'right':
+        return 'rgba(239, 68, 68, 0.7)'; // red
+      case 'middle':
+        return 'rgba(234, 179, 8, 0.7)'; // yellow
+      default:
+        return 'rgba(99, 102, 241, 0.7)'; // indigo
+    }
+  };
+
+  const color = getColor();
+  const { from, to } = trail;
+
+  return (
+    <motion.svg
+      className="fixed inset-0 w-screen h-screen pointer-events-none"
+      initial={{ opacity: 1 }}
+      animate={{ opacity: 1 }}
+      exit={{ opacity: 0 }}
+      transition={{ duration: 0.3 }}
+    >
+      {/* Start point */}
+      <circle cx={from.x} cy={from.y} r={6} fill={color} />
+
+      {/* Path drawn from start to drop point */}
+      <motion.line
+        x1={from.x}
+        y1={from.y}
+        x2={to.x}
+        y2={to.y}
+        stroke={color}
+        strokeWidth={3}
+        strokeLinecap="round"
+        initial={{ pathLength: 0 }}
+        animate={{ pathLength: 1 }}
+        transition={{ duration: 0.5, ease: 'easeInOut' }}
+      />
+
+      {/* Drop point */}
+      <motion.circle
+        cx={to.x}
+        cy={to.y}
+        r={8}
+        fill="none"
+        stroke={color}
+        strokeWidth={2}
+        initial={{ scale: 0 }}
+        animate={{ scale: 1 }}
+        transition={{ duration: 0.2, delay: 0.5 }}
+      />
+    </motion.svg>
+  );
+}
@@ -4,14 +4,19 @@ import { useEffect } from 'react';
 import { listen } from '@tauri-apps/api/event';
 import { CursorGlow } from './CursorGlow';
 import { ClickRipple } from './ClickRipple';
+import { DragPath } from './DragPath';
 import { StatusIndicator } from './StatusIndicator';
 import { useOverlayStore } from '../../stores/overlayStore';
 
 interface OverlayEvent {
-  type: 'cursor_move' | 'click' | 'status' | 'control';
+  type: 'cursor_move' | 'click' | 'mouse_down' | 'mouse_up' | 'drag' | 'status' | 'control';
   payload: {
     x?: number;
     y?: number;
+    from_x?: number;
+    from_y?: number;
+    to_x?: number;
+    to_y?: number;
     button?: 'left' | 'right' | 'middle';
     status?: string;
     message?: string;
@@ -24,6 +29,8 @@ export function OverlayApp() {
     setCursorPosition,
     setAiControlling,
     addClickEffect,
+    setPressedButton,
+    addDragTrail,
     setStatus,
   } = useOverlayStore();
 
@@ -45,6 +52,29 @@ export function OverlayApp() {
           }
           break;
 
+        case 'mouse_down':
+        case 'mouse_up':
+          if (payload.x !== undefined && payload.y !== undefined) {
+            setCursorPosition({ x: payload.x, y: payload.y });
+          }
+          setPressedButton(type === 'mouse_down' ? payload.button || 'left' : null);
+          break;
+
+        case 'drag':
+          if (
+            payload.from_x !== undefined &&
+            payload.from_y !== undefined &&
+            payload.to_x !== undefined &&
+            payload.to_y !== undefined
+          ) {
+            addDragTrail(
+              { x: payload.from_x, y: payload.from_y },
+              { x: payload.to_x, y: payload.to_y },
+              payload.button || 'left'
+            );
+          }
+          break;
+
         case 'status':
           if (payload.status) {
             setStatus(payload.status as any, payload.message);
@@ -62,11 +92,12 @@ export function OverlayApp() {
     return () => {
       unlisten.then((fn) => fn());
     };
-  }, [setCursorPosition, setAiControlling, addClickEffect, setStatus]);
+  }, [setCursorPosition, setAiControlling, addClickEffect, setPressedButton, addDragTrail, setStatus]);
 
   return (
     <div className="w-screen h-screen overflow-hidden" style={{ background: 'transparent' }}>
       <CursorGlow />
+      <DragPath />
       <ClickRipple />
       <StatusIndicator />
     </div>
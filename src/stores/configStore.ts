@@ -4,13 +4,106 @@ import { create } from 'zustand';
 import { persist } from 'zustand/middleware';
 import { invoke } from '@tauri-apps/api/core';
 
+export type ProviderKind = 'openai' | 'anthropic' | 'local';
+export type ToolCallMode = 'native' | 'text';
+
 export interface ApiConfig {
+  provider?: ProviderKind;
   endpoint: string;
   api_key: string;
   model: string;
   supports_vision: boolean;
   max_tokens: number;
   temperature: number;
+  stream?: boolean;
+  tool_mode?: ToolCallMode;
+  timeout_secs?: number;
+  connect_timeout_secs?: number;
+  retry?: RetryConfig;
+}
+
+export interface RetryConfig {
+  max_retries: number;
+  initial_backoff_ms: number;
+  max_backoff_ms: number;
+  multiplier: number;
+}
+
+export interface ModelPrice {
+  model: string;
+  input_per_mtok: number;
+  output_per_mtok: number;
+}
+
+export interface UsageConfig {
+  prices: ModelPrice[];
+  task_budget_usd: number | null;
+  daily_budget_usd: number | null;
+}
+
+export interface ContextConfig {
+  max_screenshots: number;
+  token_budget: number;
+  compact_threshold: number;
+  keep_recent_messages: number;
+}
+
+export interface RunBudget {
+  max_iterations: number;
+  max_duration_secs: number | null;
+  max_tool_calls: Record<string, number>;
+  max_sub_agent_depth: number;
+  sub_agent_max_iterations: number;
+}
+
+export type PolicyAction = 'allow' | 'ask' | 'deny';
+
+export interface PolicyRule {
+  action: PolicyAction;
+  tool?: string | null;
+  keys?: string | null;
+  text_pattern?: string | null;
+  window_pattern?: string | null;
+  description?: string | null;
+}
+
+export interface ApprovalConfig {
+  enabled: boolean;
+  default_action: PolicyAction;
+  timeout_secs: number;
+  rules: PolicyRule[];
+}
+
+export interface VerificationConfig {
+  enabled: boolean;
+  settle_ms: number;
+  pixel_threshold: number;
+  max_regions: number;
+}
+
+export interface StallConfig {
+  enabled: boolean;
+  repeat_limit: number;
+  oscillation_cycles: number;
+  unchanged_limit: number;
+  max_interventions: number;
+}
+
+export type ToolCategory = 'mouse' | 'keyboard' | 'screen' | 'plan' | 'agent';
+
+export interface ToolProfile {
+  categories: ToolCategory[];
+  disabled: string[];
+  allow_input: boolean;
+}
+
+export interface ToolsConfig {
+  default_profile: string;
+  profiles: Record<string, ToolProfile>;
+}
+
+export interface TranscriptConfig {
+  retention_days: number;
 }
 
 export interface AppConfig {
@@ -18,6 +111,15 @@ export interface AppConfig {
   language: string;
   theme: string;
   global_shortcut: string;
+  emergency_stop_shortcut: string;
+  usage?: UsageConfig;
+  context?: ContextConfig;
+  budget?: RunBudget;
+  approval?: ApprovalConfig;
+  verification?: VerificationConfig;
+  stall?: StallConfig;
+  tools?: ToolsConfig;
+  transcripts?: TranscriptConfig;
 }
 
 interface ConfigState {
@@ -34,16 +136,28 @@ interface ConfigState {
 
 const defaultConfig: AppConfig = {
   api: {
+    provider: 'openai',
     endpoint: 'https://api.openai.com/v1',
     api_key: '',
     model: 'gpt-4o',
     supports_vision: true,
     max_tokens: 4096,
     temperature: 0.7,
+    stream: true,
+    tool_mode: 'native',
+    timeout_secs: 120,
+    connect_timeout_secs: 10,
+    retry: {
+      max_retries: 3,
+      initial_backoff_ms: 1000,
+      max_backoff_ms: 30000,
+      multiplier: 2.0,
+    },
   },
   language: 'ko',
   theme: 'dark',
   global_shortcut: 'Shift+Alt+A',
+  emergency_stop_shortcut: 'Shift+Alt+X',
 };
 
 export const useConfigStore = create<ConfigState>()(
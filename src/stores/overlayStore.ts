@@ -14,6 +14,13 @@ export interface ClickEffect {
   button: 'left' | 'right' | 'middle';
 }
 
+export interface DragTrail {
+  id: string;
+  from: CursorPosition;
+  to: CursorPosition;
+  button: 'left' | 'right' | 'middle';
+}
+
 export type OverlayStatus = 'idle' | 'thinking' | 'moving' | 'clicking' | 'typing' | 'scrolling';
 
 interface OverlayState {
@@ -25,6 +32,10 @@ interface OverlayState {
   // Click effects
   clickEffects: ClickEffect[];
 
+  // Held mouse button and drag paths
+  pressedButton: 'left' | 'right' | 'middle' | null;
+  dragTrails: DragTrail[];
+
   // Status
   status: OverlayStatus;
   statusMessage: string;
@@ -34,6 +45,8 @@ interface OverlayState {
   setAiControlling: (controlling: boolean) => void;
   addClickEffect: (x: number, y: number, button?: 'left' | 'right' | 'middle') => void;
   removeClickEffect: (id: string) => void;
+  setPressedButton: (button: 'left' | 'right' | 'middle' | null) => void;
+  addDragTrail: (from: CursorPosition, to: CursorPosition, button?: 'left' | 'right' | 'middle') => void;
   setStatus: (status: OverlayStatus, message?: string) => void;
   reset: () => void;
 }
@@ -45,6 +58,8 @@ export const useOverlayStore = create<OverlayState>()((set) => ({
   isAiControlling: false,
   showCursorGlow: true,
   clickEffects: [],
+  pressedButton: null,
+  dragTrails: [],
   status: 'idle',
   statusMessage: '',
 
@@ -71,6 +86,22 @@ export const useOverlayStore = create<OverlayState>()((set) => ({
       clickEffects: state.clickEffects.filter((e) => e.id !== id),
     })),
 
+  setPressedButton: (button) => set({ pressedButton: button }),
+
+  addDragTrail: (from, to, button = 'left') => {
+    const id = generateId();
+    set((state) => ({
+      dragTrails: [...state.dragTrails, { id, from, to, button }],
+    }));
+
+    // Auto-remove after the path has been shown
+    setTimeout(() => {
+      set((state) => ({
+        dragTrails: state.dragTrails.filter((t) => t.id !== id),
+      }));
+    }, 1500);
+  },
+
   setStatus: (status, message = '') =>
     set({ status, statusMessage: message }),
 
@@ -78,6 +109,8 @@ export const useOverlayStore = create<OverlayState>()((set) => ({
     set({
       isAiControlling: false,
       clickEffects: [],
+      pressedButton: null,
+      dragTrails: [],
       status: 'idle',
       statusMessage: '',
     }),